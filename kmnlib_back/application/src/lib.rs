@@ -1,2 +1,3 @@
+pub mod policy;
 pub mod service;
 pub mod transfer;
@@ -0,0 +1,211 @@
+use error_stack::Report;
+use kernel::prelude::entity::{ApiKeyScope, UserId, UserRole};
+use kernel::KernelError;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Permission<'a> {
+    ManageBooks,
+    /// `target` is the role of the account acted on, `None` for one not created yet
+    ManageUsers {
+        target: Option<UserRole>,
+    },
+    ChangeRole,
+    Rent {
+        user_id: &'a UserId,
+    },
+    ChangePassword {
+        user_id: &'a UserId,
+    },
+    /// `submitter` is who queued the job
+    ViewJob {
+        submitter: Option<&'a UserId>,
+    },
+    ViewQueue,
+    ManageQueue,
+    ManageApiKeys,
+}
+
+impl Permission<'_> {
+    /// Scope an api key needs on top of its owner's role, `None` if keys may never do this
+    fn scope(&self) -> Option<ApiKeyScope> {
+        match self {
+            Permission::ManageBooks => Some(ApiKeyScope::Books),
+            Permission::ManageUsers { .. } => Some(ApiKeyScope::Users),
+            Permission::Rent { .. } => Some(ApiKeyScope::Rents),
+            Permission::ViewJob { .. } | Permission::ViewQueue | Permission::ManageQueue => {
+                Some(ApiKeyScope::Queue)
+            }
+            Permission::ChangeRole
+            | Permission::ChangePassword { .. }
+            | Permission::ManageApiKeys => None,
+        }
+    }
+}
+
+/// Caller a [`Permission`] is checked against
+#[derive(Debug, Clone)]
+pub struct Actor {
+    id: UserId,
+    role: UserRole,
+    scopes: Option<Vec<ApiKeyScope>>,
+}
+
+impl Actor {
+    pub fn new(id: UserId, role: UserRole) -> Self {
+        Self {
+            id,
+            role,
+            scopes: None,
+        }
+    }
+
+    /// Acts as the key owner, but only within `scopes`
+    pub fn api_key(owner_id: UserId, role: UserRole, scopes: Vec<ApiKeyScope>) -> Self {
+        Self {
+            id: owner_id,
+            role,
+            scopes: Some(scopes),
+        }
+    }
+
+    pub fn id(&self) -> &UserId {
+        &self.id
+    }
+
+    pub fn role(&self) -> UserRole {
+        self.role
+    }
+
+    pub fn authorize(&self, permission: Permission) -> error_stack::Result<(), KernelError> {
+        let staff = matches!(self.role, UserRole::Librarian | UserRole::Admin);
+        let denied = match permission {
+            Permission::ManageBooks if !staff => "Only librarians can manage books",
+            Permission::ManageUsers { .. } if !staff => "Only librarians can edit users",
+            Permission::ManageUsers {
+                target: Some(UserRole::Librarian | UserRole::Admin),
+            } if self.role != UserRole::Admin => {
+                "Only admins can manage librarian and admin accounts"
+            }
+            Permission::ChangeRole if self.role != UserRole::Admin => {
+                "Only admins can change roles"
+            }
+            Permission::Rent { user_id } if !staff && user_id != &self.id => {
+                "Members can only rent and return for themselves"
+            }
+            Permission::ChangePassword { user_id } if user_id != &self.id => {
+                "Passwords can only be changed by their owner"
+            }
+            // Whoever queued a job may follow it, whatever scopes their key has
+            Permission::ViewJob { submitter } if submitter == Some(&self.id) => return Ok(()),
+            Permission::ViewJob { .. } if !staff => "Only librarians can view jobs of others",
+            Permission::ViewQueue if self.role != UserRole::Admin => {
+                "Only admins can view the queue"
+            }
+            Permission::ManageQueue if self.role != UserRole::Admin => {
+                "Only admins can manage the queue"
+            }
+            Permission::ManageApiKeys if self.role != UserRole::Admin => {
+                "Only admins can manage api keys"
+            }
+            _ => match (&self.scopes, permission.scope()) {
+                (None, _) => return Ok(()),
+                (Some(scopes), Some(scope)) if scopes.contains(&scope) => return Ok(()),
+                (Some(_), Some(_)) => "Api key is missing the required scope",
+                (Some(_), None) => "Api keys are not allowed to do this",
+            },
+        };
+        Err(Report::new(KernelError::PermissionDenied(
+            denied.to_string(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use kernel::prelude::entity::{ApiKeyScope, UserId, UserRole};
+    use uuid::Uuid;
+
+    use crate::policy::{Actor, Permission};
+
+    fn actor(role: UserRole) -> Actor {
+        Actor::new(UserId::new(Uuid::new_v4()), role)
+    }
+
+    #[test]
+    fn test_manage_books() {
+        let member = actor(UserRole::Member);
+        assert!(member.authorize(Permission::ManageBooks).is_err());
+        let librarian = actor(UserRole::Librarian);
+        assert!(librarian.authorize(Permission::ManageBooks).is_ok());
+        let admin = actor(UserRole::Admin);
+        assert!(admin.authorize(Permission::ManageBooks).is_ok());
+    }
+
+    #[test]
+    fn test_rent_for_others() {
+        let member = actor(UserRole::Member);
+        let other = UserId::new(Uuid::new_v4());
+        let own = Permission::Rent {
+            user_id: member.id(),
+        };
+        let others = Permission::Rent { user_id: &other };
+        assert!(member.authorize(own).is_ok());
+        assert!(member.authorize(others).is_err());
+        assert!(actor(UserRole::Librarian).authorize(others).is_ok());
+    }
+
+    #[test]
+    fn test_admin_permissions() {
+        for permission in [Permission::ChangeRole, Permission::ViewQueue] {
+            assert!(actor(UserRole::Member).authorize(permission).is_err());
+            assert!(actor(UserRole::Librarian).authorize(permission).is_err());
+            assert!(actor(UserRole::Admin).authorize(permission).is_ok());
+        }
+        let users = Permission::ManageUsers { target: None };
+        assert!(actor(UserRole::Member).authorize(users).is_err());
+        assert!(actor(UserRole::Librarian).authorize(users).is_ok());
+        let staff = Permission::ManageUsers {
+            target: Some(UserRole::Librarian),
+        };
+        assert!(actor(UserRole::Librarian).authorize(staff).is_err());
+        assert!(actor(UserRole::Admin).authorize(staff).is_ok());
+    }
+
+    #[test]
+    fn test_api_key_change_role() {
+        let id = UserId::new(Uuid::new_v4());
+        let admin = Actor::new(id.clone(), UserRole::Admin);
+        assert!(admin.authorize(Permission::ChangeRole).is_ok());
+
+        let key = Actor::api_key(id, UserRole::Admin, vec![ApiKeyScope::Users]);
+        assert!(key
+            .authorize(Permission::ManageUsers { target: None })
+            .is_ok());
+        assert!(key.authorize(Permission::ChangeRole).is_err());
+    }
+
+    #[test]
+    fn test_view_job() {
+        let submitter = UserId::new(Uuid::new_v4());
+        let member = Actor::new(submitter.clone(), UserRole::Member);
+        let other = Actor::new(UserId::new(Uuid::new_v4()), UserRole::Member);
+        let librarian = Actor::new(UserId::new(Uuid::new_v4()), UserRole::Librarian);
+        let job = Permission::ViewJob {
+            submitter: Some(&submitter),
+        };
+        assert!(member.authorize(job).is_ok());
+        assert!(other.authorize(job).is_err());
+        assert!(librarian.authorize(job).is_ok());
+
+        let scheduled = Permission::ViewJob { submitter: None };
+        assert!(member.authorize(scheduled).is_err());
+        assert!(librarian.authorize(scheduled).is_ok());
+
+        let key = Actor::api_key(
+            submitter.clone(),
+            UserRole::Member,
+            vec![ApiKeyScope::Books],
+        );
+        assert!(key.authorize(job).is_ok());
+    }
+}
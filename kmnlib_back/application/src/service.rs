@@ -1,6 +1,9 @@
+mod api_key;
+mod auth;
 mod book;
-
+mod idempotency;
+mod rate_limit;
 mod rent;
 mod user;
 
-pub use self::{book::*, rent::*, user::*};
+pub use self::{api_key::*, auth::*, book::*, idempotency::*, rate_limit::*, rent::*, user::*};
@@ -0,0 +1,124 @@
+use kernel::interface::database::{DatabaseConnection, Transaction};
+use kernel::interface::query::{ApiKeyQuery, DependOnApiKeyQuery};
+use kernel::interface::update::{ApiKeyModifier, DependOnApiKeyModifier};
+use kernel::prelude::entity::{ApiKey, ApiKeyHash, ApiKeyId, ApiKeyRevokedAt, CreatedAt};
+use kernel::KernelError;
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::service::GetUserService;
+use crate::transfer::{CreateApiKeyDto, GetAllApiKeyDto, GetUserDto, RevokeApiKeyDto};
+
+const API_KEY_PREFIX: &str = "kmn_";
+
+#[async_trait::async_trait]
+pub trait ApiKeyService:
+    'static + Sync + Send + DependOnApiKeyQuery + DependOnApiKeyModifier + GetUserService
+{
+    /// Returns the stored key with its raw value, or `None` if the owner does not exist
+    #[tracing::instrument(skip_all)]
+    async fn create_api_key(
+        &self,
+        CreateApiKeyDto {
+            owner_id,
+            name,
+            scopes,
+            expires_at,
+        }: CreateApiKeyDto,
+    ) -> error_stack::Result<Option<(ApiKey, String)>, KernelError> {
+        let owner = self.get_user(&GetUserDto { id: owner_id }).await?;
+        let Some(owner) = owner.filter(|owner| !owner.is_deleted().as_ref()) else {
+            return Ok(None);
+        };
+        let raw = format!(
+            "{API_KEY_PREFIX}{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+        let api_key = ApiKey::new(
+            ApiKeyId::new(Uuid::new_v4()),
+            owner.id().clone(),
+            name,
+            hash(&raw),
+            scopes,
+            expires_at,
+            None,
+            CreatedAt::new(OffsetDateTime::now_utc()),
+        );
+
+        let mut connection = self.database_connection().transact().await?;
+        self.api_key_modifier()
+            .create(&mut connection, &api_key)
+            .await?;
+        connection.commit().await?;
+
+        Ok(Some((api_key, raw)))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_api_keys(
+        &self,
+        GetAllApiKeyDto { limit, offset }: GetAllApiKeyDto,
+    ) -> error_stack::Result<Vec<ApiKey>, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.api_key_query()
+            .get_all(&mut connection, &limit, &offset)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn revoke_api_key(
+        &self,
+        RevokeApiKeyDto { id }: RevokeApiKeyDto,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        let Some(mut api_key) = self
+            .api_key_query()
+            .find_by_id(&mut connection, &id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        if api_key.revoked_at().is_none() {
+            api_key.substitute(|key| {
+                *key.revoked_at = Some(ApiKeyRevokedAt::new(OffsetDateTime::now_utc()))
+            });
+            self.api_key_modifier()
+                .update(&mut connection, &api_key)
+                .await?;
+        }
+        connection.commit().await?;
+        Ok(Some(api_key))
+    }
+
+    /// Looks up a raw key, ignoring revoked and expired ones
+    #[tracing::instrument(skip_all)]
+    async fn find_active_api_key(
+        &self,
+        raw: &str,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        if !raw.starts_with(API_KEY_PREFIX) {
+            return Ok(None);
+        }
+        let mut connection = self.database_connection().transact().await?;
+        let api_key = self
+            .api_key_query()
+            .find_by_hash(&mut connection, &hash(raw))
+            .await?;
+        let now = OffsetDateTime::now_utc();
+        Ok(api_key.filter(|api_key| api_key.is_active(&now)))
+    }
+}
+
+impl<T> ApiKeyService for T where T: DependOnApiKeyQuery + DependOnApiKeyModifier + GetUserService {}
+
+fn hash(raw: &str) -> ApiKeyHash {
+    let digest = Sha256::digest(raw.as_bytes());
+    ApiKeyHash::new(
+        digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>(),
+    )
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use error_stack::Report;
+use kernel::interface::database::DatabaseConnection;
+use kernel::interface::event::{EventMetadata, UserEvent};
+use kernel::interface::session::{
+    DependOnPasswordResetDelivery, DependOnPasswordResetStore, DependOnTokenRevocation,
+    PasswordResetDelivery, PasswordResetStore, TokenRevocation,
+};
+use kernel::prelude::entity::{PasswordResetToken, TokenId, User, UserId, UserPasswordHash};
+use kernel::KernelError;
+use uuid::Uuid;
+
+use crate::service::{GetUserService, HandleUserService};
+use crate::transfer::{ChangePasswordDto, GetUserDto, LoginDto, ResetPasswordDto};
+
+pub const PASSWORD_RESET_TTL: Duration = Duration::from_secs(60 * 60);
+
+#[async_trait::async_trait]
+pub trait CredentialService: 'static + Sync + Send + GetUserService + HandleUserService {
+    /// Returns the user only if the password matches
+    #[tracing::instrument(skip_all)]
+    async fn login(
+        &self,
+        LoginDto { id, password }: LoginDto,
+    ) -> error_stack::Result<Option<User>, KernelError> {
+        let user = self.get_user(&GetUserDto { id }).await?;
+        Ok(user.filter(|user| !user.is_deleted().as_ref() && matches(user, &password)))
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn change_password(
+        &self,
+        ChangePasswordDto {
+            id,
+            current_password,
+            new_password,
+        }: ChangePasswordDto,
+        metadata: EventMetadata,
+    ) -> error_stack::Result<UserId, KernelError> {
+        let user = self.get_user(&GetUserDto { id: id.clone() }).await?;
+        if !user.is_some_and(|user| matches(&user, &current_password)) {
+            return Err(Report::new(KernelError::PermissionDenied(
+                "Current password does not match".to_string(),
+            )));
+        }
+        let password = hash(&new_password)?;
+        self.handle_user_event(UserEvent::ChangePassword { id, password }, None, metadata)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn reset_password(
+        &self,
+        ResetPasswordDto { id, new_password }: ResetPasswordDto,
+        metadata: EventMetadata,
+    ) -> error_stack::Result<UserId, KernelError> {
+        let password = hash(&new_password)?;
+        self.handle_user_event(UserEvent::ResetPassword { id, password }, None, metadata)
+            .await
+    }
+}
+
+impl<T> CredentialService for T where T: GetUserService + HandleUserService {}
+
+fn hash(password: &str) -> error_stack::Result<UserPasswordHash, KernelError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|error| {
+            Report::new(KernelError::Internal)
+                .attach_printable(format!("Failed to hash password: {error}"))
+        })?;
+    Ok(UserPasswordHash::new(hash.to_string()))
+}
+
+fn matches(user: &User, password: &str) -> bool {
+    user.password()
+        .as_ref()
+        .and_then(|hash| PasswordHash::new(hash.as_ref()).ok())
+        .is_some_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+}
+
+#[async_trait::async_trait]
+pub trait SessionService:
+    'static
+    + Sync
+    + Send
+    + DependOnTokenRevocation
+    + DependOnPasswordResetStore
+    + DependOnPasswordResetDelivery
+{
+    /// Returns `false` when the token was already revoked
+    #[tracing::instrument(skip_all)]
+    async fn revoke_token(
+        &self,
+        id: &TokenId,
+        ttl: &Duration,
+    ) -> error_stack::Result<bool, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.token_revocation()
+            .revoke(&mut connection, id, ttl)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn is_token_revoked(&self, id: &TokenId) -> error_stack::Result<bool, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.token_revocation()
+            .is_revoked(&mut connection, id)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn token_generation(&self, user_id: &UserId) -> error_stack::Result<u64, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.token_revocation()
+            .generation(&mut connection, user_id)
+            .await
+    }
+
+    /// Signs the user out everywhere, e.g. after the password changed
+    #[tracing::instrument(skip_all)]
+    async fn revoke_user_tokens(&self, user_id: &UserId) -> error_stack::Result<(), KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.token_revocation()
+            .revoke_all(&mut connection, user_id)
+            .await?;
+        Ok(())
+    }
+
+    /// The token is delivered to the user and only its lifetime is returned
+    #[tracing::instrument(skip_all)]
+    async fn issue_password_reset(
+        &self,
+        user_id: &UserId,
+    ) -> error_stack::Result<Duration, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        let token = PasswordResetToken::new(Uuid::new_v4());
+        self.password_reset_store()
+            .issue(&mut connection, &token, user_id, &PASSWORD_RESET_TTL)
+            .await?;
+        self.password_reset_delivery()
+            .deliver(&mut connection, &token, user_id, &PASSWORD_RESET_TTL)
+            .await?;
+        Ok(PASSWORD_RESET_TTL)
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn consume_password_reset(
+        &self,
+        token: &PasswordResetToken,
+    ) -> error_stack::Result<Option<UserId>, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.password_reset_store()
+            .consume(&mut connection, token)
+            .await
+    }
+}
+
+impl<T> SessionService for T where
+    T: DependOnTokenRevocation + DependOnPasswordResetStore + DependOnPasswordResetDelivery
+{
+}
+
+#[cfg(test)]
+mod test {
+    use kernel::prelude::entity::{
+        EventVersion, IsDeleted, User, UserId, UserName, UserRentLimit, UserRole,
+    };
+    use kernel::KernelError;
+    use uuid::Uuid;
+
+    use crate::service::auth::{hash, matches};
+
+    fn user(password: &str) -> error_stack::Result<User, KernelError> {
+        Ok(User::new(
+            UserId::new(Uuid::new_v4()),
+            UserName::new("test".to_string()),
+            UserRentLimit::new(1),
+            UserRole::Member,
+            Some(hash(password)?),
+            EventVersion::new(1),
+            IsDeleted::new(false),
+        ))
+    }
+
+    #[test]
+    fn test_matches() -> error_stack::Result<(), KernelError> {
+        let user = user("correct horse")?;
+        assert!(matches(&user, "correct horse"));
+        assert!(!matches(&user, "battery staple"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_salt() -> error_stack::Result<(), KernelError> {
+        assert_ne!(hash("password")?, hash("password")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_without_password() {
+        let user = User::new(
+            UserId::new(Uuid::new_v4()),
+            UserName::new("test".to_string()),
+            UserRentLimit::new(1),
+            UserRole::Member,
+            None,
+            EventVersion::new(1),
+            IsDeleted::new(false),
+        );
+        assert!(!matches(&user, ""));
+    }
+}
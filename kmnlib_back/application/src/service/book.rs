@@ -1,25 +1,29 @@
+use error_stack::Report;
 use kernel::interface::database::{DatabaseConnection, Transaction};
-use kernel::interface::event::{Applier, BookEvent, CommandInfo};
+use kernel::interface::event::{Applier, BookEvent, CommandInfo, EventMetadata};
 use kernel::interface::query::{
     BookEventQuery, BookQuery, DependOnBookEventQuery, DependOnBookQuery,
 };
 use kernel::interface::update::{
     BookEventHandler, BookModifier, DependOnBookEventHandler, DependOnBookModifier,
 };
-use kernel::prelude::entity::{Book, BookId};
+use kernel::prelude::entity::{Book, BookId, EventVersion, ExpectedEventVersion};
 use kernel::KernelError;
 
 use crate::transfer::{GetAllBookDto, GetBookDto};
 
 #[async_trait::async_trait]
 pub trait HandleBookService: 'static + Sync + Send + DependOnBookEventHandler {
+    #[tracing::instrument(skip_all)]
     async fn handle_book_event(
         &self,
         event: BookEvent,
+        version: Option<ExpectedEventVersion<Book>>,
+        metadata: EventMetadata,
     ) -> error_stack::Result<BookId, KernelError> {
         let mut connection = self.database_connection().transact().await?;
 
-        let command = CommandInfo::new(event, None);
+        let command = CommandInfo::new(event, version, metadata);
         let id = self
             .book_event_handler()
             .handle(&mut connection, command)
@@ -37,6 +41,7 @@ impl<T> HandleBookService for T where T: DependOnBookEventHandler {}
 pub trait GetBookService:
     'static + Sync + Send + DependOnBookQuery + DependOnBookModifier + DependOnBookEventQuery
 {
+    #[tracing::instrument(skip_all)]
     async fn get_all(
         &self,
         GetAllBookDto { limit, offset }: &GetAllBookDto,
@@ -64,6 +69,7 @@ pub trait GetBookService:
         Ok(books)
     }
 
+    #[tracing::instrument(skip_all)]
     async fn get_book(
         &self,
         GetBookDto { id }: &GetBookDto,
@@ -91,6 +97,24 @@ pub trait GetBookService:
 
         Ok(book)
     }
+
+    /// Fails with `PreconditionFailed` unless the book is still at `expected`
+    #[tracing::instrument(skip_all)]
+    async fn check_book_version(
+        &self,
+        dto: &GetBookDto,
+        expected: &EventVersion<Book>,
+    ) -> error_stack::Result<(), KernelError> {
+        match self.get_book(dto).await? {
+            Some(book) if book.version() == expected => Ok(()),
+            current => Err(
+                Report::new(KernelError::PreconditionFailed).attach_printable(format!(
+                    "Expected version {expected:?}, found {:?}",
+                    current.as_ref().map(Book::version)
+                )),
+            ),
+        }
+    }
 }
 
 impl<T> GetBookService for T where
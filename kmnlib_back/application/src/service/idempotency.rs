@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use kernel::interface::database::DatabaseConnection;
+use kernel::interface::idempotency::{DependOnIdempotencyStore, IdempotencyStore};
+use kernel::prelude::entity::{IdempotencyKey, IdempotencyRecord};
+use kernel::KernelError;
+
+/// How long a key and its response are kept for replays
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[async_trait::async_trait]
+pub trait IdempotencyService: 'static + Sync + Send + DependOnIdempotencyStore {
+    /// Returns the existing record if the key has been used before.
+    /// The key is only held for `ttl` until it is completed, so an abandoned request frees it
+    #[tracing::instrument(skip_all)]
+    async fn reserve_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+        ttl: &Duration,
+    ) -> error_stack::Result<Option<IdempotencyRecord>, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.idempotency_store()
+            .reserve(&mut connection, key, record, ttl)
+            .await
+    }
+
+    /// Keeps the key for [`IDEMPOTENCY_TTL`] from now on
+    #[tracing::instrument(skip_all)]
+    async fn complete_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+    ) -> error_stack::Result<(), KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.idempotency_store()
+            .complete(&mut connection, key, record, &IDEMPOTENCY_TTL)
+            .await
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn release_idempotency_key(
+        &self,
+        key: &IdempotencyKey,
+    ) -> error_stack::Result<(), KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.idempotency_store().release(&mut connection, key).await
+    }
+}
+
+impl<T> IdempotencyService for T where T: DependOnIdempotencyStore {}
@@ -0,0 +1,21 @@
+use kernel::interface::database::DatabaseConnection;
+use kernel::interface::rate_limit::{DependOnRateLimiter, RateLimiter};
+use kernel::prelude::entity::{RateLimitDecision, RateLimitKey, RateLimitQuota};
+use kernel::KernelError;
+
+#[async_trait::async_trait]
+pub trait RateLimitService: 'static + Sync + Send + DependOnRateLimiter {
+    #[tracing::instrument(skip_all)]
+    async fn acquire_rate_limit(
+        &self,
+        key: &RateLimitKey,
+        quota: &RateLimitQuota,
+    ) -> error_stack::Result<RateLimitDecision, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        self.rate_limiter()
+            .acquire(&mut connection, key, quota)
+            .await
+    }
+}
+
+impl<T> RateLimitService for T where T: DependOnRateLimiter {}
@@ -1,86 +1,65 @@
 use crate::service::{GetBookService, GetUserService};
 use crate::transfer::{
-    GetBookDto, GetRentFromBookIdDto, GetRentFromIdDto, GetRentFromUserIdDto, GetUserDto,
+    GetBookDto, GetOverdueRentsDto, GetRentFromBookIdDto, GetRentFromIdDto, GetRentFromUserIdDto,
+    GetUserDto,
 };
 use error_stack::Report;
 use kernel::interface::database::{DatabaseConnection, Transaction};
-use kernel::interface::event::{CommandInfo, DestructEventInfo, EventInfo, RentEvent};
+use kernel::interface::event::{
+    CommandInfo, DestructEventInfo, EventInfo, EventMetadata, RentEvent,
+};
 use kernel::interface::query::{
     DependOnRentEventQuery, DependOnRentQuery, RentEventQuery, RentQuery,
 };
 use kernel::interface::update::{
     DependOnRentEventHandler, DependOnRentModifier, RentEventHandler, RentModifier,
 };
-use kernel::prelude::entity::{EventVersion, ExpectedEventVersion, Rent, ReturnedAt};
+use kernel::prelude::entity::{Book, CreatedAt, ExpectedEventVersion, Rent, ReturnedAt, User};
 use kernel::KernelError;
 
 #[async_trait::async_trait]
 pub trait HandleRentService:
     'static + Sync + Send + DependOnRentEventHandler + GetRentService + GetUserService + GetBookService
 {
-    async fn handle_rent_event(&self, event: RentEvent) -> error_stack::Result<(), KernelError> {
+    #[tracing::instrument(skip_all)]
+    async fn handle_rent_event(
+        &self,
+        event: RentEvent,
+        metadata: EventMetadata,
+    ) -> error_stack::Result<(), KernelError> {
         let command =
             match event {
                 RentEvent::Rent { book_id, user_id } => {
                     let dto = GetRentFromIdDto { book_id, user_id };
                     let rents = self.get_rents_from_id(&dto).await?;
                     let rent = rents.last();
-                    if rent.is_some() && rent.unwrap().returned_at().is_some() {
-                        return Err(Report::new(KernelError::Concurrency).attach_printable(
-                            format!(
-                                "Target Book({:?}) already rented. User:{:?}",
-                                dto.book_id, dto.user_id
-                            ),
-                        ));
-                    }
                     let book_id_dto = GetBookDto { id: dto.book_id };
                     let book = self.get_book(&book_id_dto).await?;
-                    if book.is_none() {
-                        return Err(Report::new(KernelError::Concurrency).attach_printable(
+                    let Some(book) = book else {
+                        return Err(Report::new(KernelError::Ineligible).attach_printable(
                             format!("Target Book({:?}) does not exists", book_id_dto.id),
                         ));
-                    }
-                    let book = book.unwrap();
+                    };
                     let book_id_dto = GetRentFromBookIdDto {
                         book_id: book_id_dto.id,
                     };
                     let book_rents = self.get_rent_from_book(&book_id_dto).await?;
-                    if book_rents.len() >= *book.amount().as_ref() as usize {
-                        return Err(Report::new(KernelError::Concurrency).attach_printable(
-                            format!(
-                                "Book({:?}) amount({:?}) is exceeded.",
-                                book.id(),
-                                book.amount()
-                            ),
-                        ));
-                    }
 
                     let user_id_dto = GetUserDto { id: dto.user_id };
                     let user = self.get_user(&user_id_dto).await?;
-                    if user.is_none() {
-                        return Err(Report::new(KernelError::Concurrency).attach_printable(
+                    let Some(user) = user else {
+                        return Err(Report::new(KernelError::Ineligible).attach_printable(
                             format!("Target User({:?}) does not exists", user_id_dto.id),
                         ));
-                    }
-                    let user = user.unwrap();
+                    };
                     let user_id_dto = GetRentFromUserIdDto {
                         user_id: user_id_dto.id,
                     };
                     let user_rents = self.get_rents_from_user(&user_id_dto).await?;
-                    if user_rents.len() <= *user.rent_limit().as_ref() as usize {
-                        return Err(Report::new(KernelError::Concurrency).attach_printable(
-                            format!(
-                                "User({:?}) rent limit({:?}) is exceeded.",
-                                user.id(),
-                                user.rent_limit()
-                            ),
-                        ));
-                    }
+                    check_rent(&book, &user, rent, &book_rents, &user_rents)?;
                     let expected_version = match rent {
                         None => ExpectedEventVersion::Nothing,
-                        Some(rent) => ExpectedEventVersion::Exact(EventVersion::new(
-                            rent.version().as_ref() + 1,
-                        )),
+                        Some(rent) => ExpectedEventVersion::Exact(rent.last_version().clone()),
                     };
                     CommandInfo::new(
                         RentEvent::Rent {
@@ -88,6 +67,7 @@ pub trait HandleRentService:
                             user_id: user_id_dto.user_id,
                         },
                         Some(expected_version),
+                        metadata,
                     )
                 }
                 RentEvent::Return { book_id, user_id } => {
@@ -96,7 +76,7 @@ pub trait HandleRentService:
                     let mut rent = rents.last();
                     match &mut rent {
                         None => {
-                            return Err(Report::new(KernelError::Concurrency).attach_printable(
+                            return Err(Report::new(KernelError::Ineligible).attach_printable(
                                 format!(
                                     "Target book({:?}) rent log not found. User: {:?}",
                                     dto.book_id, dto.user_id
@@ -105,21 +85,22 @@ pub trait HandleRentService:
                         }
                         Some(rent) => {
                             if rent.returned_at().is_some() {
-                                return Err(Report::new(KernelError::Concurrency)
-                                    .attach_printable(format!(
+                                return Err(Report::new(KernelError::Ineligible).attach_printable(
+                                    format!(
                                         "Target book({:?}) is already returned. User: {:?}",
                                         dto.book_id, dto.user_id
-                                    )));
-                            } else {
-                                let version = ExpectedEventVersion::Exact(EventVersion::new(
-                                    rent.version().as_ref() + 1,
+                                    ),
                                 ));
+                            } else {
+                                let version =
+                                    ExpectedEventVersion::Exact(rent.last_version().clone());
                                 CommandInfo::new(
                                     RentEvent::Return {
                                         book_id: dto.book_id,
                                         user_id: dto.user_id,
                                     },
                                     Some(version),
+                                    metadata,
                                 )
                             }
                         }
@@ -137,6 +118,46 @@ pub trait HandleRentService:
     }
 }
 
+/// Rejects a rent of `book` by `user` while the user still has it, all copies are out
+/// or the user is at their limit. Only rents not returned yet count
+fn check_rent(
+    book: &Book,
+    user: &User,
+    rent: Option<&Rent>,
+    book_rents: &[Rent],
+    user_rents: &[Rent],
+) -> error_stack::Result<(), KernelError> {
+    let is_out = |rent: &&Rent| rent.returned_at().is_none();
+    if rent.is_some_and(|rent| is_out(&rent)) {
+        return Err(
+            Report::new(KernelError::Ineligible).attach_printable(format!(
+                "Target Book({:?}) already rented. User:{:?}",
+                book.id(),
+                user.id()
+            )),
+        );
+    }
+    if book_rents.iter().filter(is_out).count() >= *book.amount().as_ref() as usize {
+        return Err(
+            Report::new(KernelError::Ineligible).attach_printable(format!(
+                "Book({:?}) amount({:?}) is exceeded.",
+                book.id(),
+                book.amount()
+            )),
+        );
+    }
+    if user_rents.iter().filter(is_out).count() >= *user.rent_limit().as_ref() as usize {
+        return Err(
+            Report::new(KernelError::Ineligible).attach_printable(format!(
+                "User({:?}) rent limit({:?}) is exceeded.",
+                user.id(),
+                user.rent_limit()
+            )),
+        );
+    }
+    Ok(())
+}
+
 impl<T> HandleRentService for T where
     T: DependOnRentEventHandler + GetRentService + GetUserService + GetBookService
 {
@@ -146,6 +167,7 @@ impl<T> HandleRentService for T where
 pub trait GetRentService:
     'static + Sync + Send + DependOnRentQuery + DependOnRentEventQuery + DependOnRentModifier
 {
+    #[tracing::instrument(skip_all)]
     async fn get_rent_from_book(
         &self,
         GetRentFromBookIdDto { book_id }: &GetRentFromBookIdDto,
@@ -175,6 +197,7 @@ pub trait GetRentService:
         Ok(rents)
     }
 
+    #[tracing::instrument(skip_all)]
     async fn get_rents_from_user(
         &self,
         GetRentFromUserIdDto { user_id }: &GetRentFromUserIdDto,
@@ -204,6 +227,7 @@ pub trait GetRentService:
         Ok(rents)
     }
 
+    #[tracing::instrument(skip_all)]
     async fn get_rents_from_id(
         &self,
         GetRentFromIdDto { book_id, user_id }: &GetRentFromIdDto,
@@ -232,6 +256,21 @@ pub trait GetRentService:
 
         Ok(rents)
     }
+
+    /// Rent events of books still out that were rented before `rented_before`
+    #[tracing::instrument(skip_all)]
+    async fn get_overdue_rents(
+        &self,
+        GetOverdueRentsDto { rented_before }: &GetOverdueRentsDto,
+    ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError> {
+        let mut connection = self.database_connection().transact().await?;
+        let rents = self
+            .rent_event_query()
+            .get_unreturned_before(&mut connection, &CreatedAt::new(*rented_before))
+            .await?;
+        connection.commit().await?;
+        Ok(rents)
+    }
 }
 
 impl<T> GetRentService for T where
@@ -250,6 +289,7 @@ async fn apply_events<T: Transaction, M: RentModifier<Transaction = T>>(
             event,
             version,
             created_at,
+            ..
         } = event.into_destruct();
         match event {
             RentEvent::Rent { book_id, user_id } => {
@@ -278,3 +318,77 @@ async fn apply_events<T: Transaction, M: RentModifier<Transaction = T>>(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use kernel::prelude::entity::{
+        Book, BookAmount, BookId, BookTitle, EventVersion, IsDeleted, Rent, ReturnedAt, User,
+        UserId, UserName, UserRentLimit, UserRole,
+    };
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    use crate::service::rent::check_rent;
+
+    fn book(amount: i32) -> Book {
+        Book::new(
+            BookId::new(Uuid::new_v4()),
+            BookTitle::new("title".to_string()),
+            BookAmount::new(amount),
+            EventVersion::new(1),
+            IsDeleted::new(false),
+        )
+    }
+
+    fn user(rent_limit: i32) -> User {
+        User::new(
+            UserId::new(Uuid::new_v4()),
+            UserName::new("name".to_string()),
+            UserRentLimit::new(rent_limit),
+            UserRole::Member,
+            None,
+            EventVersion::new(1),
+            IsDeleted::new(false),
+        )
+    }
+
+    fn rent(book: &Book, user: &User, returned: bool) -> Rent {
+        let returned_at = returned.then(|| {
+            (
+                ReturnedAt::new(OffsetDateTime::now_utc()),
+                EventVersion::new(2),
+            )
+        });
+        Rent::new(
+            EventVersion::new(1),
+            book.id().clone(),
+            user.id().clone(),
+            returned_at,
+        )
+    }
+
+    #[test]
+    fn test_check_rent() {
+        let (target, member) = (book(1), user(1));
+        assert!(check_rent(&target, &member, None, &[], &[]).is_ok());
+
+        // Returned rents do not count
+        let returned = rent(&target, &member, true);
+        let history = [returned.clone()];
+        assert!(check_rent(&target, &member, Some(&returned), &history, &history).is_ok());
+
+        let out = rent(&target, &member, false);
+        let current = [out.clone()];
+        assert!(check_rent(&target, &member, Some(&out), &current, &current).is_err());
+
+        // Every copy is out with someone else
+        let taken = [rent(&target, &user(1), false)];
+        assert!(check_rent(&target, &member, None, &taken, &[]).is_err());
+        assert!(check_rent(&book(2), &member, None, &taken, &[]).is_ok());
+
+        // The user is at their limit with another book
+        let limited = [rent(&book(1), &member, false)];
+        assert!(check_rent(&target, &member, None, &[], &limited).is_err());
+        assert!(check_rent(&target, &user(2), None, &[], &limited).is_ok());
+    }
+}
@@ -1,25 +1,29 @@
+use error_stack::Report;
 use kernel::interface::database::{DatabaseConnection, Transaction};
-use kernel::interface::event::{Applier, CommandInfo, UserEvent};
+use kernel::interface::event::{Applier, CommandInfo, EventMetadata, UserEvent};
 use kernel::interface::query::{
     DependOnUserEventQuery, DependOnUserQuery, UserEventQuery, UserQuery,
 };
 use kernel::interface::update::{
     DependOnUserEventHandler, DependOnUserModifier, UserEventHandler, UserModifier,
 };
-use kernel::prelude::entity::{User, UserId};
+use kernel::prelude::entity::{EventVersion, ExpectedEventVersion, User, UserId};
 use kernel::KernelError;
 
 use crate::transfer::{GetAllUserDto, GetUserDto};
 
 #[async_trait::async_trait]
 pub trait HandleUserService: 'static + Sync + Send + DependOnUserEventHandler {
+    #[tracing::instrument(skip_all)]
     async fn handle_user_event(
         &self,
         event: UserEvent,
+        version: Option<ExpectedEventVersion<User>>,
+        metadata: EventMetadata,
     ) -> error_stack::Result<UserId, KernelError> {
         let mut connection = self.database_connection().transact().await?;
 
-        let command = CommandInfo::new(event, None);
+        let command = CommandInfo::new(event, version, metadata);
         let id = self
             .user_event_handler()
             .handle(&mut connection, command)
@@ -37,6 +41,7 @@ impl<T> HandleUserService for T where T: DependOnUserEventHandler {}
 pub trait GetUserService:
     'static + Sync + Send + DependOnUserQuery + DependOnUserModifier + DependOnUserEventQuery
 {
+    #[tracing::instrument(skip_all)]
     async fn get_all(
         &self,
         GetAllUserDto { limit, offset }: GetAllUserDto,
@@ -63,6 +68,7 @@ pub trait GetUserService:
 
         Ok(users)
     }
+    #[tracing::instrument(skip_all)]
     async fn get_user(
         &self,
         GetUserDto { id }: &GetUserDto,
@@ -92,6 +98,24 @@ pub trait GetUserService:
 
         Ok(user)
     }
+
+    /// Fails with `PreconditionFailed` unless the user is still at `expected`
+    #[tracing::instrument(skip_all)]
+    async fn check_user_version(
+        &self,
+        dto: &GetUserDto,
+        expected: &EventVersion<User>,
+    ) -> error_stack::Result<(), KernelError> {
+        match self.get_user(dto).await? {
+            Some(user) if user.version() == expected => Ok(()),
+            current => Err(
+                Report::new(KernelError::PreconditionFailed).attach_printable(format!(
+                    "Expected version {expected:?}, found {:?}",
+                    current.as_ref().map(User::version)
+                )),
+            ),
+        }
+    }
 }
 
 impl<T> GetUserService for T where
@@ -1,6 +1,8 @@
+mod api_key;
+mod auth;
 mod book;
 
 mod rent;
 mod user;
 
-pub use self::{book::*, rent::*, user::*};
+pub use self::{api_key::*, auth::*, book::*, rent::*, user::*};
@@ -0,0 +1,19 @@
+use kernel::prelude::entity::{
+    ApiKeyExpiresAt, ApiKeyId, ApiKeyName, ApiKeyScope, SelectLimit, SelectOffset, UserId,
+};
+
+pub struct CreateApiKeyDto {
+    pub owner_id: UserId,
+    pub name: ApiKeyName,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_at: Option<ApiKeyExpiresAt>,
+}
+
+pub struct GetAllApiKeyDto {
+    pub limit: SelectLimit,
+    pub offset: SelectOffset,
+}
+
+pub struct RevokeApiKeyDto {
+    pub id: ApiKeyId,
+}
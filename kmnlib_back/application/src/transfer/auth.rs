@@ -0,0 +1,17 @@
+use kernel::prelude::entity::UserId;
+
+pub struct LoginDto {
+    pub id: UserId,
+    pub password: String,
+}
+
+pub struct ChangePasswordDto {
+    pub id: UserId,
+    pub current_password: String,
+    pub new_password: String,
+}
+
+pub struct ResetPasswordDto {
+    pub id: UserId,
+    pub new_password: String,
+}
@@ -1,4 +1,5 @@
 use kernel::prelude::entity::{BookId, UserId};
+use time::OffsetDateTime;
 
 pub struct GetRentFromBookIdDto {
     pub book_id: BookId,
@@ -12,3 +13,7 @@ pub struct GetRentFromIdDto {
     pub book_id: BookId,
     pub user_id: UserId,
 }
+
+pub struct GetOverdueRentsDto {
+    pub rented_before: OffsetDateTime,
+}
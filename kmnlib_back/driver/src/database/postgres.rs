@@ -1,6 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
 use error_stack::Report;
+use metrics::{counter, gauge};
 use sqlx::{Error, PgConnection, Pool, Postgres};
 
 use kernel::interface::database::{DatabaseConnection, Transaction};
@@ -8,10 +9,13 @@ use kernel::KernelError;
 
 use crate::env;
 use crate::error::ConvertError;
+use crate::metrics::{DB_POOL_CONNECTIONS, EVENTS_APPENDED_TOTAL};
 
-pub use self::{book::*, rent::*, user::*};
+pub use self::{api_key::*, book::*, rent::*, user::*};
 
+mod api_key;
 mod book;
+mod metadata;
 mod rent;
 mod user;
 
@@ -28,18 +32,42 @@ impl PostgresDatabase {
         let pool = Pool::connect(&url).await.convert_error()?;
         Ok(Self { pool })
     }
+
+    pub fn record_pool_metrics(&self) {
+        let size = self.pool.size();
+        let idle = u32::try_from(self.pool.num_idle()).unwrap_or(u32::MAX);
+        let max = self.pool.options().get_max_connections();
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "postgres", "state" => "active")
+            .set(f64::from(size.saturating_sub(idle)));
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "postgres", "state" => "idle").set(f64::from(idle));
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "postgres", "state" => "max").set(f64::from(max));
+    }
 }
 
-pub struct PostgresTransaction(sqlx::Transaction<'static, Postgres>);
+pub struct PostgresTransaction {
+    transaction: sqlx::Transaction<'static, Postgres>,
+    /// Events appended by aggregate, counted once the transaction commits
+    appended: Vec<(&'static str, u64)>,
+}
+
+impl PostgresTransaction {
+    pub(crate) fn record_appended(&mut self, aggregate: &'static str, count: u64) {
+        self.appended.push((aggregate, count));
+    }
+}
 
 #[async_trait::async_trait]
 impl Transaction for PostgresTransaction {
     async fn commit(self) -> error_stack::Result<(), KernelError> {
-        self.0.commit().await.convert_error()
+        self.transaction.commit().await.convert_error()?;
+        for (aggregate, count) in self.appended {
+            counter!(EVENTS_APPENDED_TOTAL, "aggregate" => aggregate).increment(count);
+        }
+        Ok(())
     }
 
     async fn roll_back(self) -> error_stack::Result<(), KernelError> {
-        self.0.rollback().await.convert_error()
+        self.transaction.rollback().await.convert_error()
     }
 }
 
@@ -47,13 +75,13 @@ impl Deref for PostgresTransaction {
     type Target = PgConnection;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.transaction
     }
 }
 
 impl DerefMut for PostgresTransaction {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.transaction
     }
 }
 
@@ -62,7 +90,10 @@ impl DatabaseConnection for PostgresDatabase {
     type Transaction = PostgresTransaction;
     async fn transact(&self) -> error_stack::Result<PostgresTransaction, KernelError> {
         let con = self.pool.begin().await.convert_error()?;
-        Ok(PostgresTransaction(con))
+        Ok(PostgresTransaction {
+            transaction: con,
+            appended: Vec::new(),
+        })
     }
 }
 
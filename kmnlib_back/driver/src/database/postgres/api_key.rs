@@ -0,0 +1,310 @@
+use error_stack::Report;
+use sqlx::types::Uuid;
+use sqlx::PgConnection;
+use time::OffsetDateTime;
+
+use kernel::interface::query::{ApiKeyQuery, DependOnApiKeyQuery};
+use kernel::interface::update::{ApiKeyModifier, DependOnApiKeyModifier};
+use kernel::prelude::entity::{
+    ApiKey, ApiKeyExpiresAt, ApiKeyHash, ApiKeyId, ApiKeyName, ApiKeyRevokedAt, ApiKeyScope,
+    CreatedAt, SelectLimit, SelectOffset, UserId,
+};
+use kernel::KernelError;
+
+use crate::database::postgres::PostgresTransaction;
+use crate::database::PostgresDatabase;
+use crate::error::ConvertError;
+
+pub struct PostgresApiKeyRepository;
+
+#[async_trait::async_trait]
+impl ApiKeyQuery for PostgresApiKeyRepository {
+    type Transaction = PostgresTransaction;
+
+    async fn get_all(
+        &self,
+        con: &mut PostgresTransaction,
+        limit: &SelectLimit,
+        offset: &SelectOffset,
+    ) -> error_stack::Result<Vec<ApiKey>, KernelError> {
+        PgApiKeyInternal::get_all(con, limit, offset).await
+    }
+
+    async fn find_by_id(
+        &self,
+        con: &mut PostgresTransaction,
+        id: &ApiKeyId,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        PgApiKeyInternal::find_by_id(con, id).await
+    }
+
+    async fn find_by_hash(
+        &self,
+        con: &mut PostgresTransaction,
+        hash: &ApiKeyHash,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        PgApiKeyInternal::find_by_hash(con, hash).await
+    }
+}
+
+impl DependOnApiKeyQuery for PostgresDatabase {
+    type ApiKeyQuery = PostgresApiKeyRepository;
+    fn api_key_query(&self) -> &Self::ApiKeyQuery {
+        &PostgresApiKeyRepository
+    }
+}
+
+#[async_trait::async_trait]
+impl ApiKeyModifier for PostgresApiKeyRepository {
+    type Transaction = PostgresTransaction;
+
+    async fn create(
+        &self,
+        con: &mut PostgresTransaction,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError> {
+        PgApiKeyInternal::create(con, api_key).await
+    }
+
+    async fn update(
+        &self,
+        con: &mut PostgresTransaction,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError> {
+        PgApiKeyInternal::update(con, api_key).await
+    }
+}
+
+impl DependOnApiKeyModifier for PostgresDatabase {
+    type ApiKeyModifier = PostgresApiKeyRepository;
+    fn api_key_modifier(&self) -> &Self::ApiKeyModifier {
+        &PostgresApiKeyRepository
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ApiKeyRow {
+    id: Uuid,
+    owner_id: Uuid,
+    name: String,
+    hash: String,
+    scopes: Vec<String>,
+    expires_at: Option<OffsetDateTime>,
+    revoked_at: Option<OffsetDateTime>,
+    created_at: OffsetDateTime,
+}
+
+impl TryFrom<ApiKeyRow> for ApiKey {
+    type Error = Report<KernelError>;
+    fn try_from(row: ApiKeyRow) -> Result<Self, Self::Error> {
+        let scopes = row
+            .scopes
+            .iter()
+            .map(|scope| scope.parse::<ApiKeyScope>())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ApiKey::new(
+            ApiKeyId::new(row.id),
+            UserId::new(row.owner_id),
+            ApiKeyName::new(row.name),
+            ApiKeyHash::new(row.hash),
+            scopes,
+            row.expires_at.map(ApiKeyExpiresAt::new),
+            row.revoked_at.map(ApiKeyRevokedAt::new),
+            CreatedAt::new(row.created_at),
+        ))
+    }
+}
+
+pub(in crate::database) struct PgApiKeyInternal;
+
+impl PgApiKeyInternal {
+    #[tracing::instrument(skip_all)]
+    async fn get_all(
+        con: &mut PgConnection,
+        limit: &SelectLimit,
+        offset: &SelectOffset,
+    ) -> error_stack::Result<Vec<ApiKey>, KernelError> {
+        sqlx::query_as::<_, ApiKeyRow>(
+            // language=postgresql
+            r#"
+            SELECT id, owner_id, name, hash, scopes, expires_at, revoked_at, created_at
+            FROM api_keys
+            ORDER BY created_at
+            LIMIT $1
+            OFFSET $2
+            "#,
+        )
+        .bind(limit.as_ref())
+        .bind(offset.as_ref())
+        .fetch_all(con)
+        .await
+        .convert_error()?
+        .into_iter()
+        .map(ApiKey::try_from)
+        .collect()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn find_by_id(
+        con: &mut PgConnection,
+        id: &ApiKeyId,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(
+            // language=postgresql
+            r#"
+            SELECT id, owner_id, name, hash, scopes, expires_at, revoked_at, created_at
+            FROM api_keys
+            WHERE id = $1
+            "#,
+        )
+        .bind(id.as_ref())
+        .fetch_optional(con)
+        .await
+        .convert_error()?;
+        row.map(ApiKey::try_from).transpose()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn find_by_hash(
+        con: &mut PgConnection,
+        hash: &ApiKeyHash,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError> {
+        let row = sqlx::query_as::<_, ApiKeyRow>(
+            // language=postgresql
+            r#"
+            SELECT id, owner_id, name, hash, scopes, expires_at, revoked_at, created_at
+            FROM api_keys
+            WHERE hash = $1
+            "#,
+        )
+        .bind(hash.as_ref())
+        .fetch_optional(con)
+        .await
+        .convert_error()?;
+        row.map(ApiKey::try_from).transpose()
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn create(
+        con: &mut PgConnection,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError> {
+        let scopes = api_key
+            .scopes()
+            .iter()
+            .map(ApiKeyScope::as_str)
+            .collect::<Vec<_>>();
+        sqlx::query(
+            // language=postgresql
+            r#"
+            INSERT INTO api_keys (id, owner_id, name, hash, scopes, expires_at, revoked_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(api_key.id().as_ref())
+        .bind(api_key.owner_id().as_ref())
+        .bind(api_key.name().as_ref())
+        .bind(api_key.hash().as_ref())
+        .bind(scopes)
+        .bind(api_key.expires_at().as_ref().map(AsRef::as_ref))
+        .bind(api_key.revoked_at().as_ref().map(AsRef::as_ref))
+        .bind(api_key.created_at().as_ref())
+        .execute(con)
+        .await
+        .convert_error()?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn update(
+        con: &mut PgConnection,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError> {
+        let scopes = api_key
+            .scopes()
+            .iter()
+            .map(ApiKeyScope::as_str)
+            .collect::<Vec<_>>();
+        sqlx::query(
+            // language=postgresql
+            r#"
+            UPDATE api_keys
+            SET name = $2, scopes = $3, expires_at = $4, revoked_at = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(api_key.id().as_ref())
+        .bind(api_key.name().as_ref())
+        .bind(scopes)
+        .bind(api_key.expires_at().as_ref().map(AsRef::as_ref))
+        .bind(api_key.revoked_at().as_ref().map(AsRef::as_ref))
+        .execute(con)
+        .await
+        .convert_error()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    use kernel::interface::database::DatabaseConnection;
+    use kernel::interface::query::ApiKeyQuery;
+    use kernel::interface::update::{ApiKeyModifier, UserModifier};
+    use kernel::prelude::entity::{
+        ApiKey, ApiKeyHash, ApiKeyId, ApiKeyName, ApiKeyRevokedAt, ApiKeyScope, CreatedAt,
+        EventVersion, IsDeleted, User, UserId, UserName, UserRentLimit, UserRole,
+    };
+    use kernel::KernelError;
+
+    use crate::database::postgres::api_key::PostgresApiKeyRepository;
+    use crate::database::postgres::user::PostgresUserRepository;
+    use crate::database::postgres::PostgresDatabase;
+
+    #[test_with::env(POSTGRES_TEST)]
+    #[tokio::test]
+    async fn test_query() -> error_stack::Result<(), KernelError> {
+        let db = PostgresDatabase::new().await?;
+        let mut con = db.transact().await?;
+
+        let owner_id = UserId::new(Uuid::new_v4());
+        let owner = User::new(
+            owner_id.clone(),
+            UserName::new("kiosk".to_string()),
+            UserRentLimit::new(0),
+            UserRole::Librarian,
+            None,
+            EventVersion::new(0),
+            IsDeleted::new(false),
+        );
+        PostgresUserRepository.create(&mut con, &owner).await?;
+
+        let id = ApiKeyId::new(Uuid::new_v4());
+        let hash = ApiKeyHash::new(Uuid::new_v4().simple().to_string());
+        let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+        let api_key = ApiKey::new(
+            id.clone(),
+            owner_id,
+            ApiKeyName::new("kiosk".to_string()),
+            hash.clone(),
+            vec![ApiKeyScope::Rents],
+            None,
+            None,
+            CreatedAt::new(now),
+        );
+        PostgresApiKeyRepository.create(&mut con, &api_key).await?;
+
+        let found = PostgresApiKeyRepository
+            .find_by_hash(&mut con, &hash)
+            .await?;
+        assert_eq!(found, Some(api_key.clone()));
+
+        let api_key = api_key.reconstruct(|key| key.revoked_at = Some(ApiKeyRevokedAt::new(now)));
+        PostgresApiKeyRepository.update(&mut con, &api_key).await?;
+        let found = PostgresApiKeyRepository.find_by_id(&mut con, &id).await?;
+        assert_eq!(found, Some(api_key));
+        Ok(())
+    }
+}
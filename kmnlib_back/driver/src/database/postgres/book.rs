@@ -5,6 +5,7 @@ use uuid::Uuid;
 
 use kernel::interface::event::{
     BookEvent, BookEventRow, CommandInfo, DestructBookEventRow, DestructCommandInfo, EventInfo,
+    EventMetadata,
 };
 use kernel::interface::query::{
     BookEventQuery, BookQuery, DependOnBookEventQuery, DependOnBookQuery,
@@ -18,6 +19,7 @@ use kernel::prelude::entity::{
 };
 use kernel::KernelError;
 
+use crate::database::postgres::metadata::{BindEventMetadata, EventMetadataColumn};
 use crate::database::postgres::PostgresTransaction;
 use crate::database::PostgresDatabase;
 use crate::error::ConvertError;
@@ -96,7 +98,9 @@ impl BookEventHandler for PostgresBookRepository {
         con: &mut PostgresTransaction,
         event: CommandInfo<BookEvent, Book>,
     ) -> error_stack::Result<BookId, KernelError> {
-        PgBookInternal::handle_command(con, event).await
+        let (id, appended) = PgBookInternal::handle_command(con, event).await?;
+        con.record_appended("book", appended);
+        Ok(id)
     }
 }
 
@@ -156,6 +160,8 @@ struct BookEventRowColumn {
     title: Option<String>,
     amount: Option<i32>,
     created_at: OffsetDateTime,
+    #[sqlx(flatten)]
+    metadata: EventMetadataColumn,
 }
 
 impl TryFrom<BookEventRowColumn> for EventInfo<BookEvent, Book> {
@@ -172,6 +178,7 @@ impl TryFrom<BookEventRowColumn> for EventInfo<BookEvent, Book> {
             event,
             EventVersion::new(value.version),
             CreatedAt::new(value.created_at),
+            EventMetadata::from(value.metadata),
         ))
     }
 }
@@ -179,6 +186,7 @@ impl TryFrom<BookEventRowColumn> for EventInfo<BookEvent, Book> {
 pub(in crate::database) struct PgBookInternal;
 
 impl PgBookInternal {
+    #[tracing::instrument(skip_all)]
     async fn get_all(
         con: &mut PgConnection,
         limit: &SelectLimit,
@@ -187,7 +195,7 @@ impl PgBookInternal {
         sqlx::query_as::<_, BookRow>(
             // language=postgresql
             r#"
-            SELECT id, title, amount, version
+            SELECT id, title, amount, version, is_deleted
             FROM books
             ORDER BY id
             LIMIT $1
@@ -204,6 +212,7 @@ impl PgBookInternal {
         })
         .map(|vec| vec.into_iter().map(Book::from).collect())
     }
+    #[tracing::instrument(skip_all)]
     async fn find_by_id(
         con: &mut PgConnection,
         id: &BookId,
@@ -211,7 +220,7 @@ impl PgBookInternal {
         let row = sqlx::query_as::<_, BookRow>(
             // language=postgresql
             r#"
-            SELECT id, title, amount, version
+            SELECT id, title, amount, version, is_deleted
             FROM books
             WHERE id = $1
             "#,
@@ -227,6 +236,7 @@ impl PgBookInternal {
         Ok(found)
     }
 
+    #[tracing::instrument(skip_all)]
     async fn create(con: &mut PgConnection, book: &Book) -> error_stack::Result<(), KernelError> {
         // language=postgresql
         sqlx::query(
@@ -249,6 +259,7 @@ impl PgBookInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn update(con: &mut PgConnection, book: &Book) -> error_stack::Result<(), KernelError> {
         // language=postgresql
         sqlx::query(
@@ -269,6 +280,7 @@ impl PgBookInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn delete(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -287,11 +299,16 @@ impl PgBookInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn handle_command(
         con: &mut PgConnection,
         event: CommandInfo<BookEvent, Book>,
-    ) -> error_stack::Result<BookId, KernelError> {
-        let DestructCommandInfo { event, version } = event.into_destruct();
+    ) -> error_stack::Result<(BookId, u64), KernelError> {
+        let DestructCommandInfo {
+            event,
+            version,
+            metadata,
+        } = event.into_destruct();
         let DestructBookEventRow {
             event_name,
             id,
@@ -300,41 +317,52 @@ impl PgBookInternal {
         } = BookEventRow::from(event).into_destruct();
         let title_row = title.as_ref().map(AsRef::as_ref);
         let amount = amount.as_ref().map(AsRef::as_ref);
-        match version {
+        let version: Option<EventVersion<Book>> = match version {
+            None => None,
+            Some(ExpectedEventVersion::Nothing) => {
+                let event = PgBookInternal::get_events(con, &id, None).await?;
+                if !event.is_empty() {
+                    return Err(Report::new(KernelError::Concurrency)
+                        .attach_printable("Event stream is already exists"));
+                }
+                Some(EventVersion::new(1))
+            }
+            Some(ExpectedEventVersion::Exact(expected)) => {
+                let current = PgBookInternal::lock_stream(con, &id).await?;
+                if current.as_ref() != Some(&expected) {
+                    let message = format!("Stream is at {current:?}, expected {expected:?}");
+                    return Err(Report::new(KernelError::Concurrency).attach_printable(message));
+                }
+                None
+            }
+        };
+        let appended = match version {
             None => {
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO book_events (book_id, event_name, title, amount)
-                    VALUES ($1, $2, $3, $4)
+                    INSERT INTO book_events (book_id, event_name, title, amount,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
                     "#,
                 )
                 .bind(id.as_ref())
                 .bind(event_name)
                 .bind(title_row)
                 .bind(amount)
+                .bind_metadata(&metadata)
                 .execute(con)
                 .await
-                .convert_error()?;
+                .convert_error()?
+.rows_affected()
             }
             Some(version) => {
-                let version = match version {
-                    ExpectedEventVersion::Nothing => {
-                        let event = PgBookInternal::get_events(con, &id, None).await?;
-                        if !event.is_empty() {
-                            return Err(Report::new(KernelError::Concurrency)
-                                .attach_printable("Event stream is already exists"));
-                        } else {
-                            EventVersion::new(1)
-                        }
-                    }
-                    ExpectedEventVersion::Exact(version) => version,
-                };
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO book_events (version, book_id, event_name, title, amount)
-                    VALUES ($1, $2, $3, $4)
+                    INSERT INTO book_events (version, book_id, event_name, title, amount,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
                     "#,
                 )
                 .bind(version.as_ref())
@@ -342,14 +370,39 @@ impl PgBookInternal {
                 .bind(event_name)
                 .bind(title_row)
                 .bind(amount)
+                .bind_metadata(&metadata)
                 .execute(con)
                 .await
-                .convert_error()?;
+                .convert_error()?
+.rows_affected()
             }
-        }
-        Ok(id)
+        };
+        Ok((id, appended))
     }
 
+    /// Serializes writers of the stream until the transaction ends and returns its latest version
+    #[tracing::instrument(skip_all)]
+    async fn lock_stream(
+        con: &mut PgConnection,
+        id: &BookId,
+    ) -> error_stack::Result<Option<EventVersion<Book>>, KernelError> {
+        // language=postgresql
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::uuid::text, 0))")
+            .bind(id.as_ref())
+            .execute(&mut *con)
+            .await
+            .convert_error()?;
+        // language=postgresql
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM book_events WHERE book_id = $1")
+                .bind(id.as_ref())
+                .fetch_one(con)
+                .await
+                .convert_error()?;
+        Ok(version.map(EventVersion::new))
+    }
+
+    #[tracing::instrument(skip_all)]
     async fn get_events(
         con: &mut PgConnection,
         id: &BookId,
@@ -360,7 +413,7 @@ impl PgBookInternal {
                 // language=postgresql
                 sqlx::query_as::<_, BookEventRowColumn>(
                     r#"
-            SELECT version, event_name, title, amount, created_at FROM book_events where version > $1 AND book_id = $2
+            SELECT version, event_name, book_id, title, amount, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent FROM book_events where version > $1 AND book_id = $2
             "#,
                 )
                     .bind(version.as_ref())
@@ -369,7 +422,7 @@ impl PgBookInternal {
                 // language=postgresql
                 sqlx::query_as::<_, BookEventRowColumn>(
                     r#"
-            SELECT version, event_name, title, amount, created_at FROM book_events where book_id = $1
+            SELECT version, event_name, book_id, title, amount, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent FROM book_events where book_id = $1
             "#,
                 )
             }
@@ -388,10 +441,13 @@ mod test {
     use uuid::Uuid;
 
     use kernel::interface::database::DatabaseConnection;
-    use kernel::interface::event::{BookEvent, CommandInfo};
+    use kernel::interface::event::{BookEvent, CommandInfo, EventMetadata};
     use kernel::interface::query::{BookEventQuery, BookQuery};
     use kernel::interface::update::{BookEventHandler, BookModifier};
-    use kernel::prelude::entity::{Book, BookAmount, BookId, BookTitle, EventVersion, IsDeleted};
+    use kernel::prelude::entity::{
+        ActorId, Book, BookAmount, BookId, BookTitle, CausationId, ClientIp, CorrelationId,
+        EventVersion, ExpectedEventVersion, IsDeleted, UserAgent,
+    };
     use kernel::KernelError;
 
     use crate::database::postgres::book::PostgresBookRepository;
@@ -444,7 +500,15 @@ mod test {
             title,
             amount,
         };
-        let create_command: CommandInfo<BookEvent, Book> = CommandInfo::new(create_event, None);
+        let metadata = EventMetadata::new(
+            Some(ActorId::new("test_actor")),
+            Some(CorrelationId::new(Uuid::new_v4())),
+            Some(CausationId::new(Uuid::new_v4())),
+            Some(ClientIp::new("127.0.0.1")),
+            Some(UserAgent::new("test_agent")),
+        );
+        let create_command: CommandInfo<BookEvent, Book> =
+            CommandInfo::new(create_event, None, metadata.clone());
         PostgresBookRepository
             .handle(&mut con, create_command.clone())
             .await?;
@@ -455,13 +519,14 @@ mod test {
         let event_version_first = EventVersion::new(1);
         assert_eq!(create_event.version(), &event_version_first);
         assert_eq!(create_event.event(), &create_command.into_destruct().event);
+        assert_eq!(create_event.metadata(), &metadata);
 
         let update_event = BookEvent::Update {
             id: id.clone(),
             title: Some(BookTitle::new("test_book2".to_string())),
             amount: None,
         };
-        let update_command = CommandInfo::new(update_event, None);
+        let update_command = CommandInfo::new(update_event, None, EventMetadata::default());
         PostgresBookRepository
             .handle(&mut con, update_command.clone())
             .await?;
@@ -475,4 +540,48 @@ mod test {
         // TODO: create book entity
         Ok(())
     }
+
+    #[test_with::env(POSTGRES_TEST)]
+    #[tokio::test]
+    async fn test_expected_version() -> error_stack::Result<(), KernelError> {
+        let db = PostgresDatabase::new().await?;
+        let mut con = db.transact().await?;
+
+        let id = BookId::new(Uuid::new_v4());
+        let create_event = BookEvent::Create {
+            id: id.clone(),
+            title: BookTitle::new("test_book".to_string()),
+            amount: BookAmount::new(1),
+        };
+        PostgresBookRepository
+            .handle(
+                &mut con,
+                CommandInfo::new(create_event, None, EventMetadata::default()),
+            )
+            .await?;
+        let events = PostgresBookRepository
+            .get_events(&mut con, &id, None)
+            .await?;
+        let version = events.last().unwrap().version().clone();
+
+        let update_event = BookEvent::Update {
+            id: id.clone(),
+            title: None,
+            amount: Some(BookAmount::new(2)),
+        };
+        let update_command: CommandInfo<BookEvent, Book> = CommandInfo::new(
+            update_event,
+            Some(ExpectedEventVersion::Exact(version)),
+            EventMetadata::default(),
+        );
+        PostgresBookRepository
+            .handle(&mut con, update_command.clone())
+            .await?;
+        let stale = PostgresBookRepository
+            .handle(&mut con, update_command)
+            .await
+            .unwrap_err();
+        assert!(matches!(stale.current_context(), KernelError::Concurrency));
+        Ok(())
+    }
 }
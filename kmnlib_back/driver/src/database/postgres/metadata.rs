@@ -0,0 +1,43 @@
+use sqlx::postgres::PgArguments;
+use sqlx::query::Query;
+use sqlx::Postgres;
+use uuid::Uuid;
+
+use kernel::interface::event::EventMetadata;
+use kernel::prelude::entity::{ActorId, CausationId, ClientIp, CorrelationId, UserAgent};
+
+#[derive(sqlx::FromRow)]
+pub(in crate::database::postgres) struct EventMetadataColumn {
+    actor_id: Option<String>,
+    correlation_id: Option<Uuid>,
+    causation_id: Option<Uuid>,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl From<EventMetadataColumn> for EventMetadata {
+    fn from(value: EventMetadataColumn) -> Self {
+        EventMetadata::new(
+            value.actor_id.map(ActorId::new),
+            value.correlation_id.map(CorrelationId::new),
+            value.causation_id.map(CausationId::new),
+            value.client_ip.map(ClientIp::new),
+            value.user_agent.map(UserAgent::new),
+        )
+    }
+}
+
+pub(in crate::database::postgres) trait BindEventMetadata<'q> {
+    /// Binds `actor_id, correlation_id, causation_id, client_ip, user_agent` in this order
+    fn bind_metadata(self, metadata: &'q EventMetadata) -> Self;
+}
+
+impl<'q> BindEventMetadata<'q> for Query<'q, Postgres, PgArguments> {
+    fn bind_metadata(self, metadata: &'q EventMetadata) -> Self {
+        self.bind(metadata.actor_id().as_ref().map(AsRef::as_ref))
+            .bind(metadata.correlation_id().as_ref().map(|id| *id.as_ref()))
+            .bind(metadata.causation_id().as_ref().map(|id| *id.as_ref()))
+            .bind(metadata.client_ip().as_ref().map(AsRef::as_ref))
+            .bind(metadata.user_agent().as_ref().map(AsRef::as_ref))
+    }
+}
@@ -4,7 +4,8 @@ use time::OffsetDateTime;
 use uuid::Uuid;
 
 use kernel::interface::event::{
-    CommandInfo, DestructCommandInfo, DestructRentEventRow, EventInfo, RentEvent, RentEventRow,
+    CommandInfo, DestructCommandInfo, DestructRentEventRow, EventInfo, EventMetadata, RentEvent,
+    RentEventRow, BOOK_RENTED,
 };
 use kernel::interface::query::{
     DependOnRentEventQuery, DependOnRentQuery, RentEventQuery, RentQuery,
@@ -17,6 +18,7 @@ use kernel::prelude::entity::{
 };
 use kernel::KernelError;
 
+use crate::database::postgres::metadata::{BindEventMetadata, EventMetadataColumn};
 use crate::database::postgres::PostgresTransaction;
 use crate::database::PostgresDatabase;
 use crate::error::ConvertError;
@@ -102,7 +104,9 @@ impl RentEventHandler for PostgresRentRepository {
         con: &mut PostgresTransaction,
         command: CommandInfo<RentEvent, Rent>,
     ) -> error_stack::Result<(), KernelError> {
-        PgRentInternal::handle_command(con, command).await
+        let appended = PgRentInternal::handle_command(con, command).await?;
+        con.record_appended("rent", appended);
+        Ok(())
     }
 }
 
@@ -143,6 +147,14 @@ impl RentEventQuery for PostgresRentRepository {
     ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError> {
         PgRentInternal::get_events(con, book_id, user_id, since).await
     }
+
+    async fn get_unreturned_before(
+        &self,
+        con: &mut PostgresTransaction,
+        rented_before: &CreatedAt<Rent>,
+    ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError> {
+        PgRentInternal::get_unreturned_before(con, rented_before).await
+    }
 }
 
 impl DependOnRentEventQuery for PostgresDatabase {
@@ -200,6 +212,8 @@ struct RentEventRowColumn {
     book_id: Uuid,
     user_id: Uuid,
     created_at: OffsetDateTime,
+    #[sqlx(flatten)]
+    metadata: EventMetadataColumn,
 }
 
 impl TryFrom<RentEventRowColumn> for EventInfo<RentEvent, Rent> {
@@ -215,6 +229,7 @@ impl TryFrom<RentEventRowColumn> for EventInfo<RentEvent, Rent> {
             event,
             EventVersion::new(value.version),
             CreatedAt::new(value.created_at),
+            EventMetadata::from(value.metadata),
         ))
     }
 }
@@ -222,6 +237,7 @@ impl TryFrom<RentEventRowColumn> for EventInfo<RentEvent, Rent> {
 pub(in crate::database) struct PgRentInternal;
 
 impl PgRentInternal {
+    #[tracing::instrument(skip_all)]
     async fn find_by_id(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -250,6 +266,7 @@ impl PgRentInternal {
             .collect::<error_stack::Result<Vec<_>, KernelError>>()
     }
 
+    #[tracing::instrument(skip_all)]
     async fn find_by_book_id(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -275,6 +292,7 @@ impl PgRentInternal {
             .collect::<error_stack::Result<Vec<_>, KernelError>>()
     }
 
+    #[tracing::instrument(skip_all)]
     async fn find_by_user_id(
         con: &mut PgConnection,
         user_id: &UserId,
@@ -300,6 +318,7 @@ impl PgRentInternal {
             .collect::<error_stack::Result<_, KernelError>>()
     }
 
+    #[tracing::instrument(skip_all)]
     async fn create(con: &mut PgConnection, rent: &Rent) -> error_stack::Result<(), KernelError> {
         sqlx::query(
             // language=postgresql
@@ -317,6 +336,7 @@ impl PgRentInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn update(con: &mut PgConnection, rent: &Rent) -> error_stack::Result<(), KernelError> {
         let (returned_at, returned_version) = match rent.returned_at() {
             None => (None, None),
@@ -326,7 +346,7 @@ impl PgRentInternal {
             // language=postgresql
             r#"
             UPDATE book_rents
-            SET returned_at = $4, returned_version = 5
+            SET returned_at = $4, returned_version = $5
             WHERE version = $1 AND book_id = $2 AND user_id = $3
             "#,
         )
@@ -341,6 +361,7 @@ impl PgRentInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn delete(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -361,65 +382,82 @@ impl PgRentInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn handle_command(
         con: &mut PgConnection,
         command: CommandInfo<RentEvent, Rent>,
-    ) -> error_stack::Result<(), KernelError> {
-        let DestructCommandInfo { event, version } = command.into_destruct();
+    ) -> error_stack::Result<u64, KernelError> {
+        let DestructCommandInfo {
+            event,
+            version,
+            metadata,
+        } = command.into_destruct();
         let DestructRentEventRow {
             user_id,
             book_id,
             event_name,
         } = RentEventRow::from(event).into_destruct();
-        match version {
+        let version: Option<EventVersion<Rent>> = match version {
+            None => None,
+            Some(ExpectedEventVersion::Nothing) => {
+                let event = PgRentInternal::get_events(con, &book_id, &user_id, None).await?;
+                if !event.is_empty() {
+                    return Err(Report::new(KernelError::Concurrency)
+                        .attach_printable("Event stream already exists"));
+                }
+                Some(EventVersion::new(1))
+            }
+            Some(ExpectedEventVersion::Exact(expected)) => {
+                let current = PgRentInternal::lock_stream(con, &book_id, &user_id).await?;
+                if current.as_ref() != Some(&expected) {
+                    let message = format!("Stream is at {current:?}, expected {expected:?}");
+                    return Err(Report::new(KernelError::Concurrency).attach_printable(message));
+                }
+                Some(EventVersion::new(expected.as_ref() + 1))
+            }
+        };
+        let appended = match version {
             None => {
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO rent_events (book_id, user_id, event_name)
-                    VALUES ($1, $2, $3)
+                    INSERT INTO rent_events (book_id, user_id, event_name,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
                     "#,
                 )
                 .bind(book_id.as_ref())
                 .bind(user_id.as_ref())
                 .bind(event_name)
+                .bind_metadata(&metadata)
                 .execute(con)
                 .await
-                .convert_error()?;
+                .convert_error()?
+.rows_affected()
             }
             Some(version) => {
-                let version = match version {
-                    ExpectedEventVersion::Nothing => {
-                        let event =
-                            PgRentInternal::get_events(con, &book_id, &user_id, None).await?;
-                        if !event.is_empty() {
-                            return Err(Report::new(KernelError::Concurrency)
-                                .attach_printable("Event stream already exists"));
-                        } else {
-                            EventVersion::new(1)
-                        }
-                    }
-                    ExpectedEventVersion::Exact(version) => version,
-                };
-
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO rent_events (version, book_id, user_id, event_name)
-                    VALUES ($1, $2, $3, $4)
+                    INSERT INTO rent_events (version, book_id, user_id, event_name,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
                     "#,
                 )
                 .bind(version.as_ref())
                 .bind(book_id.as_ref())
                 .bind(user_id.as_ref())
                 .bind(event_name)
+                .bind_metadata(&metadata)
                 .execute(con)
                 .await
-                .convert_error()?;
+                .convert_error()?
+.rows_affected()
             }
-        }
-        Ok(())
+        };
+        Ok(appended)
     }
+    #[tracing::instrument(skip_all)]
     async fn get_events_from_book(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -430,7 +468,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE book_id = $1
                     "#,
@@ -440,7 +478,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE version > $1 AND book_id = $2
                     "#,
@@ -456,6 +494,7 @@ impl PgRentInternal {
         row.into_iter().map(EventInfo::try_from).collect()
     }
 
+    #[tracing::instrument(skip_all)]
     async fn get_events_from_user(
         con: &mut PgConnection,
         user_id: &UserId,
@@ -466,7 +505,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE user_id = $1
                     "#,
@@ -476,7 +515,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE version > $1 AND user_id = $2
                     "#,
@@ -492,6 +531,35 @@ impl PgRentInternal {
         row.into_iter().map(EventInfo::try_from).collect()
     }
 
+    /// Serializes writers of the stream until the transaction ends and returns its latest version
+    #[tracing::instrument(skip_all)]
+    async fn lock_stream(
+        con: &mut PgConnection,
+        book_id: &BookId,
+        user_id: &UserId,
+    ) -> error_stack::Result<Option<EventVersion<Rent>>, KernelError> {
+        // language=postgresql
+        sqlx::query(
+            "SELECT pg_advisory_xact_lock(hashtextextended($1::uuid::text || $2::uuid::text, 0))",
+        )
+        .bind(book_id.as_ref())
+        .bind(user_id.as_ref())
+        .execute(&mut *con)
+        .await
+        .convert_error()?;
+        // language=postgresql
+        let version: Option<i64> = sqlx::query_scalar(
+            "SELECT MAX(version) FROM rent_events WHERE book_id = $1 AND user_id = $2",
+        )
+        .bind(book_id.as_ref())
+        .bind(user_id.as_ref())
+        .fetch_one(con)
+        .await
+        .convert_error()?;
+        Ok(version.map(EventVersion::new))
+    }
+
+    #[tracing::instrument(skip_all)]
     async fn get_events(
         con: &mut PgConnection,
         book_id: &BookId,
@@ -503,7 +571,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE user_id = $1 AND book_id = $2
                     "#,
@@ -513,7 +581,7 @@ impl PgRentInternal {
                 // language=postgresql
                 sqlx::query_as::<_, RentEventRowColumn>(
                     r#"
-                    SELECT version, event_name, book_id, user_id, created_at
+                    SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM rent_events
                     WHERE version > $1 AND user_id = $2 AND book_id = $3
                     "#,
@@ -529,19 +597,47 @@ impl PgRentInternal {
 
         row.into_iter().map(EventInfo::try_from).collect()
     }
+
+    #[tracing::instrument(skip_all)]
+    async fn get_unreturned_before(
+        con: &mut PgConnection,
+        rented_before: &CreatedAt<Rent>,
+    ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError> {
+        // language=postgresql
+        let row = sqlx::query_as::<_, RentEventRowColumn>(
+            r#"
+            SELECT version, event_name, book_id, user_id, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
+            FROM (
+                SELECT DISTINCT ON (book_id, user_id) *
+                FROM rent_events
+                ORDER BY book_id, user_id, version DESC
+            ) latest
+            WHERE event_name = $1 AND created_at < $2
+            ORDER BY created_at
+            "#,
+        )
+        .bind(BOOK_RENTED)
+        .bind(rented_before.as_ref())
+        .fetch_all(con)
+        .await
+        .convert_error()?;
+
+        row.into_iter().map(EventInfo::try_from).collect()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use kernel::interface::database::DatabaseConnection;
-    use kernel::interface::event::{CommandInfo, RentEvent};
+    use kernel::interface::event::{CommandInfo, EventInfo, EventMetadata, RentEvent};
     use kernel::interface::query::{RentEventQuery, RentQuery};
     use kernel::interface::update::{BookModifier, RentEventHandler, RentModifier, UserModifier};
     use kernel::prelude::entity::{
-        Book, BookAmount, BookId, BookTitle, EventVersion, ExpectedEventVersion, IsDeleted, Rent,
-        User, UserId, UserName, UserRentLimit,
+        Book, BookAmount, BookId, BookTitle, CreatedAt, EventVersion, ExpectedEventVersion,
+        IsDeleted, Rent, User, UserId, UserName, UserRentLimit, UserRole,
     };
     use kernel::KernelError;
+    use time::OffsetDateTime;
 
     use crate::database::postgres::{
         PostgresBookRepository, PostgresDatabase, PostgresRentRepository, PostgresUserRepository,
@@ -567,6 +663,8 @@ mod test {
             user_id.clone(),
             UserName::new("name".to_string()),
             UserRentLimit::new(1),
+            UserRole::Member,
+            None,
             EventVersion::new(0),
             IsDeleted::new(false),
         );
@@ -604,7 +702,11 @@ mod test {
             book_id: book_id.clone(),
             user_id: user_id.clone(),
         };
-        let rent_command = CommandInfo::new(rent_event, Some(ExpectedEventVersion::Nothing));
+        let rent_command = CommandInfo::new(
+            rent_event,
+            Some(ExpectedEventVersion::Nothing),
+            EventMetadata::default(),
+        );
         PostgresRentRepository
             .handle(&mut con, rent_command.clone())
             .await?;
@@ -622,7 +724,8 @@ mod test {
         };
         let return_command = CommandInfo::new(
             return_event,
-            Some(ExpectedEventVersion::Exact(EventVersion::new(2))),
+            Some(ExpectedEventVersion::Exact(event_version_first.clone())),
+            EventMetadata::default(),
         );
         PostgresRentRepository
             .handle(&mut con, return_command.clone())
@@ -632,9 +735,56 @@ mod test {
             .await?;
         let rent_event = rent_event.first().unwrap();
         assert_eq!(rent_event.version(), &EventVersion::new(2));
-        assert_eq!(rent_event.event(), &return_command.into_destruct().event);
+        assert_eq!(
+            rent_event.event(),
+            &return_command.clone().into_destruct().event
+        );
+
+        let stale = PostgresRentRepository
+            .handle(&mut con, return_command)
+            .await
+            .unwrap_err();
+        assert!(matches!(stale.current_context(), KernelError::Concurrency));
 
         // TODO: create rent entity
         Ok(())
     }
+
+    #[test_with::env(POSTGRES_TEST)]
+    #[tokio::test]
+    async fn test_unreturned() -> error_stack::Result<(), KernelError> {
+        let db = PostgresDatabase::new().await?;
+        let mut con = db.transact().await?;
+
+        let book_id = BookId::new(uuid::Uuid::new_v4());
+        let user_id = UserId::new(uuid::Uuid::new_v4());
+        let rent_event = RentEvent::Rent {
+            book_id: book_id.clone(),
+            user_id: user_id.clone(),
+        };
+        PostgresRentRepository
+            .handle(
+                &mut con,
+                CommandInfo::new(
+                    rent_event.clone(),
+                    Some(ExpectedEventVersion::Nothing),
+                    EventMetadata::default(),
+                ),
+            )
+            .await?;
+
+        let is_target = |info: &EventInfo<RentEvent, Rent>| info.event() == &rent_event;
+        let past = CreatedAt::new(OffsetDateTime::now_utc() - time::Duration::hours(1));
+        let unreturned = PostgresRentRepository
+            .get_unreturned_before(&mut con, &past)
+            .await?;
+        assert!(!unreturned.iter().any(is_target));
+
+        let future = CreatedAt::new(OffsetDateTime::now_utc() + time::Duration::hours(1));
+        let unreturned = PostgresRentRepository
+            .get_unreturned_before(&mut con, &future)
+            .await?;
+        assert!(unreturned.iter().any(is_target));
+        Ok(())
+    }
 }
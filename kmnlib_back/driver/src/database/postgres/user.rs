@@ -4,7 +4,8 @@ use sqlx::PgConnection;
 use time::OffsetDateTime;
 
 use kernel::interface::event::{
-    CommandInfo, DestructCommandInfo, DestructUserEventRow, EventInfo, UserEvent, UserEventRow,
+    CommandInfo, DestructCommandInfo, DestructUserEventRow, EventInfo, EventMetadata, UserEvent,
+    UserEventRow,
 };
 use kernel::interface::query::{
     DependOnUserEventQuery, DependOnUserQuery, UserEventQuery, UserQuery,
@@ -14,10 +15,11 @@ use kernel::interface::update::{
 };
 use kernel::prelude::entity::{
     CreatedAt, EventVersion, ExpectedEventVersion, IsDeleted, SelectLimit, SelectOffset, User,
-    UserId, UserName, UserRentLimit,
+    UserId, UserName, UserPasswordHash, UserRentLimit, UserRole,
 };
 use kernel::KernelError;
 
+use crate::database::postgres::metadata::{BindEventMetadata, EventMetadataColumn};
 use crate::database::postgres::PostgresTransaction;
 use crate::database::PostgresDatabase;
 use crate::error::ConvertError;
@@ -96,7 +98,9 @@ impl UserEventHandler for PostgresUserRepository {
         con: &mut PostgresTransaction,
         command: CommandInfo<UserEvent, User>,
     ) -> error_stack::Result<UserId, KernelError> {
-        PgUserInternal::handle_command(con, command).await
+        let (id, appended) = PgUserInternal::handle_command(con, command).await?;
+        con.record_appended("user", appended);
+        Ok(id)
     }
 }
 
@@ -132,19 +136,24 @@ struct UserRow {
     id: Uuid,
     name: String,
     rent_limit: i32,
+    role: String,
+    password_hash: Option<String>,
     version: i64,
     is_deleted: bool,
 }
 
-impl From<UserRow> for User {
-    fn from(row: UserRow) -> Self {
-        User::new(
+impl TryFrom<UserRow> for User {
+    type Error = Report<KernelError>;
+    fn try_from(row: UserRow) -> Result<Self, Self::Error> {
+        Ok(User::new(
             UserId::new(row.id),
             UserName::new(row.name),
             UserRentLimit::new(row.rent_limit),
+            row.role.parse::<UserRole>()?,
+            row.password_hash.map(UserPasswordHash::new),
             EventVersion::new(row.version),
             IsDeleted::new(row.is_deleted),
-        )
+        ))
     }
 }
 
@@ -155,7 +164,11 @@ struct UserEventRowColumn {
     user_id: Uuid,
     name: Option<String>,
     rent_limit: Option<i32>,
+    role: Option<String>,
+    password_hash: Option<String>,
     created_at: OffsetDateTime,
+    #[sqlx(flatten)]
+    metadata: EventMetadataColumn,
 }
 
 impl TryFrom<UserEventRowColumn> for EventInfo<UserEvent, User> {
@@ -166,12 +179,15 @@ impl TryFrom<UserEventRowColumn> for EventInfo<UserEvent, User> {
             UserId::new(value.user_id),
             value.name.map(UserName::new),
             value.rent_limit.map(UserRentLimit::new),
+            value.role.as_deref().map(str::parse).transpose()?,
+            value.password_hash.map(UserPasswordHash::new),
         );
         let event = UserEvent::try_from(row)?;
         Ok(EventInfo::new(
             event,
             EventVersion::new(value.version),
             CreatedAt::new(value.created_at),
+            EventMetadata::from(value.metadata),
         ))
     }
 }
@@ -179,6 +195,7 @@ impl TryFrom<UserEventRowColumn> for EventInfo<UserEvent, User> {
 pub(in crate::database) struct PgUserInternal;
 
 impl PgUserInternal {
+    #[tracing::instrument(skip_all)]
     async fn get_all(
         con: &mut PgConnection,
         limit: &SelectLimit,
@@ -187,7 +204,7 @@ impl PgUserInternal {
         sqlx::query_as::<_, UserRow>(
             //language=postgresql
             r#"
-            SELECT id, name, rent_limit, version
+            SELECT id, name, rent_limit, role, password_hash, version, is_deleted
             FROM users
             ORDER BY id
             LIMIT $1
@@ -198,9 +215,12 @@ impl PgUserInternal {
         .bind(offset.as_ref())
         .fetch_all(con)
         .await
-        .convert_error()
-        .map(|vec| vec.into_iter().map(User::from).collect())
+        .convert_error()?
+        .into_iter()
+        .map(User::try_from)
+        .collect()
     }
+    #[tracing::instrument(skip_all)]
     async fn find_by_id(
         con: &mut PgConnection,
         id: &UserId,
@@ -208,7 +228,7 @@ impl PgUserInternal {
         let row = sqlx::query_as::<_, UserRow>(
             // language=postgresql
             r#"
-            SELECT id, name, rent_limit, version
+            SELECT id, name, rent_limit, role, password_hash, version, is_deleted
             FROM users
             WHERE id = $1
             "#,
@@ -217,21 +237,23 @@ impl PgUserInternal {
         .fetch_optional(con)
         .await
         .convert_error()?;
-        let found = row.map(User::from);
-        Ok(found)
+        row.map(User::try_from).transpose()
     }
 
+    #[tracing::instrument(skip_all)]
     async fn create(con: &mut PgConnection, user: &User) -> error_stack::Result<(), KernelError> {
         sqlx::query(
             // language=postgresql
             r#"
-            INSERT INTO users (id, name, rent_limit, version, is_deleted)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO users (id, name, rent_limit, role, password_hash, version, is_deleted)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#,
         )
         .bind(user.id().as_ref())
         .bind(user.name().as_ref())
         .bind(user.rent_limit().as_ref())
+        .bind(user.role().as_str())
+        .bind(user.password().as_ref().map(AsRef::as_ref))
         .bind(user.version().as_ref())
         .bind(user.is_deleted().as_ref())
         .execute(con)
@@ -240,17 +262,21 @@ impl PgUserInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn update(con: &mut PgConnection, user: &User) -> error_stack::Result<(), KernelError> {
         // language=postgresql
         sqlx::query(
             r#"
             UPDATE users
-            SET name = $2, version = $3, is_deleted = $4
+            SET name = $2, rent_limit = $3, role = $4, password_hash = $5, version = $6, is_deleted = $7
             WHERE id = $1
             "#,
         )
         .bind(user.id().as_ref())
         .bind(user.name().as_ref())
+        .bind(user.rent_limit().as_ref())
+        .bind(user.role().as_str())
+        .bind(user.password().as_ref().map(AsRef::as_ref))
         .bind(user.version().as_ref())
         .bind(user.is_deleted().as_ref())
         .execute(con)
@@ -259,6 +285,7 @@ impl PgUserInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn delete(
         con: &mut PgConnection,
         user_id: &UserId,
@@ -277,52 +304,76 @@ impl PgUserInternal {
         Ok(())
     }
 
+    #[tracing::instrument(skip_all)]
     async fn handle_command(
         con: &mut PgConnection,
         command: CommandInfo<UserEvent, User>,
-    ) -> error_stack::Result<UserId, KernelError> {
-        let DestructCommandInfo { event, version } = command.into_destruct();
+    ) -> error_stack::Result<(UserId, u64), KernelError> {
+        let DestructCommandInfo {
+            event,
+            version,
+            metadata,
+        } = command.into_destruct();
         let DestructUserEventRow {
             event_name,
             id,
             name,
             rent_limit,
+            role,
+            password,
         } = UserEventRow::from(event).into_destruct();
         let name = name.as_ref().map(AsRef::as_ref);
         let rent_limit = rent_limit.as_ref().map(AsRef::as_ref);
-        match version {
+        let role = role.as_ref().map(UserRole::as_str);
+        let password = password.as_ref().map(AsRef::as_ref);
+        let version: Option<EventVersion<User>> = match version {
+            None => None,
+            Some(ExpectedEventVersion::Nothing) => {
+                let event = PgUserInternal::get_events(con, &id, None).await?;
+                if !event.is_empty() {
+                    return Err(Report::new(KernelError::Concurrency)
+                        .attach_printable("Event stream is already exists"));
+                }
+                Some(EventVersion::new(1))
+            }
+            Some(ExpectedEventVersion::Exact(expected)) => {
+                let current = PgUserInternal::lock_stream(con, &id).await?;
+                if current.as_ref() != Some(&expected) {
+                    let message = format!("Stream is at {current:?}, expected {expected:?}");
+                    return Err(Report::new(KernelError::Concurrency).attach_printable(message));
+                }
+                None
+            }
+        };
+        let appended = match version {
             None => {
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO user_events (user_id, event_name, name, rent_limit) VALUES ($1, $2, $3, $4)
+                    INSERT INTO user_events (user_id, event_name, name, rent_limit, role, password_hash,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
                     "#,
                 )
                     .bind(id.as_ref())
                     .bind(event_name)
                     .bind(name)
                     .bind(rent_limit)
+                    .bind(role)
+                    .bind(password)
+                    .bind_metadata(&metadata)
                     .execute(con)
                     .await
-                    .convert_error()?;
+                    .convert_error()?
+.rows_affected()
             }
             Some(version) => {
-                let version = match version {
-                    ExpectedEventVersion::Nothing => {
-                        let event = PgUserInternal::get_events(con, &id, None).await?;
-                        if !event.is_empty() {
-                            return Err(Report::new(KernelError::Concurrency)
-                                .attach_printable("Event stream is already exists"));
-                        } else {
-                            EventVersion::new(1)
-                        }
-                    }
-                    ExpectedEventVersion::Exact(version) => version,
-                };
                 // language=postgresql
                 sqlx::query(
                     r#"
-                    INSERT INTO user_events (version, user_id, event_name, name, rent_limit) VALUES ($1, $2, $3, $4, $5)
+                    INSERT INTO user_events (version, user_id, event_name, name, rent_limit, role, password_hash,
+                                             actor_id, correlation_id, causation_id, client_ip, user_agent)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
                     "#,
                 )
                     .bind(version.as_ref())
@@ -330,14 +381,41 @@ impl PgUserInternal {
                     .bind(event_name)
                     .bind(name)
                     .bind(rent_limit)
+                    .bind(role)
+                    .bind(password)
+                    .bind_metadata(&metadata)
                     .execute(con)
                     .await
-                    .convert_error()?;
+                    .convert_error()?
+.rows_affected()
             }
-        }
-        Ok(id)
+        };
+        Ok((id, appended))
     }
 
+    /// Serializes writers of the stream until the transaction ends and returns its latest version
+    #[tracing::instrument(skip_all)]
+    async fn lock_stream(
+        con: &mut PgConnection,
+        id: &UserId,
+    ) -> error_stack::Result<Option<EventVersion<User>>, KernelError> {
+        // language=postgresql
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::uuid::text, 0))")
+            .bind(id.as_ref())
+            .execute(&mut *con)
+            .await
+            .convert_error()?;
+        // language=postgresql
+        let version: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM user_events WHERE user_id = $1")
+                .bind(id.as_ref())
+                .fetch_one(con)
+                .await
+                .convert_error()?;
+        Ok(version.map(EventVersion::new))
+    }
+
+    #[tracing::instrument(skip_all)]
     async fn get_events(
         con: &mut PgConnection,
         id: &UserId,
@@ -348,7 +426,7 @@ impl PgUserInternal {
                 // language=postgresql
                 sqlx::query_as::<_, UserEventRowColumn>(
                     r#"
-                    SELECT version, event_name, name, rent_limit, created_at
+                    SELECT version, user_id, event_name, name, rent_limit, role, password_hash, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM user_events
                     WHERE user_id = $1
                     "#,
@@ -358,7 +436,7 @@ impl PgUserInternal {
                 // language=postgresql
                 sqlx::query_as::<_, UserEventRowColumn>(
                     r#"
-                    SELECT version, event_name, name, rent_limit, created_at
+                    SELECT version, user_id, event_name, name, rent_limit, role, password_hash, created_at, actor_id, correlation_id, causation_id, client_ip, user_agent
                     FROM user_events
                     WHERE version > $1 AND user_id = $2
                     "#,
@@ -381,10 +459,12 @@ mod test {
     use uuid::Uuid;
 
     use kernel::interface::database::DatabaseConnection;
-    use kernel::interface::event::{CommandInfo, UserEvent};
+    use kernel::interface::event::{Applier, CommandInfo, EventMetadata, UserEvent};
     use kernel::interface::query::{UserEventQuery, UserQuery};
     use kernel::interface::update::{UserEventHandler, UserModifier};
-    use kernel::prelude::entity::{EventVersion, IsDeleted, User, UserId, UserName, UserRentLimit};
+    use kernel::prelude::entity::{
+        EventVersion, IsDeleted, User, UserId, UserName, UserPasswordHash, UserRentLimit, UserRole,
+    };
     use kernel::KernelError;
 
     use crate::database::postgres::user::PostgresUserRepository;
@@ -402,6 +482,8 @@ mod test {
             id.clone(),
             UserName::new("test".to_string()),
             UserRentLimit::new(1),
+            UserRole::Member,
+            None,
             EventVersion::new(0),
             IsDeleted::new(false),
         );
@@ -448,7 +530,7 @@ mod test {
             name,
             rent_limit,
         };
-        let create_command = CommandInfo::new(create_event, None);
+        let create_command = CommandInfo::new(create_event, None, EventMetadata::default());
         PostgresUserRepository
             .handle(&mut connection, create_command.clone())
             .await?;
@@ -465,7 +547,7 @@ mod test {
             name: Some(UserName::new("test2".to_string())),
             rent_limit: None,
         };
-        let update_command = CommandInfo::new(update_event, None);
+        let update_command = CommandInfo::new(update_event, None, EventMetadata::default());
         PostgresUserRepository
             .handle(&mut connection, update_command.clone())
             .await?;
@@ -476,7 +558,75 @@ mod test {
         assert_eq!(update_event.version(), &EventVersion::new(2));
         assert_eq!(update_event.event(), &update_command.into_destruct().event);
 
+        let role_event = UserEvent::ChangeRole {
+            id: id.clone(),
+            role: UserRole::Librarian,
+        };
+        let role_command = CommandInfo::new(role_event, None, EventMetadata::default());
+        PostgresUserRepository
+            .handle(&mut connection, role_command.clone())
+            .await?;
+        let role_event = PostgresUserRepository
+            .get_events(&mut connection, &id, Some(&EventVersion::new(2)))
+            .await?;
+        let role_event = role_event.first().unwrap();
+        assert_eq!(role_event.event(), &role_command.into_destruct().event);
+
         // TODO: create user entity
         Ok(())
     }
+
+    /// Login reads the hash of the latest password change or reset
+    #[test_with::env(POSTGRES_TEST)]
+    #[tokio::test]
+    async fn test_credentials() -> error_stack::Result<(), KernelError> {
+        let db = PostgresDatabase::new().await?;
+        let mut connection = db.transact().await?;
+        let id = UserId::new(Uuid::new_v4());
+
+        let events = [
+            UserEvent::Create {
+                id: id.clone(),
+                name: UserName::new("test".to_string()),
+                rent_limit: UserRentLimit::new(1),
+            },
+            UserEvent::ChangePassword {
+                id: id.clone(),
+                password: UserPasswordHash::new("changed".to_string()),
+            },
+            UserEvent::ResetPassword {
+                id: id.clone(),
+                password: UserPasswordHash::new("reset".to_string()),
+            },
+        ];
+        for event in events {
+            let command = CommandInfo::new(event, None, EventMetadata::default());
+            PostgresUserRepository
+                .handle(&mut connection, command)
+                .await?;
+        }
+        let events = PostgresUserRepository
+            .get_events(&mut connection, &id, None)
+            .await?;
+        assert_eq!(events.len(), 3);
+
+        let mut user: Option<User> = None;
+        for event in events {
+            user.apply(event);
+        }
+        let user = user.unwrap();
+        assert_eq!(
+            user.password(),
+            &Some(UserPasswordHash::new("reset".to_string()))
+        );
+
+        PostgresUserRepository
+            .create(&mut connection, &user)
+            .await?;
+        let found = PostgresUserRepository
+            .find_by_id(&mut connection, &id)
+            .await?;
+        assert_eq!(found, Some(user));
+        Ok(())
+    }
 }
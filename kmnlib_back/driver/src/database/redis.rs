@@ -1,15 +1,20 @@
+mod idempotency;
 mod mq;
+mod rate_limit;
+mod session;
 
 use crate::env;
 use crate::error::ConvertError;
+use crate::metrics::DB_POOL_CONNECTIONS;
 use deadpool_redis::redis::RedisError;
 use deadpool_redis::{Config, Connection, Pool, PoolError, Runtime};
 use error_stack::{Report, ResultExt};
 use kernel::interface::database::{DatabaseConnection, Transaction};
 use kernel::KernelError;
+use metrics::gauge;
 use std::ops::{Deref, DerefMut};
 
-pub use crate::database::redis::mq::*;
+pub use crate::database::redis::{idempotency::*, mq::*, rate_limit::*, session::*};
 
 const REDIS_URL: &str = "REDIS_URL";
 
@@ -26,6 +31,16 @@ impl RedisDatabase {
             .change_context_lazy(|| KernelError::Internal)?;
         Ok(Self { pool })
     }
+
+    pub fn record_pool_metrics(&self) {
+        let status = self.pool.status();
+        let idle = status.available;
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "redis", "state" => "active")
+            .set(status.size.saturating_sub(idle) as f64);
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "redis", "state" => "idle").set(idle as f64);
+        gauge!(DB_POOL_CONNECTIONS, "pool" => "redis", "state" => "max")
+            .set(status.max_size as f64);
+    }
 }
 
 impl Clone for RedisDatabase {
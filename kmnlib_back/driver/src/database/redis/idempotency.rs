@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use deadpool_redis::redis;
+use deadpool_redis::redis::AsyncCommands;
+use error_stack::ResultExt;
+use kernel::interface::idempotency::{DependOnIdempotencyStore, IdempotencyStore};
+use kernel::prelude::entity::{IdempotencyKey, IdempotencyRecord};
+use kernel::KernelError;
+
+use crate::database::{RedisDatabase, RedisTransaction};
+use crate::error::ConvertError;
+
+const IDEMPOTENCY: &str = "idempotency";
+
+pub struct RedisIdempotencyStore;
+
+#[async_trait::async_trait]
+impl IdempotencyStore for RedisIdempotencyStore {
+    type Transaction = RedisTransaction;
+
+    async fn reserve(
+        &self,
+        con: &mut RedisTransaction,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+        ttl: &Duration,
+    ) -> error_stack::Result<Option<IdempotencyRecord>, KernelError> {
+        let key = format!("{IDEMPOTENCY}:{}", key.as_ref());
+        let raw = serde_json::to_string(record).change_context_lazy(|| KernelError::Internal)?;
+        let reserved: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(raw)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut **con)
+            .await
+            .convert_error()?;
+        if reserved.is_some() {
+            return Ok(None);
+        }
+        let existing: Option<String> = con.get(&key).await.convert_error()?;
+        existing
+            .map(|raw| serde_json::from_str(&raw).change_context_lazy(|| KernelError::Internal))
+            .transpose()
+    }
+
+    async fn complete(
+        &self,
+        con: &mut RedisTransaction,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError> {
+        let key = format!("{IDEMPOTENCY}:{}", key.as_ref());
+        let raw = serde_json::to_string(record).change_context_lazy(|| KernelError::Internal)?;
+        con.set_ex(key, raw, ttl.as_secs().max(1))
+            .await
+            .convert_error()
+    }
+
+    async fn release(
+        &self,
+        con: &mut RedisTransaction,
+        key: &IdempotencyKey,
+    ) -> error_stack::Result<(), KernelError> {
+        let key = format!("{IDEMPOTENCY}:{}", key.as_ref());
+        con.del(key).await.convert_error()
+    }
+}
+
+impl DependOnIdempotencyStore for RedisDatabase {
+    type IdempotencyStore = RedisIdempotencyStore;
+    fn idempotency_store(&self) -> &Self::IdempotencyStore {
+        &RedisIdempotencyStore
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use kernel::interface::database::DatabaseConnection;
+    use kernel::interface::idempotency::IdempotencyStore;
+    use kernel::prelude::entity::{
+        IdempotencyKey, IdempotencyRecord, IdempotentResponse, RequestFingerprint,
+    };
+    use kernel::KernelError;
+    use uuid::Uuid;
+
+    use crate::database::redis::idempotency::RedisIdempotencyStore;
+    use crate::database::RedisDatabase;
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_idempotency() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let ttl = Duration::from_secs(10);
+        let key = IdempotencyKey::new(Uuid::new_v4().to_string());
+        let pending = IdempotencyRecord::new(RequestFingerprint::new("fingerprint"), None);
+
+        let reserved = RedisIdempotencyStore
+            .reserve(&mut con, &key, &pending, &ttl)
+            .await?;
+        assert!(reserved.is_none());
+        let reserved = RedisIdempotencyStore
+            .reserve(&mut con, &key, &pending, &ttl)
+            .await?;
+        assert_eq!(reserved, Some(pending.clone()));
+
+        let completed = IdempotencyRecord::new(
+            RequestFingerprint::new("fingerprint"),
+            Some(IdempotentResponse {
+                status: 201,
+                headers: vec![("content-type".to_string(), "application/json".to_string())],
+                body: "{}".to_string(),
+            }),
+        );
+        RedisIdempotencyStore
+            .complete(&mut con, &key, &completed, &ttl)
+            .await?;
+        let reserved = RedisIdempotencyStore
+            .reserve(&mut con, &key, &pending, &ttl)
+            .await?;
+        assert_eq!(reserved, Some(completed));
+
+        RedisIdempotencyStore.release(&mut con, &key).await?;
+        let reserved = RedisIdempotencyStore
+            .reserve(&mut con, &key, &pending, &ttl)
+            .await?;
+        assert!(reserved.is_none());
+        Ok(())
+    }
+}
@@ -1,22 +1,36 @@
 use crate::database::RedisDatabase;
 use crate::error::ConvertError;
+use crate::metrics::{MQ_JOBS_TOTAL, MQ_QUEUE_DEPTH};
+use crate::telemetry::current_trace_context;
 use deadpool_redis::redis::AsyncCommands;
 use deadpool_redis::{redis, Connection};
 use error_stack::{Report, ResultExt};
 use kernel::interface::database::DatabaseConnection;
 use kernel::interface::mq::MQConfig;
+use kernel::interface::mq::{
+    AttemptRecord, ErrorKind, ErroredInfo, ErroredInfoFilter, InfoSort, JobState, JobStatus,
+    Priority, QuarantinedInfo, QueueInfo,
+};
+use kernel::interface::mq::{DecodeError, Payload};
 use kernel::interface::mq::{DestructQueueInfo, ErrorOperation, MessageQueue};
-use kernel::interface::mq::{ErroredInfo, QueueInfo};
-use kernel::interface::mq::{Handler, HandlerContainer, HandlerConverter};
+use kernel::interface::mq::{Handler, HandlerContainer, HandlerConverter, WorkerState};
+use kernel::interface::mq::{JobContext, Layer, Layers, Next};
+use kernel::interface::mq::{Schedule, ScheduleState};
 use kernel::KernelError;
+use metrics::{counter, gauge};
 use redis::streams::StreamReadOptions;
-use redis::{RedisResult, Value};
+use redis::{RedisResult, Script, Value};
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::str::from_utf8;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
 use std::time::Duration;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::{debug, error, warn};
 use uuid::Uuid;
@@ -24,43 +38,63 @@ use uuid::Uuid;
 #[derive(Debug)]
 struct QueueData<T> {
     id: String,
+    /// Lane the entry was read from
+    priority: Priority,
     delivered_count: i64,
     info: QueueInfo<T>,
 }
 
+struct WorkerHandle {
+    member: String,
+    heartbeat: Arc<AtomicI64>,
+    handle: JoinHandle<()>,
+}
+
 pub struct RedisMessageQueue<M, T>
 where
     M: 'static + Clone + Send + Sync,
-    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Sync + Send,
+    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Payload + Sync + Send,
 {
     name: String,
     db: RedisDatabase,
     module: M,
     config: MQConfig,
     worker_process: Mutex<Box<dyn HandlerConverter<M, T>>>,
+    layers: Vec<Arc<dyn Layer<M, T>>>,
+    workers: Mutex<Vec<WorkerHandle>>,
+    schedules: Arc<Mutex<Vec<Schedule<T>>>>,
     _data_type: PhantomData<T>,
 }
 
 impl<M, T> RedisMessageQueue<M, T>
 where
     M: 'static + Clone + Send + Sync,
-    T: Clone + Serialize + for<'de> Deserialize<'de> + Sync + Send,
+    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Payload + Sync + Send,
 {
-    #[tracing::instrument(skip(db, module, block))]
+    /// Runs every job through `layer` before the handler, the first layer attached is the outermost.
+    /// Only workers started afterwards pick it up
+    pub fn with_layer(mut self, layer: impl Layer<M, T>) -> Self {
+        self.layers.push(Arc::new(layer));
+        self
+    }
+
+    #[tracing::instrument(skip(db, module, block, layers, heartbeat))]
+    #[allow(clippy::too_many_arguments)]
     async fn listen(
         db: RedisDatabase,
         module: M,
         name: String,
+        member_name: String,
         config: MQConfig,
         block: Box<dyn HandlerConverter<M, T>>,
+        layers: Layers<M, T>,
+        heartbeat: Arc<AtomicI64>,
     ) {
-        let member_name = format!("consumer:{}", Uuid::new_v4());
+        // A blocking read across lanes can hand out more than one entry, they are ours to process
+        let mut popped: VecDeque<QueueData<T>> = VecDeque::new();
         loop {
-            let QueueData {
-                id,
-                delivered_count,
-                info,
-            } = {
+            heartbeat.store(now_millis(), Ordering::Relaxed);
+            if popped.is_empty() {
                 let mut con = match db.transact().await {
                     Ok(con) => con,
                     Err(report) => {
@@ -69,31 +103,69 @@ where
                         continue;
                     }
                 };
-                let mut result = RedisJobInternal::pop_pending::<T>(
-                    &mut con,
-                    &name,
-                    &member_name,
-                    config.retry_delay(),
-                )
-                .await;
-                if result.is_err() || result.as_ref().is_ok_and(Option::is_none) {
-                    result = RedisJobInternal::pop_to_process(&mut con, &name, &member_name).await;
-                }
-                match result {
-                    Ok(Some(data)) => data,
-                    Ok(None) => continue,
+                match Self::pop_next(&mut con, &name, &member_name, &config).await {
+                    Ok(data) => popped.extend(data),
                     Err(report) => {
                         error!("{report:?}");
                         sleep(Duration::from_secs(1)).await;
                         continue;
                     }
                 }
+            }
+            let Some(QueueData {
+                id,
+                priority,
+                delivered_count,
+                info,
+            }) = popped.pop_front()
+            else {
+                continue;
             };
-            debug!("Processing Id: {id}, TryCount: {delivered_count}");
-            let DestructQueueInfo { id: uuid, data }: DestructQueueInfo<T> = info.into_destruct();
-            let result = block
-                .clone_box()
-                .convert(module.clone(), data.clone())
+            match Self::take_turn(&db, &name, &config, &priority, &id, &info).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(report) => {
+                    // Stays pending, so it is claimed again after `reclaim_idle`
+                    error!("{report:?}");
+                    continue;
+                }
+            }
+            // Redeliveries after a crashed worker count as attempts too
+            let attempt = info.attempts() + delivered_count + 1;
+            debug!("Processing Id: {id}, Attempt: {attempt}");
+            let policy = info
+                .retry_policy()
+                .clone()
+                .unwrap_or_else(|| config.retry_policy().clone());
+            let retry = info.clone();
+            let DestructQueueInfo {
+                id: uuid,
+                data,
+                trace_context,
+                unique_key,
+                partition_key,
+                ..
+            }: DestructQueueInfo<T> = info.into_destruct();
+            if let Ok(mut con) = db.transact().await {
+                if let Err(report) =
+                    RedisJobInternal::update_status(&mut con, &name, &uuid, &config, |status| {
+                        status.processing(attempt)
+                    })
+                    .await
+                {
+                    error!("{report:?}");
+                }
+            }
+            let started_at = OffsetDateTime::now_utc();
+            let job = JobContext::new(
+                name.clone(),
+                uuid,
+                attempt,
+                member_name.clone(),
+                trace_context,
+            );
+            let result = Next::new(layers.clone(), block.clone_box())
+                .run(job, module.clone(), data.clone())
                 .await;
             {
                 let transact = db.transact().await;
@@ -105,52 +177,442 @@ where
                     }
                 };
 
-                if let Err(report) = result {
-                    if delivered_count > (*config.max_retry()).into() {
-                        if let Err(report) = RedisJobInternal::push_failed_info(
+                match result {
+                    Err(report)
+                        if matches!(report.current_context(), ErrorOperation::Delay)
+                            && policy.can_retry(attempt) =>
+                    {
+                        let stack_trace = format!("{report:?}");
+                        let kind = report.downcast_ref::<ErrorKind>().copied();
+                        let record = AttemptRecord::new(
+                            attempt,
+                            member_name.clone(),
+                            started_at,
+                            *report.current_context(),
+                            kind,
+                            stack_trace.clone(),
+                        );
+                        let errored = ErroredInfo::new(uuid, data, stack_trace.clone(), kind)
+                            .with_envelope(&retry);
+                        let run_at =
+                            OffsetDateTime::now_utc() + policy.delay(attempt, rand::random());
+                        let mut retry = retry.into_destruct();
+                        retry.attempts = attempt;
+                        let member = match RedisJobInternal::reschedule(
+                            &mut con,
+                            &name,
+                            &priority,
+                            &id,
+                            &retry.freeze(),
+                            run_at,
+                        )
+                        .await
+                        {
+                            Ok(member) => member,
+                            Err(report) => {
+                                // Stays pending, so it is claimed again after `reclaim_idle`
+                                error!("{report:?}");
+                                continue;
+                            }
+                        };
+                        if let Err(report) = RedisJobInternal::update_status(
+                            &mut con,
+                            &name,
+                            &uuid,
+                            &config,
+                            |status| status.delayed(stack_trace.clone(), run_at),
+                        )
+                        .await
+                        {
+                            error!("{report:?}");
+                        }
+                        let errored =
+                            RedisJobInternal::record_attempt(&mut con, &name, errored, record)
+                                .await;
+                        if let Err(report) =
+                            RedisJobInternal::push_delayed_info(&mut con, &name, &member, &errored)
+                                .await
+                        {
+                            error!("{report:?}");
+                        }
+                        counter!(MQ_JOBS_TOTAL, "queue" => name.clone(), "outcome" => "delayed")
+                            .increment(1);
+                        warn!("Delayed Id: {id}, Attempt: {attempt}, NextAttempt: {run_at}, Report: {report:?}");
+                        continue;
+                    }
+                    Err(report) => {
+                        let reason = match report.current_context() {
+                            ErrorOperation::Failed => "Task failed".to_string(),
+                            ErrorOperation::Delay => format!("Task delayed {attempt} times"),
+                        };
+                        let kind = report.downcast_ref::<ErrorKind>().copied();
+                        let operation = *report.current_context();
+                        let stack_trace = format!("{:?}", report.attach_printable(reason));
+                        let record = AttemptRecord::new(
+                            attempt,
+                            member_name.clone(),
+                            started_at,
+                            operation,
+                            kind,
+                            stack_trace.clone(),
+                        );
+                        if let Err(report) = RedisJobInternal::update_status(
                             &mut con,
                             &name,
-                            format!(
-                                "{:?}",
-                                report.attach_printable("Task failed or 3 time delayed")
-                            ),
-                            uuid,
-                            data,
+                            &uuid,
+                            &config,
+                            |status| status.failed(stack_trace.clone()),
                         )
                         .await
                         {
                             error!("{report:?}");
                         }
-                        error!("Failed Id: {id}, TryCount: {delivered_count}");
-                    } else if let ErrorOperation::Delay = report.current_context() {
-                        if let Err(report) = RedisJobInternal::push_delayed_info(
+                        let errored = RedisJobInternal::record_attempt(
+                            &mut con,
+                            &name,
+                            ErroredInfo::new(uuid, data, stack_trace, kind).with_envelope(&retry),
+                            record,
+                        )
+                        .await;
+                        if let Err(report) =
+                            RedisJobInternal::push_failed_info(&mut con, &name, &errored).await
+                        {
+                            error!("{report:?}");
+                        }
+                        counter!(MQ_JOBS_TOTAL, "queue" => name.clone(), "outcome" => "failed")
+                            .increment(1);
+                        error!("Failed Id: {id}, Attempt: {attempt}");
+                    }
+                    Ok(output) => {
+                        if let Err(report) = RedisJobInternal::update_status(
                             &mut con,
                             &name,
-                            uuid,
-                            data,
-                            format!("{report:?}"),
+                            &uuid,
+                            &config,
+                            |status| status.done(output),
                         )
                         .await
                         {
                             error!("{report:?}");
                         }
-                        warn!("Delayed Id: {id}, TryCount: {delivered_count}, Report: {report:?}");
-                        continue;
+                        counter!(MQ_JOBS_TOTAL, "queue" => name.clone(), "outcome" => "done")
+                            .increment(1);
+                        debug!("Done Id: {id}, Attempt: {attempt}");
                     }
-                } else {
-                    debug!("Done Id: {id}, TryCount: {delivered_count}");
                 }
-                if let Err(report) = RedisJobInternal::mark_done(&mut con, &name, &id).await {
+                if let Err(report) =
+                    RedisJobInternal::mark_done(&mut con, &name, &priority, &id).await
+                {
                     error!("{report:?}");
-                } else if delivered_count > 0 {
+                    continue;
+                }
+                if attempt > 1 {
                     if let Err(report) =
                         RedisJobInternal::remove_delayed_info(&mut con, &name, &uuid).await
                     {
                         error!("{report:?}");
                     };
-                };
+                }
+                if let Some(key) = &unique_key {
+                    if let Err(report) =
+                        RedisJobInternal::release_unique(&mut con, &name, key, &uuid).await
+                    {
+                        error!("{report:?}");
+                    }
+                }
+                if let Some(key) = &partition_key {
+                    if let Err(report) =
+                        RedisJobInternal::leave_partition(&mut con, &name, key, &uuid).await
+                    {
+                        error!("{report:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns `false` once a job waiting behind others of its partition was put back for later.
+    /// A delayed job keeps its place, so the ones behind it wait for its retries
+    async fn take_turn(
+        db: &RedisDatabase,
+        name: &str,
+        config: &MQConfig,
+        priority: &Priority,
+        entry: &str,
+        info: &QueueInfo<T>,
+    ) -> error_stack::Result<bool, KernelError> {
+        let Some(key) = info.partition_key() else {
+            return Ok(true);
+        };
+        let mut con = db.transact().await?;
+        // Jobs promoted from the scheduled set join their partition here
+        let ahead =
+            RedisJobInternal::join_partition(&mut con, name, key, info.id(), config).await?;
+        if ahead == 0 {
+            return Ok(true);
+        }
+        let wait = PARTITION_WAIT
+            .saturating_mul(u32::try_from(ahead).unwrap_or(u32::MAX))
+            .min(PARTITION_MAX_WAIT);
+        RedisJobInternal::reschedule(
+            &mut con,
+            name,
+            priority,
+            entry,
+            info,
+            OffsetDateTime::now_utc() + wait,
+        )
+        .await?;
+        debug!(
+            "Deferred Id: {}, Partition: {key}, Ahead: {ahead}",
+            info.id()
+        );
+        Ok(false)
+    }
+
+    /// Jobs claimed back from dead workers come first, then waiting ones in [`lane_order`]
+    async fn pop_next(
+        con: &mut Connection,
+        name: &str,
+        member: &str,
+        config: &MQConfig,
+    ) -> error_stack::Result<Vec<QueueData<T>>, KernelError> {
+        for priority in Priority::ALL {
+            match RedisJobInternal::pop_pending(con, name, &priority, member, config).await {
+                Ok(Some(data)) => return Ok(vec![data]),
+                Ok(None) => {}
+                Err(report) => error!("{report:?}"),
+            }
+        }
+        let order = lane_order(rand::random());
+        for priority in &order {
+            let data =
+                RedisJobInternal::pop_to_process(con, name, &[*priority], member, config, None)
+                    .await?;
+            if !data.is_empty() {
+                return Ok(data);
+            }
+        }
+        // Every lane is empty, wait for whichever gets a job first
+        let mut data =
+            RedisJobInternal::pop_to_process(con, name, &order, member, config, Some(1000)).await?;
+        data.sort_by_key(|data| data.priority);
+        Ok(data)
+    }
+
+    /// Fires due schedules and moves due scheduled jobs into the stream, every instance runs one.
+    /// The schedule lock and the script keep a job from being queued twice
+    #[tracing::instrument(skip(db, schedules, heartbeat))]
+    async fn schedule(
+        db: RedisDatabase,
+        name: String,
+        config: MQConfig,
+        schedules: Arc<Mutex<Vec<Schedule<T>>>>,
+        heartbeat: Arc<AtomicI64>,
+    ) {
+        loop {
+            heartbeat.store(now_millis(), Ordering::Relaxed);
+            let schedules = schedules
+                .lock()
+                .map(|schedules| schedules.clone())
+                .unwrap_or_default();
+            let promoted = match db.transact().await {
+                Ok(mut con) => {
+                    for schedule in &schedules {
+                        if let Err(report) =
+                            Self::run_schedule(&mut con, &name, &config, schedule, false).await
+                        {
+                            error!("{report:?}");
+                        }
+                    }
+                    RedisJobInternal::promote_lanes(&mut con, &name).await
+                }
+                Err(report) => Err(report),
+            };
+            match promoted {
+                Ok(counts) if counts.iter().any(|count| *count >= PROMOTE_BATCH) => continue,
+                Ok(counts) => {
+                    let count: usize = counts.iter().sum();
+                    if count > 0 {
+                        debug!("Promoted {count} scheduled jobs");
+                    }
+                    sleep(*config.schedule_interval()).await;
+                }
+                Err(report) => {
+                    error!("{report:?}");
+                    sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    /// Queues the job of `schedule` if it is due, or right away if `force`d.
+    /// Returns `None` if it is not due or another instance holds its lock
+    async fn run_schedule(
+        con: &mut Connection,
+        name: &str,
+        config: &MQConfig,
+        schedule: &Schedule<T>,
+        force: bool,
+    ) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let Some(token) = RedisJobInternal::lock_schedule(con, name, schedule.name()).await? else {
+            return Ok(None);
+        };
+        let result = Self::fire_schedule(con, name, config, schedule, force).await;
+        RedisJobInternal::unlock_schedule(con, name, schedule.name(), &token).await?;
+        result
+    }
+
+    /// Only called with the schedule lock held
+    async fn fire_schedule(
+        con: &mut Connection,
+        name: &str,
+        config: &MQConfig,
+        schedule: &Schedule<T>,
+        force: bool,
+    ) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let now = OffsetDateTime::now_utc();
+        let state = match RedisJobInternal::get_schedule_state(con, name, schedule.name()).await? {
+            Some(state) if state.expression() == schedule.expression() => state,
+            // Never ran, or the expression changed since
+            _ => {
+                let state = ScheduleState::new(schedule, now);
+                RedisJobInternal::set_schedule_state(con, name, &state).await?;
+                state
+            }
+        };
+        if !force && !state.is_due(now) {
+            return Ok(None);
+        }
+        // Runs missed while no instance was up are folded into this one
+        let next_run = match force {
+            true => *state.next_run(),
+            false => schedule.next_after(now),
+        };
+        let info = QueueInfo::from(schedule.data().clone());
+        let id = Self::enqueue(con, name, config, &info).await?;
+        RedisJobInternal::set_schedule_state(con, name, &state.ran(id, now, next_run)).await?;
+        debug!("Schedule: {}, Queued Id: {id}", schedule.name());
+        Ok(Some(id))
+    }
+
+    async fn enqueue(
+        con: &mut Connection,
+        name: &str,
+        config: &MQConfig,
+        info: &QueueInfo<T>,
+    ) -> error_stack::Result<Uuid, KernelError> {
+        let id = *info.id();
+        let Some(key) = info.partition_key() else {
+            Self::insert(con, name, config, info).await?;
+            return Ok(id);
+        };
+        RedisJobInternal::join_partition(con, name, key, &id, config).await?;
+        if let Err(report) = Self::insert(con, name, config, info).await {
+            // Would hold up the jobs queued behind it until the partition expires
+            RedisJobInternal::leave_partition(con, name, key, &id).await?;
+            return Err(report);
+        }
+        Ok(id)
+    }
+
+    async fn insert(
+        con: &mut Connection,
+        name: &str,
+        config: &MQConfig,
+        info: &QueueInfo<T>,
+    ) -> error_stack::Result<(), KernelError> {
+        // Recorded before the job is visible to workers, so it never overwrites their transitions
+        let status = JobStatus::queued(*info.id()).with_submitter(*info.submitter());
+        RedisJobInternal::set_status(con, name, &status, config.status_ttl()).await?;
+        RedisJobInternal::insert_waiting(con, name, info).await
+    }
+
+    /// Returns the pending job `info` is merged into, or `None` once it holds `key`.
+    /// Within a partition a job that others were queued behind is not merged into, so a resend
+    /// does not skip them. `join` has `info` join its partition in the same step
+    async fn claim(
+        con: &mut Connection,
+        name: &str,
+        config: &MQConfig,
+        info: &QueueInfo<T>,
+        key: &str,
+        join: bool,
+    ) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let pending = match info.partition_key() {
+            Some(partition_key) => {
+                RedisJobInternal::claim_unique_in_partition(
+                    con,
+                    name,
+                    key,
+                    partition_key,
+                    info.id(),
+                    config,
+                    join,
+                )
+                .await?
+            }
+            None => {
+                RedisJobInternal::claim_unique(con, name, key, info.id(), config.unique_window())
+                    .await?
+            }
+        };
+        if let Some(pending) = &pending {
+            debug!("Merged into pending Id: {pending}, Key: {key}");
+        }
+        Ok(pending)
+    }
+
+    async fn filter_infos(
+        &self,
+        hash: &str,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError> {
+        let mut con = self.db.transact().await?;
+        let infos: Vec<ErroredInfo<T>> =
+            RedisJobInternal::scan_errored_infos(&mut con, hash).await?;
+        let mut matched = Vec::new();
+        for info in infos {
+            if matches(filter, &info)? {
+                matched.push(info);
             }
         }
+        Ok(matched)
+    }
+
+    async fn list_infos(
+        &self,
+        hash: &str,
+        filter: &ErroredInfoFilter,
+        sort: &Option<InfoSort>,
+        size: &i64,
+        offset: &i64,
+    ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError> {
+        let size = usize::try_from(*size).unwrap_or(0);
+        let offset = usize::try_from(*offset).unwrap_or(0);
+        let mut infos = self.filter_infos(hash, filter).await?;
+        if let Some(sort) = sort {
+            infos.sort_by(|a, b| sort.compare(a, b));
+        }
+        Ok(infos.into_iter().skip(offset).take(size).collect())
+    }
+
+    pub async fn record_depth_metrics(&self) -> error_stack::Result<(), KernelError> {
+        let waiting = self.get_queued_len().await?;
+        let scheduled = self.get_scheduled_len().await?;
+        let delayed = self.get_delayed_len().await?;
+        let failed = self.get_failed_len().await?;
+        let quarantined = self.get_quarantined_len().await?;
+        for (state, len) in [
+            ("waiting", waiting),
+            ("scheduled", scheduled),
+            ("delayed", delayed),
+            ("failed", failed),
+            ("quarantined", quarantined),
+        ] {
+            gauge!(MQ_QUEUE_DEPTH, "queue" => self.name.clone(), "state" => state).set(len as f64);
+        }
+        Ok(())
     }
 }
 
@@ -158,7 +620,7 @@ where
 impl<M, T> MessageQueue<M, T> for RedisMessageQueue<M, T>
 where
     M: 'static + Clone + Send + Sync,
-    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Sync + Send,
+    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Payload + Sync + Send,
 {
     type DatabaseConnection = RedisDatabase;
 
@@ -180,6 +642,9 @@ where
             module,
             config,
             worker_process: Mutex::new(Box::new(container)),
+            layers: Vec::new(),
+            workers: Mutex::new(Vec::new()),
+            schedules: Arc::new(Mutex::new(Vec::new())),
             _data_type: PhantomData,
         }
     }
@@ -198,36 +663,226 @@ where
                 Err(_) => continue,
             };
             let name = self.name.clone();
+            let member = format!("consumer:{}", Uuid::new_v4());
             let config = self.config.clone();
+            let layers: Layers<M, T> = self.layers.clone().into();
+            let heartbeat = Arc::new(AtomicI64::new(now_millis()));
+            let handle = {
+                let member = member.clone();
+                let heartbeat = heartbeat.clone();
+                tokio::spawn(async move {
+                    RedisMessageQueue::listen(
+                        db, module, name, member, config, process, layers, heartbeat,
+                    )
+                    .await;
+                })
+            };
+            if let Ok(mut workers) = self.workers.lock() {
+                workers.push(WorkerHandle {
+                    member,
+                    heartbeat,
+                    handle,
+                });
+            }
+            i += 1;
+        }
+
+        let db = self.db.clone();
+        let name = self.name.clone();
+        let member = format!("scheduler:{}", Uuid::new_v4());
+        let config = self.config.clone();
+        let schedules = self.schedules.clone();
+        let heartbeat = Arc::new(AtomicI64::new(now_millis()));
+        let handle = {
+            let heartbeat = heartbeat.clone();
             tokio::spawn(async move {
-                RedisMessageQueue::listen(db, module, name, config, process).await;
+                RedisMessageQueue::<M, T>::schedule(db, name, config, schedules, heartbeat).await;
+            })
+        };
+        if let Ok(mut workers) = self.workers.lock() {
+            workers.push(WorkerHandle {
+                member,
+                heartbeat,
+                handle,
             });
-            i += 1;
         }
     }
 
-    async fn queue(&self, info: &QueueInfo<T>) -> error_stack::Result<(), KernelError> {
+    fn get_worker_states(&self) -> Vec<WorkerState> {
+        let Ok(workers) = self.workers.lock() else {
+            return Vec::new();
+        };
+        let now = now_millis();
+        let stuck_timeout =
+            i64::try_from(self.config.stuck_timeout().as_millis()).unwrap_or(i64::MAX);
+        workers
+            .iter()
+            .map(|worker| {
+                let heartbeat = worker.heartbeat.load(Ordering::Relaxed);
+                let last_heartbeat =
+                    OffsetDateTime::from_unix_timestamp_nanos(i128::from(heartbeat) * 1_000_000)
+                        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+                WorkerState::new(
+                    worker.member.clone(),
+                    !worker.handle.is_finished(),
+                    now - heartbeat > stuck_timeout,
+                    last_heartbeat,
+                )
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip_all, fields(queue = %self.name))]
+    async fn queue(&self, info: &QueueInfo<T>) -> error_stack::Result<Uuid, KernelError> {
         let name = &self.name;
+        let info = info.clone().with_trace_context(current_trace_context());
         let mut con = self.db.transact().await?;
-        RedisJobInternal::insert_waiting(&mut con, name, info).await
+        let Some(key) = info.unique_key() else {
+            return Self::enqueue(&mut con, name, &self.config, &info).await;
+        };
+        if let Some(pending) = Self::claim(&mut con, name, &self.config, &info, key, true).await? {
+            return Ok(pending);
+        }
+        let queued = Self::enqueue(&mut con, name, &self.config, &info).await;
+        if queued.is_err() {
+            RedisJobInternal::release_unique(&mut con, name, key, info.id()).await?;
+        }
+        queued
     }
 
-    async fn get_queued_len(&self) -> error_stack::Result<usize, KernelError> {
+    #[tracing::instrument(skip_all, fields(queue = %self.name))]
+    async fn queue_at(
+        &self,
+        info: &QueueInfo<T>,
+        when: OffsetDateTime,
+    ) -> error_stack::Result<Uuid, KernelError> {
+        let delay = when - OffsetDateTime::now_utc();
+        if !delay.is_positive() {
+            return self.queue(info).await;
+        }
         let name = &self.name;
+        let info = info.clone().with_trace_context(current_trace_context());
+        let id = *info.id();
+        let ttl = delay.unsigned_abs() + *self.config.status_ttl();
+        let mut con = self.db.transact().await?;
+        if let Some(key) = info.unique_key() {
+            // Joins its partition once it is due, like any other scheduled job
+            if let Some(pending) =
+                Self::claim(&mut con, name, &self.config, &info, key, false).await?
+            {
+                return Ok(pending);
+            }
+        }
+        let status = JobStatus::scheduled(id, when).with_submitter(*info.submitter());
+        let scheduled = async {
+            RedisJobInternal::set_status(&mut con, name, &status, &ttl).await?;
+            RedisJobInternal::insert_scheduled(&mut con, name, &info, when).await
+        }
+        .await;
+        if let Err(report) = scheduled {
+            if let Some(key) = info.unique_key() {
+                RedisJobInternal::release_unique(&mut con, name, key, &id).await?;
+            }
+            return Err(report);
+        }
+        Ok(id)
+    }
+
+    fn register_schedule(&self, schedule: Schedule<T>) {
+        if let Ok(mut schedules) = self.schedules.lock() {
+            schedules.retain(|registered| registered.name() != schedule.name());
+            schedules.push(schedule);
+        }
+    }
+
+    async fn get_schedules(&self) -> error_stack::Result<Vec<ScheduleState>, KernelError> {
+        let schedules = self
+            .schedules
+            .lock()
+            .map(|schedules| schedules.clone())
+            .unwrap_or_default();
+        let now = OffsetDateTime::now_utc();
+        let mut con = self.db.transact().await?;
+        let mut states = Vec::new();
+        for schedule in &schedules {
+            let state =
+                RedisJobInternal::get_schedule_state(&mut con, &self.name, schedule.name()).await?;
+            states.push(match state {
+                Some(state) if state.expression() == schedule.expression() => state,
+                _ => ScheduleState::new(schedule, now),
+            });
+        }
+        Ok(states)
+    }
+
+    #[tracing::instrument(skip(self), fields(queue = %self.name))]
+    async fn trigger_schedule(&self, name: &str) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let schedule = self.schedules.lock().ok().and_then(|schedules| {
+            schedules
+                .iter()
+                .find(|schedule| schedule.name() == name)
+                .cloned()
+        });
+        let Some(schedule) = schedule else {
+            return Ok(None);
+        };
+        let mut con = self.db.transact().await?;
+        match Self::run_schedule(&mut con, &self.name, &self.config, &schedule, true).await? {
+            Some(id) => Ok(Some(id)),
+            None => Err(Report::new(KernelError::Concurrency)
+                .attach_printable(format!("Schedule {name} is being run by another instance"))),
+        }
+    }
+
+    async fn get_job_status(
+        &self,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<JobStatus>, KernelError> {
+        let mut con = self.db.transact().await?;
+        RedisJobInternal::get_status(&mut con, &self.name, id).await
+    }
+
+    async fn get_queued_len(&self) -> error_stack::Result<usize, KernelError> {
+        let mut len = 0;
+        for priority in Priority::ALL {
+            len += self.get_queued_len_by_priority(&priority).await?;
+        }
+        Ok(len)
+    }
+
+    async fn get_queued_len_by_priority(
+        &self,
+        priority: &Priority,
+    ) -> error_stack::Result<usize, KernelError> {
+        let name = lane(&self.name, priority);
         let mut con = self.db.transact().await?;
-        RedisJobInternal::get_wait_len(&mut con, name)
+        RedisJobInternal::get_wait_len(&mut con, &name)
             .await
             .and_then(|size| usize::try_from(size).change_context_lazy(|| KernelError::Internal))
     }
 
+    async fn get_scheduled_len(&self) -> error_stack::Result<usize, KernelError> {
+        let mut con = self.db.transact().await?;
+        let mut len = 0;
+        for priority in Priority::ALL {
+            let size: i64 = con
+                .zcard(scheduled(&self.name, &priority))
+                .await
+                .convert_error()?;
+            len += usize::try_from(size).change_context_lazy(|| KernelError::Internal)?;
+        }
+        Ok(len)
+    }
+
     async fn get_delayed_infos(
         &self,
+        filter: &ErroredInfoFilter,
+        sort: &Option<InfoSort>,
         size: &i64,
         offset: &i64,
     ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError> {
-        let name = delayed(&self.name);
-        let mut con = self.db.transact().await?;
-        RedisJobInternal::get_infos_from_hash(&mut con, &name, size, offset).await
+        self.list_infos(&delayed(&self.name), filter, sort, size, offset)
+            .await
     }
 
     async fn get_delayed_info(
@@ -236,7 +891,7 @@ where
     ) -> error_stack::Result<Option<ErroredInfo<T>>, KernelError> {
         let name = delayed(&self.name);
         let mut con = self.db.transact().await?;
-        RedisJobInternal::get_info_from_hash(&mut con, &name, id).await
+        RedisJobInternal::get_errored_info(&mut con, &name, id).await
     }
 
     async fn get_delayed_len(&self) -> error_stack::Result<usize, KernelError> {
@@ -247,12 +902,13 @@ where
 
     async fn get_failed_infos(
         &self,
+        filter: &ErroredInfoFilter,
+        sort: &Option<InfoSort>,
         size: &i64,
         offset: &i64,
     ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError> {
-        let mut con = self.db.transact().await?;
-        let name = failed(&self.name);
-        RedisJobInternal::get_infos_from_hash(&mut con, &name, size, offset).await
+        self.list_infos(&failed(&self.name), filter, sort, size, offset)
+            .await
     }
 
     async fn get_failed_info(
@@ -261,7 +917,7 @@ where
     ) -> error_stack::Result<Option<ErroredInfo<T>>, KernelError> {
         let mut con = self.db.transact().await?;
         let name = failed(&self.name);
-        RedisJobInternal::get_info_from_hash(&mut con, &name, id).await
+        RedisJobInternal::get_errored_info(&mut con, &name, id).await
     }
 
     async fn get_failed_len(&self) -> error_stack::Result<usize, KernelError> {
@@ -269,32 +925,410 @@ where
         let mut con = self.db.transact().await?;
         RedisJobInternal::get_hash_len(&mut con, &name).await
     }
-}
-
-const QUEUE_FIELD: &str = "info";
 
-fn group(name: &str) -> String {
-    format!("g:{name}")
-}
+    async fn retry_delayed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        // A worker has already picked it up again
+        let status = RedisJobInternal::get_status(&mut con, name, id).await?;
+        if status.is_some_and(|status| status.state() != &JobState::Delayed) {
+            return Ok(false);
+        }
+        let Some(member) = RedisJobInternal::get_delayed_entry(&mut con, name, id).await? else {
+            return Ok(false);
+        };
+        RedisJobInternal::make_due(&mut con, name, &member).await
+    }
 
-fn failed(name: &str) -> String {
-    format!("failed:{name}")
-}
+    async fn retry_failed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        let info: Option<ErroredInfo<T>> =
+            RedisJobInternal::get_errored_info(&mut con, &failed(name), id).await?;
+        let Some(info) = info else {
+            return Ok(false);
+        };
+        // Whoever removes the entry retries it, so concurrent retries queue the job once
+        if !RedisJobInternal::remove_failed_info(&mut con, name, id).await? {
+            return Ok(false);
+        }
+        if let Err(report) = self.queue(&info.to_queue_info()).await {
+            RedisJobInternal::push_failed_info(&mut con, name, &info).await?;
+            return Err(report);
+        }
+        Ok(true)
+    }
 
-fn delayed(name: &str) -> String {
-    format!("delayed:{name}")
-}
+    async fn retry_delayed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError> {
+        let mut count = 0;
+        for info in self.filter_infos(&delayed(&self.name), filter).await? {
+            if self.retry_delayed(info.id()).await? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
 
-fn parse_error(value: impl Debug) -> Report<KernelError> {
-    Report::new(KernelError::Internal)
-        .attach_printable(format!("Failed to parse received data. {value:?}"))
+    async fn retry_failed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError> {
+        let mut count = 0;
+        for info in self.filter_infos(&failed(&self.name), filter).await? {
+            if self.retry_failed(info.id()).await? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn discard_delayed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        // Leaves the data alone, so jobs that no longer decode can be discarded too
+        let info: Option<ErroredInfo<IgnoredAny>> =
+            RedisJobInternal::get_info_from_hash(&mut con, &delayed(name), id).await?;
+        if info.is_none() {
+            return Ok(false);
+        }
+        if let Some(member) = RedisJobInternal::get_delayed_entry(&mut con, name, id).await? {
+            let info = member_info(&member)?;
+            let _: () = con
+                .zrem(scheduled(name, info.priority()), &member)
+                .await
+                .convert_error()?;
+            if let Some(key) = info.unique_key() {
+                RedisJobInternal::release_unique(&mut con, name, key, id).await?;
+            }
+            if let Some(key) = info.partition_key() {
+                RedisJobInternal::leave_partition(&mut con, name, key, id).await?;
+            }
+        }
+        RedisJobInternal::remove_delayed_info(&mut con, name, id).await?;
+        RedisJobInternal::remove_status(&mut con, name, id).await?;
+        Ok(true)
+    }
+
+    async fn discard_failed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        if !RedisJobInternal::remove_failed_info(&mut con, name, id).await? {
+            return Ok(false);
+        }
+        RedisJobInternal::remove_status(&mut con, name, id).await?;
+        Ok(true)
+    }
+
+    async fn purge_delayed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError> {
+        let mut count = 0;
+        for info in self.filter_infos(&delayed(&self.name), filter).await? {
+            if self.discard_delayed(info.id()).await? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn purge_failed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError> {
+        let mut count = 0;
+        for info in self.filter_infos(&failed(&self.name), filter).await? {
+            if self.discard_failed(info.id()).await? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    async fn get_quarantined_infos(
+        &self,
+        size: &i64,
+        offset: &i64,
+    ) -> error_stack::Result<Vec<QuarantinedInfo>, KernelError> {
+        let size = usize::try_from(*size).unwrap_or(0);
+        let offset = usize::try_from(*offset).unwrap_or(0);
+        let mut con = self.db.transact().await?;
+        let mut infos: Vec<QuarantinedInfo> =
+            RedisJobInternal::scan_infos_from_hash(&mut con, &quarantine(&self.name)).await?;
+        // Hash order is arbitrary, pages need a stable one
+        infos.sort_by(|a, b| {
+            b.quarantined_at()
+                .cmp(a.quarantined_at())
+                .then_with(|| a.id().cmp(b.id()))
+        });
+        Ok(infos.into_iter().skip(offset).take(size).collect())
+    }
+
+    async fn get_quarantined_info(
+        &self,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<QuarantinedInfo>, KernelError> {
+        let mut con = self.db.transact().await?;
+        let name = quarantine(&self.name);
+        RedisJobInternal::get_info_from_hash(&mut con, &name, id).await
+    }
+
+    async fn get_quarantined_len(&self) -> error_stack::Result<usize, KernelError> {
+        let name = quarantine(&self.name);
+        let mut con = self.db.transact().await?;
+        RedisJobInternal::get_hash_len(&mut con, &name).await
+    }
+
+    async fn retry_quarantined(&self, id: &Uuid) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        let info: Option<QuarantinedInfo> =
+            RedisJobInternal::get_info_from_hash(&mut con, &quarantine(name), id).await?;
+        let Some(info) = info else {
+            return Ok(None);
+        };
+        let job: QueueInfo<T> = QueueInfo::decode(info.raw().as_bytes())
+            .map_err(|error| Report::new(error).change_context(KernelError::Unprocessable))
+            .attach_printable_lazy(|| format!("Quarantined job {id} still does not decode"))?;
+        if !RedisJobInternal::remove_quarantined_info(&mut con, name, id).await? {
+            return Ok(None);
+        }
+        match self.queue(&job).await {
+            Ok(queued) => Ok(Some(queued)),
+            Err(report) => {
+                RedisJobInternal::push_quarantined_info(&mut con, name, &info).await?;
+                Err(report)
+            }
+        }
+    }
+
+    async fn discard_quarantined(&self, id: &Uuid) -> error_stack::Result<bool, KernelError> {
+        let name = &self.name;
+        let mut con = self.db.transact().await?;
+        if !RedisJobInternal::remove_quarantined_info(&mut con, name, id).await? {
+            return Ok(false);
+        }
+        RedisJobInternal::remove_status(&mut con, name, id).await?;
+        Ok(true)
+    }
+}
+
+const QUEUE_FIELD: &str = "info";
+const PROMOTE_BATCH: usize = 100;
+
+/// Moves up to `ARGV[1]` jobs whose score is due by the server clock from the sorted set into the stream.
+/// Returns how many were moved
+static PROMOTE_DUE: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local due = redis.call('ZRANGEBYSCORE', KEYS[1], '-inf', now, 'LIMIT', 0, tonumber(ARGV[1]))
+for _, info in ipairs(due) do
+    redis.call('ZREM', KEYS[1], info)
+    redis.call('XADD', KEYS[2], '*', ARGV[2], info)
+end
+return #due
+"#,
+    )
+});
+
+/// Deletes `KEYS[1]` only if it still holds `ARGV[1]`, it may have expired and been taken by someone else
+static DELETE_IF_HELD: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#,
+    )
+});
+
+/// Holds `KEYS[1]` for `ARGV[1]` unless it is held already, then returns the holder
+static CLAIM: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    return false
+end
+return redis.call('GET', KEYS[1])
+"#,
+    )
+});
+
+/// Holds `KEYS[1]` for `ARGV[1]` unless the job holding it is the last one in the partition `KEYS[2]`,
+/// or has yet to join it, then returns the holder.
+/// With `ARGV[4]` set `ARGV[1]` also joins the partition, see [`JOIN_PARTITION`]
+static CLAIM_IN_PARTITION: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local holder = redis.call('GET', KEYS[1])
+if holder then
+    local behind = redis.call('ZREVRANK', KEYS[2], holder)
+    if not behind or behind == 0 then
+        return holder
+    end
+end
+redis.call('SET', KEYS[1], ARGV[1], 'PX', ARGV[2])
+if ARGV[4] == '1' then
+    local order = redis.call('INCR', KEYS[3])
+    redis.call('ZADD', KEYS[2], 'NX', order, ARGV[1])
+    redis.call('PEXPIRE', KEYS[2], ARGV[3])
+end
+return false
+"#,
+    )
+});
+
+/// Adds `ARGV[1]` to the partition `KEYS[1]` behind everything there unless it is in already,
+/// returns how many jobs are ahead of it
+static JOIN_PARTITION: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local order = redis.call('INCR', KEYS[2])
+redis.call('ZADD', KEYS[1], 'NX', order, ARGV[1])
+redis.call('PEXPIRE', KEYS[1], ARGV[2])
+return redis.call('ZRANK', KEYS[1], ARGV[1])
+"#,
+    )
+});
+
+/// How long a job waits per job ahead of it in its partition before it is tried again
+const PARTITION_WAIT: Duration = Duration::from_millis(100);
+const PARTITION_MAX_WAIT: Duration = Duration::from_secs(5);
+
+/// Outlives any run of a schedule, so a crashed instance cannot hold it forever
+const SCHEDULE_LOCK_TTL: Duration = Duration::from_secs(30);
+
+fn now_millis() -> i64 {
+    i64::try_from(OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000).unwrap_or(i64::MAX)
+}
+
+/// Shared by every lane of the queue, so a worker can read all of them at once
+fn group(name: &str) -> String {
+    format!("g:{name}")
+}
+
+fn lane(name: &str, priority: &Priority) -> String {
+    match priority {
+        Priority::High => format!("{name}:high"),
+        // The stream from before priorities existed
+        Priority::Normal => name.to_string(),
+        Priority::Low => format!("{name}:low"),
+    }
+}
+
+/// Lanes in the order a worker tries them, the first is drawn by weight using `random` in `[0, 1)`
+/// and the rest follow by priority
+fn lane_order(random: f64) -> Vec<Priority> {
+    let total: u32 = Priority::ALL.iter().map(Priority::weight).sum();
+    let mut pick = random.clamp(0.0, 1.0) * f64::from(total);
+    let first = Priority::ALL
+        .into_iter()
+        .find(|priority| {
+            pick -= f64::from(priority.weight());
+            pick < 0.0
+        })
+        .unwrap_or(Priority::Low);
+    let mut order = vec![first];
+    order.extend(
+        Priority::ALL
+            .into_iter()
+            .filter(|priority| *priority != first),
+    );
+    order
+}
+
+/// Reads a serialized job without knowing its data type
+fn member_info(member: &str) -> error_stack::Result<QueueInfo<IgnoredAny>, KernelError> {
+    serde_json::from_str(member).change_context_lazy(|| KernelError::Internal)
+}
+
+fn failed(name: &str) -> String {
+    format!("failed:{name}")
+}
+
+fn delayed(name: &str) -> String {
+    format!("delayed:{name}")
+}
+
+fn quarantine(name: &str) -> String {
+    format!("quarantine:{name}")
+}
+
+fn score(when: OffsetDateTime) -> error_stack::Result<i64, KernelError> {
+    i64::try_from(when.unix_timestamp_nanos() / 1_000_000)
+        .change_context_lazy(|| KernelError::Internal)
+}
+
+fn scheduled(name: &str, priority: &Priority) -> String {
+    format!("scheduled:{}", lane(name, priority))
+}
+
+fn delayed_entry(name: &str) -> String {
+    format!("delayed_entry:{name}")
+}
+
+fn schedules(name: &str) -> String {
+    format!("schedules:{name}")
+}
+
+fn schedule_lock(name: &str, schedule: &str) -> String {
+    format!("schedule_lock:{name}:{schedule}")
+}
+
+fn partition(name: &str, key: &str) -> String {
+    format!("partition:{name}:{key}")
+}
+
+fn partition_order(name: &str) -> String {
+    format!("partition_order:{name}")
+}
+
+fn unique(name: &str, key: &str) -> String {
+    format!("unique:{name}:{key}")
+}
+
+fn status(name: &str, id: &Uuid) -> String {
+    format!("status:{name}:{id}")
+}
+
+fn matches<T: Serialize>(
+    filter: &ErroredInfoFilter,
+    info: &ErroredInfo<T>,
+) -> error_stack::Result<bool, KernelError> {
+    if !filter.matches_info(info) {
+        return Ok(false);
+    }
+    if let Some(data) = filter.data() {
+        let raw =
+            serde_json::to_string(info.data()).change_context_lazy(|| KernelError::Internal)?;
+        if !raw.contains(data.as_str()) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn parse_error(value: impl Debug) -> Report<KernelError> {
+    Report::new(KernelError::Internal)
+        .attach_printable(format!("Failed to parse received data. {value:?}"))
 }
 
 pub(in crate::database) struct RedisJobInternal;
 
 impl RedisJobInternal {
-    async fn create_group(con: &mut Connection, name: &str) -> RedisResult<Value> {
-        con.xgroup_create_mkstream(name, &group(name), 0).await
+    async fn create_group(
+        con: &mut Connection,
+        name: &str,
+        priority: &Priority,
+    ) -> RedisResult<Value> {
+        con.xgroup_create_mkstream(lane(name, priority), group(name), 0)
+            .await
     }
 
     async fn insert_waiting<T: Serialize>(
@@ -303,89 +1337,239 @@ impl RedisJobInternal {
         info: &QueueInfo<T>,
     ) -> error_stack::Result<(), KernelError> {
         // Ignore error
-        let _ = Self::create_group(con, name).await;
+        let _ = Self::create_group(con, name, info.priority()).await;
+        let serialize = serde_json::to_string(info)
+            .map_err(|e| Report::new(e).change_context(KernelError::Internal))?;
+        con.xadd(
+            lane(name, info.priority()),
+            "*",
+            &[(QUEUE_FIELD, &serialize)],
+        )
+        .await
+        .convert_error()
+    }
+
+    async fn insert_scheduled<T: Serialize>(
+        con: &mut Connection,
+        name: &str,
+        info: &QueueInfo<T>,
+        when: OffsetDateTime,
+    ) -> error_stack::Result<(), KernelError> {
         let serialize = serde_json::to_string(info)
             .map_err(|e| Report::new(e).change_context(KernelError::Internal))?;
-        con.xadd(name, "*", &[(QUEUE_FIELD, &serialize)])
+        con.zadd(scheduled(name, info.priority()), serialize, score(when)?)
+            .await
+            .convert_error()
+    }
+
+    /// Swaps the stream entry for a scheduled one in a single transaction, returns the scheduled member
+    async fn reschedule<T: Serialize>(
+        con: &mut Connection,
+        name: &str,
+        priority: &Priority,
+        entry: &str,
+        info: &QueueInfo<T>,
+        when: OffsetDateTime,
+    ) -> error_stack::Result<String, KernelError> {
+        let member = serde_json::to_string(info)
+            .map_err(|e| Report::new(e).change_context(KernelError::Internal))?;
+        let _: () = redis::pipe()
+            .atomic()
+            .zadd(scheduled(name, info.priority()), &member, score(when)?)
+            .ignore()
+            .xack(lane(name, priority), group(name), &[entry])
+            .ignore()
+            .xdel(lane(name, priority), &[entry])
+            .ignore()
+            .query_async(con)
+            .await
+            .convert_error()?;
+        Ok(member)
+    }
+
+    async fn promote_due(
+        con: &mut Connection,
+        name: &str,
+        priority: &Priority,
+    ) -> error_stack::Result<usize, KernelError> {
+        // Ignore error
+        let _ = Self::create_group(con, name, priority).await;
+        PROMOTE_DUE
+            .key(scheduled(name, priority))
+            .key(lane(name, priority))
+            .arg(PROMOTE_BATCH)
+            .arg(QUEUE_FIELD)
+            .invoke_async(con)
             .await
             .convert_error()
     }
 
+    /// Promotes every lane once, returns how many jobs moved in each
+    async fn promote_lanes(
+        con: &mut Connection,
+        name: &str,
+    ) -> error_stack::Result<Vec<usize>, KernelError> {
+        let mut counts = Vec::new();
+        for priority in Priority::ALL {
+            counts.push(Self::promote_due(con, name, &priority).await?);
+        }
+        Ok(counts)
+    }
+
+    /// Reads at most one new entry from each of `priorities`, waiting up to `block` milliseconds if all are empty
     async fn pop_to_process<T>(
         con: &mut Connection,
         name: &str,
+        priorities: &[Priority],
         member: &str,
-    ) -> error_stack::Result<Option<QueueData<T>>, KernelError>
+        config: &MQConfig,
+        block: Option<usize>,
+    ) -> error_stack::Result<Vec<QueueData<T>>, KernelError>
     where
-        T: for<'de> Deserialize<'de>,
+        T: for<'de> Deserialize<'de> + Payload,
     {
         let options = StreamReadOptions::default()
-            .block(1000)
             .count(1)
             .group(group(name), member);
+        let options = match block {
+            Some(block) => options.block(block),
+            None => options,
+        };
+        let lanes = priorities
+            .iter()
+            .map(|priority| lane(name, priority))
+            .collect::<Vec<_>>();
+        let ids = vec![">"; lanes.len()];
         let result: Value = con
-            .xread_options(&[name], &[">"], &options)
+            .xread_options(&lanes, &ids, &options)
             .await
             .convert_error()?;
-        let bulk = match result {
+        let streams = match result {
             Value::Bulk(bulk) => bulk,
-            Value::Nil => return Ok(None),
+            Value::Nil => return Ok(Vec::new()),
             _ => return Err(parse_error(result)),
         };
-        let bulk = match bulk.as_slice() {
-            [Value::Bulk(bulk)] => bulk,
-            _ => return Err(parse_error(bulk)),
-        };
-        let bulk = match bulk.as_slice() {
-            [Value::Data(_name), Value::Bulk(bulk)] => bulk,
-            _ => return Err(parse_error(bulk)),
-        };
-        let bulk = match bulk.as_slice() {
-            [Value::Bulk(bulk)] => bulk,
-            _ => return Err(parse_error(bulk)),
-        };
-        let (id, bulk) = match bulk.as_slice() {
-            [Value::Data(id), Value::Bulk(bulk)] => (id, bulk),
-            _ => return Err(parse_error(bulk)),
-        };
-        let data = match bulk.as_slice() {
-            [Value::Data(_field), Value::Data(data)] => data,
-            _ => return Err(parse_error(bulk)),
-        };
-        Ok(Some(QueueData {
-            id: from_utf8(id)
+        let mut popped = Vec::new();
+        for stream in streams {
+            let (stream, bulk) = match stream {
+                Value::Bulk(bulk) => match bulk.as_slice() {
+                    [Value::Data(stream), Value::Bulk(bulk)] => (stream.clone(), bulk.clone()),
+                    _ => return Err(parse_error(bulk)),
+                },
+                _ => return Err(parse_error(stream)),
+            };
+            let priority = priorities
+                .iter()
+                .find(|priority| lane(name, priority).as_bytes() == stream.as_slice())
+                .ok_or_else(|| parse_error(&stream))?;
+            let bulk = match bulk.as_slice() {
+                [Value::Bulk(bulk)] => bulk,
+                _ => return Err(parse_error(bulk)),
+            };
+            let (id, bulk) = match bulk.as_slice() {
+                [Value::Data(id), Value::Bulk(bulk)] => (id, bulk),
+                _ => return Err(parse_error(bulk)),
+            };
+            let data = match bulk.as_slice() {
+                [Value::Data(_field), Value::Data(data)] => data,
+                _ => return Err(parse_error(bulk)),
+            };
+            let id = from_utf8(id)
                 .change_context_lazy(|| KernelError::Internal)?
-                .to_string(),
-            delivered_count: 0,
-            info: serde_json::from_slice(data).change_context_lazy(|| KernelError::Internal)?,
-        }))
+                .to_string();
+            let info = match QueueInfo::decode(data) {
+                Ok(info) => info,
+                Err(error @ DecodeError::Newer { .. }) => {
+                    // Stays pending, so a worker of the newer build claims it after `reclaim_idle`
+                    warn!("Left Entry: {id}, Error: {error}");
+                    continue;
+                }
+                Err(error) => {
+                    Self::quarantine(con, name, priority, &id, data, &error, config).await?;
+                    continue;
+                }
+            };
+            popped.push(QueueData {
+                id,
+                priority: *priority,
+                delivered_count: 0,
+                info,
+            });
+        }
+        Ok(popped)
+    }
+
+    /// Takes an entry that does not decode off the stream, so it is not redelivered forever.
+    /// The holds of the job are released and its status failed, as far as the envelope still reads
+    async fn quarantine(
+        con: &mut Connection,
+        name: &str,
+        priority: &Priority,
+        id: &str,
+        data: &[u8],
+        error: &DecodeError,
+        config: &MQConfig,
+    ) -> error_stack::Result<(), KernelError> {
+        let raw = String::from_utf8_lossy(data).into_owned();
+        // Usually only the job data changed shape
+        let envelope = member_info(&raw).ok();
+        let uuid = envelope
+            .as_ref()
+            .map(|info| *info.id())
+            .unwrap_or_else(Uuid::new_v4);
+        let info = QuarantinedInfo::new(uuid, *priority, raw, error.to_string());
+        Self::push_quarantined_info(con, name, &info).await?;
+        Self::mark_done(con, name, priority, id).await?;
+        if let Some(envelope) = envelope {
+            if let Some(key) = envelope.unique_key() {
+                Self::release_unique(con, name, key, &uuid).await?;
+            }
+            if let Some(key) = envelope.partition_key() {
+                Self::leave_partition(con, name, key, &uuid).await?;
+            }
+            Self::update_status(con, name, &uuid, config, |status| {
+                status.failed(format!("Quarantined, the job does not decode: {error}"))
+            })
+            .await?;
+        }
+        counter!(MQ_JOBS_TOTAL, "queue" => name.to_string(), "outcome" => "quarantined")
+            .increment(1);
+        warn!("Quarantined Id: {uuid}, Entry: {id}, Error: {error}");
+        Ok(())
     }
 
     async fn mark_done(
         con: &mut Connection,
         name: &str,
+        priority: &Priority,
         id: &str,
     ) -> error_stack::Result<(), KernelError> {
-        con.xack(name, &group(name), &[id]).await.convert_error()?;
-        con.xdel(name, &[id]).await.convert_error()
+        let stream = lane(name, priority);
+        let _: () = con
+            .xack(&stream, group(name), &[id])
+            .await
+            .convert_error()?;
+        con.xdel(&stream, &[id]).await.convert_error()
     }
 
     async fn pop_pending<T>(
         con: &mut Connection,
         name: &str,
+        priority: &Priority,
         own_member: &str,
-        idle_time: &Duration,
+        config: &MQConfig,
     ) -> error_stack::Result<Option<QueueData<T>>, KernelError>
     where
-        T: for<'de> Deserialize<'de>,
+        T: for<'de> Deserialize<'de> + Payload,
     {
         // Ignore error
-        let _ = Self::create_group(con, name).await;
-        let time_millis =
-            u64::try_from(idle_time.as_millis()).change_context_lazy(|| KernelError::Internal)?;
+        let _ = Self::create_group(con, name, priority).await;
+        let time_millis = u64::try_from(config.reclaim_idle().as_millis())
+            .change_context_lazy(|| KernelError::Internal)?;
         let group = group(name);
+        let stream = lane(name, priority);
         let value: Value = redis::cmd("XPENDING")
-            .arg(name)
+            .arg(&stream)
             .arg(&group)
             .arg("IDLE")
             .arg(time_millis)
@@ -418,7 +1602,7 @@ impl RedisJobInternal {
         };
 
         let result: Value = con
-            .xclaim(name, &group, own_member, time_millis, &[&id])
+            .xclaim(&stream, &group, own_member, time_millis, &[&id])
             .await
             .convert_error()?;
 
@@ -436,11 +1620,21 @@ impl RedisJobInternal {
         };
         match bulk.as_slice() {
             [Value::Data(_field), Value::Data(data)] => {
-                let info: QueueInfo<T> =
-                    serde_json::from_slice(data).change_context_lazy(|| KernelError::Internal)?;
+                let info = match QueueInfo::decode(data) {
+                    Ok(info) => info,
+                    Err(error @ DecodeError::Newer { .. }) => {
+                        warn!("Left Entry: {id}, Error: {error}");
+                        return Ok(None);
+                    }
+                    Err(error) => {
+                        Self::quarantine(con, name, priority, &id, data, &error, config).await?;
+                        return Ok(None);
+                    }
+                };
 
                 Ok(Some(QueueData {
                     id,
+                    priority: *priority,
                     delivered_count: count,
                     info,
                 }))
@@ -449,17 +1643,20 @@ impl RedisJobInternal {
         }
     }
 
+    /// `member` is what the job waits as in the scheduled set until its next attempt
     async fn push_delayed_info<T: Serialize>(
         con: &mut Connection,
         name: &str,
-        id: Uuid,
-        data: T,
-        stack_trace: String,
+        member: &str,
+        info: &ErroredInfo<T>,
     ) -> error_stack::Result<(), KernelError> {
-        let string_id = id.to_string();
-        let info = ErroredInfo::new(id, data, stack_trace);
+        let string_id = info.id().to_string();
         let raw = serde_json::to_string(&info).change_context_lazy(|| KernelError::Internal)?;
-        con.hset(&delayed(name), &string_id, &raw)
+        let _: () = con
+            .hset(delayed_entry(name), &string_id, member)
+            .await
+            .convert_error()?;
+        con.hset(delayed(name), &string_id, &raw)
             .await
             .convert_error()
     }
@@ -469,11 +1666,75 @@ impl RedisJobInternal {
         name: &str,
         id: &Uuid,
     ) -> error_stack::Result<(), KernelError> {
-        con.hdel(&delayed(name), &id.to_string())
+        let _: () = con
+            .hdel(delayed_entry(name), id.to_string())
+            .await
+            .convert_error()?;
+        con.hdel(delayed(name), id.to_string())
+            .await
+            .convert_error()
+    }
+
+    async fn get_delayed_entry(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<String>, KernelError> {
+        con.hget(delayed_entry(name), id.to_string())
             .await
             .convert_error()
     }
 
+    /// Moves a delayed job to now, so the next promotion picks it up
+    async fn make_due(
+        con: &mut Connection,
+        name: &str,
+        member: &str,
+    ) -> error_stack::Result<bool, KernelError> {
+        let changed: i64 = redis::cmd("ZADD")
+            .arg(scheduled(name, member_info(member)?.priority()))
+            .arg("XX") // only if it is still waiting
+            .arg("CH")
+            .arg(now_millis())
+            .arg(member)
+            .query_async(con)
+            .await
+            .convert_error()?;
+        Ok(changed > 0)
+    }
+
+    async fn remove_failed_info(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<bool, KernelError> {
+        let removed: i64 = con
+            .hdel(failed(name), id.to_string())
+            .await
+            .convert_error()?;
+        Ok(removed > 0)
+    }
+
+    async fn remove_quarantined_info(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<bool, KernelError> {
+        let removed: i64 = con
+            .hdel(quarantine(name), id.to_string())
+            .await
+            .convert_error()?;
+        Ok(removed > 0)
+    }
+
+    async fn remove_status(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<(), KernelError> {
+        con.del(status(name, id)).await.convert_error()
+    }
+
     async fn get_hash_len(
         con: &mut Connection,
         name: &str,
@@ -492,77 +1753,330 @@ impl RedisJobInternal {
     async fn push_failed_info<T: Serialize>(
         con: &mut Connection,
         name: &str,
-        info: String,
-        uuid: Uuid,
-        data: T,
+        info: &ErroredInfo<T>,
     ) -> error_stack::Result<(), KernelError> {
-        let raw_uuid = uuid.to_string();
-        let data = ErroredInfo::new(uuid, data, info);
-        let raw = serde_json::to_string(&data).change_context_lazy(|| KernelError::Internal)?;
-        con.hset(&failed(name), &raw_uuid, &raw)
+        let raw = serde_json::to_string(info).change_context_lazy(|| KernelError::Internal)?;
+        con.hset(failed(name), info.id().to_string(), &raw)
             .await
             .convert_error()
     }
 
-    async fn get_wait_len(
+    async fn push_quarantined_info(
         con: &mut Connection,
         name: &str,
-    ) -> error_stack::Result<i64, KernelError> {
-        let result: Value = con.xlen(name).await.convert_error()?;
-        if let Value::Int(size) = result {
-            Ok(size)
-        } else {
-            Err(Report::new(KernelError::Internal)
-                .attach_printable(format!("Failed to get size. target: {name}")))
-        }
+        info: &QuarantinedInfo,
+    ) -> error_stack::Result<(), KernelError> {
+        let raw = serde_json::to_string(info).change_context_lazy(|| KernelError::Internal)?;
+        con.hset(quarantine(name), info.id().to_string(), &raw)
+            .await
+            .convert_error()
     }
 
-    async fn get_infos_from_hash<T: for<'de> Deserialize<'de>>(
+    /// Carries over the history of the delayed entry the job had, if any, and appends `record`.
+    /// History that cannot be read is logged and started over
+    async fn record_attempt<T>(
         con: &mut Connection,
         name: &str,
-        size: &i64,
-        offset: &i64,
-    ) -> error_stack::Result<Vec<T>, KernelError> {
-        if *size <= 0 {
-            return Ok(vec![]);
-        }
-        let result: Value = redis::cmd("HSCAN")
-            .arg(name)
-            .arg(offset)
-            .arg("COUNT")
-            .arg(size)
-            .query_async(con)
-            .await
-            .convert_error()?;
-        let bulk = match result {
-            Value::Bulk(bulk) => bulk,
-            _ => return Err(parse_error(result)),
+        info: ErroredInfo<T>,
+        record: AttemptRecord,
+    ) -> ErroredInfo<T> {
+        let (queued_at, mut attempts) = match Self::get_history(con, name, info.id()).await {
+            Ok(history) => history,
+            Err(report) => {
+                error!("{report:?}");
+                (None, Vec::new())
+            }
         };
-        let bulk = match bulk.as_slice() {
-            [Value::Data(_offset), Value::Bulk(bulk)] => bulk,
-            _ => return Err(parse_error(bulk)),
+        attempts.push(record);
+        info.with_history(queued_at, attempts)
+    }
+
+    async fn get_history(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<(Option<OffsetDateTime>, Vec<AttemptRecord>), KernelError> {
+        let previous: Option<ErroredInfo<IgnoredAny>> =
+            Self::get_info_from_hash(con, &delayed(name), id).await?;
+        let (queued_at, attempts) = match previous {
+            Some(previous) => {
+                let previous = previous.into_destruct();
+                (previous.queued_at, previous.attempts)
+            }
+            None => (None, Vec::new()),
         };
-        let usize = usize::try_from(*size).change_context_lazy(|| KernelError::Internal)?;
-        // HSCAN may return more than size
-        bulk.chunks(2)
-            .take(usize)
-            .map(|pair| match pair {
-                [Value::Data(_id), Value::Data(data)] => {
-                    let info = serde_json::from_slice(data)
-                        .change_context_lazy(|| KernelError::Internal)?;
-                    Ok(info)
-                }
-                _ => Err(parse_error(pair)),
-            })
+        let queued_at = match queued_at {
+            Some(queued_at) => Some(queued_at),
+            None => Self::get_status(con, name, id)
+                .await?
+                .map(|status| *status.queued_at()),
+        };
+        Ok((queued_at, attempts))
+    }
+
+    async fn get_wait_len(
+        con: &mut Connection,
+        name: &str,
+    ) -> error_stack::Result<i64, KernelError> {
+        let result: Value = con.xlen(name).await.convert_error()?;
+        if let Value::Int(size) = result {
+            Ok(size)
+        } else {
+            Err(Report::new(KernelError::Internal)
+                .attach_printable(format!("Failed to get size. target: {name}")))
+        }
+    }
+
+    async fn set_status(
+        con: &mut Connection,
+        name: &str,
+        status: &JobStatus,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError> {
+        let raw = serde_json::to_string(status).change_context_lazy(|| KernelError::Internal)?;
+        con.set_ex(self::status(name, status.id()), raw, ttl.as_secs())
+            .await
+            .convert_error()
+    }
+
+    async fn get_status(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<JobStatus>, KernelError> {
+        let raw: Option<String> = con.get(status(name, id)).await.convert_error()?;
+        raw.map(|raw| serde_json::from_str(&raw).change_context_lazy(|| KernelError::Internal))
+            .transpose()
+    }
+
+    /// Jobs queued before status tracking existed start from a fresh `queued` status
+    async fn update_status(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+        config: &MQConfig,
+        transition: impl FnOnce(JobStatus) -> JobStatus,
+    ) -> error_stack::Result<(), KernelError> {
+        let current = Self::get_status(con, name, id)
+            .await?
+            .unwrap_or_else(|| JobStatus::queued(*id));
+        Self::set_status(con, name, &transition(current), config.status_ttl()).await
+    }
+
+    /// Returns the token to unlock with, or `None` if the lock is held already
+    async fn lock_schedule(
+        con: &mut Connection,
+        name: &str,
+        schedule: &str,
+    ) -> error_stack::Result<Option<String>, KernelError> {
+        let token = Uuid::new_v4().to_string();
+        let locked: Option<String> = redis::cmd("SET")
+            .arg(schedule_lock(name, schedule))
+            .arg(&token)
+            .arg("NX")
+            .arg("PX")
+            .arg(u64::try_from(SCHEDULE_LOCK_TTL.as_millis()).unwrap_or(u64::MAX))
+            .query_async(con)
+            .await
+            .convert_error()?;
+        Ok(locked.map(|_| token))
+    }
+
+    async fn unlock_schedule(
+        con: &mut Connection,
+        name: &str,
+        schedule: &str,
+        token: &str,
+    ) -> error_stack::Result<(), KernelError> {
+        let _: i64 = DELETE_IF_HELD
+            .key(schedule_lock(name, schedule))
+            .arg(token)
+            .invoke_async(con)
+            .await
+            .convert_error()?;
+        Ok(())
+    }
+
+    /// Returns the id of the pending job holding `key`, or `None` once `id` holds it
+    async fn claim_unique(
+        con: &mut Connection,
+        name: &str,
+        key: &str,
+        id: &Uuid,
+        window: &Duration,
+    ) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let holder: Option<String> = CLAIM
+            .key(unique(name, key))
+            .arg(id.to_string())
+            .arg(u64::try_from(window.as_millis()).unwrap_or(u64::MAX))
+            .invoke_async(con)
+            .await
+            .convert_error()?;
+        holder
+            .map(|holder| Uuid::parse_str(&holder).change_context_lazy(|| KernelError::Internal))
+            .transpose()
+    }
+
+    /// Like [`Self::claim_unique`], but a holder that is no longer the last job of `partition_key`
+    /// loses `key` to `id`
+    async fn claim_unique_in_partition(
+        con: &mut Connection,
+        name: &str,
+        key: &str,
+        partition_key: &str,
+        id: &Uuid,
+        config: &MQConfig,
+        join: bool,
+    ) -> error_stack::Result<Option<Uuid>, KernelError> {
+        let holder: Option<String> = CLAIM_IN_PARTITION
+            .key(unique(name, key))
+            .key(partition(name, partition_key))
+            .key(partition_order(name))
+            .arg(id.to_string())
+            .arg(u64::try_from(config.unique_window().as_millis()).unwrap_or(u64::MAX))
+            .arg(u64::try_from(config.status_ttl().as_millis()).unwrap_or(u64::MAX))
+            .arg(if join { "1" } else { "0" })
+            .invoke_async(con)
+            .await
+            .convert_error()?;
+        holder
+            .map(|holder| Uuid::parse_str(&holder).change_context_lazy(|| KernelError::Internal))
+            .transpose()
+    }
+
+    async fn join_partition(
+        con: &mut Connection,
+        name: &str,
+        key: &str,
+        id: &Uuid,
+        config: &MQConfig,
+    ) -> error_stack::Result<i64, KernelError> {
+        JOIN_PARTITION
+            .key(partition(name, key))
+            .key(partition_order(name))
+            .arg(id.to_string())
+            .arg(u64::try_from(config.status_ttl().as_millis()).unwrap_or(u64::MAX))
+            .invoke_async(con)
+            .await
+            .convert_error()
+    }
+
+    async fn leave_partition(
+        con: &mut Connection,
+        name: &str,
+        key: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<(), KernelError> {
+        con.zrem(partition(name, key), id.to_string())
+            .await
+            .convert_error()
+    }
+
+    async fn release_unique(
+        con: &mut Connection,
+        name: &str,
+        key: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<(), KernelError> {
+        let _: i64 = DELETE_IF_HELD
+            .key(unique(name, key))
+            .arg(id.to_string())
+            .invoke_async(con)
+            .await
+            .convert_error()?;
+        Ok(())
+    }
+
+    async fn get_schedule_state(
+        con: &mut Connection,
+        name: &str,
+        schedule: &str,
+    ) -> error_stack::Result<Option<ScheduleState>, KernelError> {
+        let raw: Option<String> = con.hget(schedules(name), schedule).await.convert_error()?;
+        raw.map(|raw| serde_json::from_str(&raw).change_context_lazy(|| KernelError::Internal))
+            .transpose()
+    }
+
+    async fn set_schedule_state(
+        con: &mut Connection,
+        name: &str,
+        state: &ScheduleState,
+    ) -> error_stack::Result<(), KernelError> {
+        let raw = serde_json::to_string(state).change_context_lazy(|| KernelError::Internal)?;
+        con.hset(schedules(name), state.name(), raw)
+            .await
+            .convert_error()
+    }
+
+    async fn scan_hash(
+        con: &mut Connection,
+        name: &str,
+    ) -> error_stack::Result<Vec<String>, KernelError> {
+        let mut values = Vec::new();
+        let mut cursor = 0_u64;
+        loop {
+            let (next, pairs): (u64, Vec<(String, String)>) = redis::cmd("HSCAN")
+                .arg(name)
+                .arg(cursor)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(con)
+                .await
+                .convert_error()?;
+            values.extend(pairs.into_iter().map(|(_id, data)| data));
+            if next == 0 {
+                return Ok(values);
+            }
+            cursor = next;
+        }
+    }
+
+    async fn scan_infos_from_hash<T: for<'de> Deserialize<'de>>(
+        con: &mut Connection,
+        name: &str,
+    ) -> error_stack::Result<Vec<T>, KernelError> {
+        Self::scan_hash(con, name)
+            .await?
+            .iter()
+            .map(|data| serde_json::from_str(data).change_context_lazy(|| KernelError::Internal))
             .collect()
     }
 
+    /// Upgrades jobs written by an older version, those of a newer build are left out
+    async fn scan_errored_infos<T: Payload + DeserializeOwned>(
+        con: &mut Connection,
+        name: &str,
+    ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError> {
+        let mut infos = Vec::new();
+        for data in Self::scan_hash(con, name).await? {
+            match ErroredInfo::decode(data.as_bytes()) {
+                Ok(info) => infos.push(info),
+                Err(error) => warn!("Skipped an entry of {name}: {error}"),
+            }
+        }
+        Ok(infos)
+    }
+
+    async fn get_errored_info<T: Payload + DeserializeOwned>(
+        con: &mut Connection,
+        name: &str,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<ErroredInfo<T>>, KernelError> {
+        let data: Option<String> = con.hget(name, id.to_string()).await.convert_error()?;
+        data.map(|data| {
+            ErroredInfo::decode(data.as_bytes())
+                .map_err(|error| Report::new(error).change_context(KernelError::Internal))
+                .attach_printable_lazy(|| format!("Entry {id} of {name} does not decode"))
+        })
+        .transpose()
+    }
+
     async fn get_info_from_hash<T: for<'de> Deserialize<'de>>(
         con: &mut Connection,
         name: &str,
         id: &Uuid,
     ) -> error_stack::Result<Option<T>, KernelError> {
-        let result: Value = con.hget(name, &id.to_string()).await.convert_error()?;
+        let result: Value = con.hget(name, id.to_string()).await.convert_error()?;
         match result {
             Value::Data(data) => {
                 let info =
@@ -577,18 +2091,31 @@ impl RedisJobInternal {
 
 #[cfg(test)]
 mod test {
-    use crate::database::redis::mq::{QueueData, RedisJobInternal, RedisMessageQueue};
+    use crate::database::redis::mq::{
+        lane_order, partition, quarantine, unique, QueueData, RedisJobInternal, RedisMessageQueue,
+    };
     use crate::database::RedisDatabase;
-    use error_stack::Report;
+    use crate::error::ConvertError;
+    use crate::layer::{CatchPanicLayer, ConcurrencyLimitLayer, TimeoutLayer, TraceLayer};
+    use deadpool_redis::redis::AsyncCommands;
+    use error_stack::{Report, ResultExt};
     use kernel::interface::database::DatabaseConnection;
     use kernel::interface::mq::ErrorOperation::Delay;
     use kernel::interface::mq::MQConfig;
     use kernel::interface::mq::MessageQueue;
     use kernel::interface::mq::QueueInfo;
+    use kernel::interface::mq::Schedule;
+    use kernel::interface::mq::{
+        ErrorKind, ErroredInfo, ErroredInfoFilter, InfoSort, InfoSortKey, JobContext, JobState,
+        JobStatus, Migrations, Next, Payload, Priority, QuarantinedInfo, RetryPolicy, SortOrder,
+    };
     use kernel::KernelError;
     use rand::random;
     use serde::{Deserialize, Serialize};
-    use std::time::Duration;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use time::OffsetDateTime;
     use tokio::time::sleep;
     use tracing::info;
     use tracing_subscriber::layer::SubscriberExt;
@@ -600,6 +2127,62 @@ mod test {
         a: String,
     }
 
+    impl Payload for TestData {
+        const TYPE_NAME: &'static str = "test_data";
+        const VERSION: u32 = 1;
+    }
+
+    /// `TestData` changed without a new version
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Reshaped {
+        a: i64,
+    }
+
+    impl Payload for Reshaped {
+        const TYPE_NAME: &'static str = "test_data";
+        const VERSION: u32 = 1;
+    }
+
+    /// The next version of `TestData`
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Renamed {
+        b: String,
+    }
+
+    impl Payload for Renamed {
+        const TYPE_NAME: &'static str = "test_data";
+        const VERSION: u32 = 2;
+
+        fn migrations() -> Migrations {
+            Migrations::default().register(1, |mut data| {
+                if let Some(data) = data.as_object_mut() {
+                    let a = data.remove("a").unwrap_or_default();
+                    data.insert("b".to_string(), a);
+                }
+                Ok(data)
+            })
+        }
+    }
+
+    /// Queue name no other test run shares
+    fn test_name(prefix: &str) -> String {
+        format!("{prefix}:{}", Uuid::new_v4())
+    }
+
+    /// Queue whose handler does nothing, for tests that drive it by hand
+    fn idle_queue<T>(db: &RedisDatabase, name: &str, config: &MQConfig) -> RedisMessageQueue<(), T>
+    where
+        T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Payload + Sync + Send,
+    {
+        RedisMessageQueue::new(
+            db.clone(),
+            (),
+            name,
+            config.clone(),
+            |_none, _data: T| async move { Ok(None) },
+        )
+    }
+
     #[test_with::env(REDIS_TEST)]
     #[tokio::test]
     async fn test_internal() -> error_stack::Result<(), KernelError> {
@@ -611,19 +2194,800 @@ mod test {
             a: "testtss".to_string(),
         };
         let info = QueueInfo::new(Uuid::new_v4(), data);
+        let mut config = MQConfig::default();
+        config.substitute(|config| *config.reclaim_idle = Duration::from_millis(500));
         RedisJobInternal::insert_waiting(&mut con, name, &info).await?;
-        let result: QueueData<TestData> = RedisJobInternal::pop_to_process(&mut con, name, member)
-            .await
-            .and_then(|option| option.ok_or_else(|| Report::new(KernelError::Internal)))?;
+        let result: QueueData<TestData> = RedisJobInternal::pop_to_process(
+            &mut con,
+            name,
+            &[Priority::Normal],
+            member,
+            &config,
+            Some(1000),
+        )
+        .await?
+        .pop()
+        .ok_or_else(|| Report::new(KernelError::Internal))?;
         println!("result: {result:?}");
 
         sleep(Duration::from_secs(1)).await;
         let pending: Option<QueueData<TestData>> =
-            RedisJobInternal::pop_pending(&mut con, name, member, &Duration::from_millis(500))
+            RedisJobInternal::pop_pending(&mut con, name, &Priority::Normal, member, &config)
                 .await?;
         println!("result: {pending:?}");
 
-        RedisJobInternal::mark_done(&mut con, name, &result.id).await?;
+        RedisJobInternal::mark_done(&mut con, name, &result.priority, &result.id).await?;
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_status() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = "test_status";
+        let config = MQConfig::default();
+        let id = Uuid::new_v4();
+        assert!(RedisJobInternal::get_status(&mut con, name, &id)
+            .await?
+            .is_none());
+
+        RedisJobInternal::set_status(&mut con, name, &JobStatus::queued(id), config.status_ttl())
+            .await?;
+        RedisJobInternal::update_status(&mut con, name, &id, &config, |status| {
+            status.processing(1)
+        })
+        .await?;
+        RedisJobInternal::update_status(&mut con, name, &id, &config, |status| {
+            status.done(Some("result".to_string()))
+        })
+        .await?;
+
+        let status = RedisJobInternal::get_status(&mut con, name, &id)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(status.state(), &JobState::Done);
+        assert_eq!(status.attempts(), &1);
+        assert_eq!(status.result().as_deref(), Some("result"));
+        assert!(status.error().is_none());
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_retry_and_discard() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_admin");
+        let mq = idle_queue::<TestData>(&db, &name, &MQConfig::default());
+        let failed = |a: &str| TestData { a: a.to_string() };
+
+        let retried = Uuid::new_v4();
+        let discarded = Uuid::new_v4();
+        let envelope = QueueInfo::new(retried, failed("x"))
+            .with_priority(Priority::High)
+            .with_partition_key("p");
+        let info =
+            ErroredInfo::new(retried, failed("x"), "boom".into(), None).with_envelope(&envelope);
+        RedisJobInternal::push_failed_info(&mut con, &name, &info).await?;
+        let info = ErroredInfo::new(
+            discarded,
+            failed("y"),
+            "boom".into(),
+            Some(ErrorKind::Conflict),
+        );
+        RedisJobInternal::push_failed_info(&mut con, &name, &info).await?;
+        assert_eq!(mq.get_failed_len().await?, 2);
+        let info = mq
+            .get_failed_info(&discarded)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(info.kind(), &Some(ErrorKind::Conflict));
+
+        // Concurrent retries queue the job once
+        let (first, second) = tokio::join!(mq.retry_failed(&retried), mq.retry_failed(&retried));
+        assert!(first? ^ second?);
+        assert!(!mq.retry_failed(&retried).await?);
+        assert_eq!(mq.get_queued_len_by_priority(&Priority::High).await?, 1);
+        let members: Vec<String> = con
+            .zrange(partition(&name, "p"), 0, -1)
+            .await
+            .convert_error()?;
+        assert_eq!(members, vec![retried.to_string()]);
+        let status = mq
+            .get_job_status(&retried)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(status.state(), &JobState::Queued);
+
+        let filter = ErroredInfoFilter::new(None, Some("\"z\"".into()));
+        assert_eq!(mq.purge_failed_infos(&filter).await?, 0);
+        let filter = ErroredInfoFilter::new(Some("boom".into()), Some("\"y\"".into()));
+        assert_eq!(mq.purge_failed_infos(&filter).await?, 1);
+        assert!(!mq.discard_failed(&discarded).await?);
+        assert_eq!(mq.get_failed_len().await?, 0);
+
+        let delayed = Uuid::new_v4();
+        let info = QueueInfo::new(delayed, failed("z"));
+        RedisJobInternal::insert_waiting(&mut con, &name, &info).await?;
+        let entry = loop {
+            let data: QueueData<TestData> = RedisJobInternal::pop_to_process(
+                &mut con,
+                &name,
+                &[Priority::Normal],
+                "member",
+                &MQConfig::default(),
+                Some(1000),
+            )
+            .await?
+            .pop()
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+            if data.info.id() == &delayed {
+                break data.id;
+            }
+        };
+        let later = OffsetDateTime::now_utc() + Duration::from_secs(60);
+        let member =
+            RedisJobInternal::reschedule(&mut con, &name, &Priority::Normal, &entry, &info, later)
+                .await?;
+        let errored = ErroredInfo::new(delayed, failed("z"), "".into(), None);
+        RedisJobInternal::push_delayed_info(&mut con, &name, &member, &errored).await?;
+        assert_eq!(mq.get_scheduled_len().await?, 1);
+        assert_eq!(
+            RedisJobInternal::promote_due(&mut con, &name, &Priority::Normal).await?,
+            0
+        );
+        assert!(mq.retry_delayed(&delayed).await?);
+        assert_eq!(
+            RedisJobInternal::promote_due(&mut con, &name, &Priority::Normal).await?,
+            1
+        );
+        assert!(mq.discard_delayed(&delayed).await?);
+        assert_eq!(mq.get_delayed_len().await?, 0);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_schedule() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_schedule");
+        let mq = idle_queue::<TestData>(&db, &name, &MQConfig::default());
+        let data = TestData {
+            a: "scheduled".to_string(),
+        };
+
+        let submitter = Uuid::new_v4();
+        let later = mq
+            .queue_after(
+                &QueueInfo::from(data.clone()).with_submitter(submitter),
+                Duration::from_millis(500),
+            )
+            .await?;
+        mq.queue_at(&QueueInfo::from(data), OffsetDateTime::UNIX_EPOCH)
+            .await?;
+        assert_eq!(mq.get_scheduled_len().await?, 1);
+        assert_eq!(mq.get_queued_len().await?, 1);
+        let status = mq
+            .get_job_status(&later)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(status.state(), &JobState::Scheduled);
+        assert_eq!(status.submitter(), &Some(submitter));
+
+        assert_eq!(
+            RedisJobInternal::promote_due(&mut con, &name, &Priority::Normal).await?,
+            0
+        );
+        sleep(Duration::from_millis(600)).await;
+        assert_eq!(
+            RedisJobInternal::promote_due(&mut con, &name, &Priority::Normal).await?,
+            1
+        );
+        assert_eq!(mq.get_scheduled_len().await?, 0);
+        assert_eq!(mq.get_queued_len().await?, 2);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_priority() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_priority");
+        let mq = idle_queue::<TestData>(&db, &name, &MQConfig::default());
+        let info = |priority| {
+            QueueInfo::from(TestData {
+                a: format!("{priority:?}"),
+            })
+            .with_priority(priority)
+        };
+
+        assert_eq!(lane_order(0.0), Priority::ALL);
+        assert_eq!(
+            lane_order(0.99),
+            [Priority::Low, Priority::High, Priority::Normal]
+        );
+
+        mq.queue(&info(Priority::Low)).await?;
+        mq.queue(&info(Priority::High)).await?;
+        mq.queue(&info(Priority::High)).await?;
+        mq.queue_at(&info(Priority::Low), OffsetDateTime::UNIX_EPOCH)
+            .await?;
+        mq.queue_after(&info(Priority::High), Duration::from_secs(60))
+            .await?;
+        assert_eq!(mq.get_queued_len_by_priority(&Priority::High).await?, 2);
+        assert_eq!(mq.get_queued_len_by_priority(&Priority::Normal).await?, 0);
+        assert_eq!(mq.get_queued_len_by_priority(&Priority::Low).await?, 2);
+        assert_eq!(mq.get_queued_len().await?, 4);
+        assert_eq!(mq.get_scheduled_len().await?, 1);
+
+        // Nothing is pending, so the first lane of the order is read
+        let popped: Vec<QueueData<TestData>> = RedisMessageQueue::<(), TestData>::pop_next(
+            &mut con,
+            &name,
+            "member",
+            &MQConfig::default(),
+        )
+        .await?;
+        assert_eq!(popped.len(), 1);
+        assert_ne!(popped[0].priority, Priority::Normal);
+        assert_eq!(popped[0].info.priority(), &popped[0].priority);
+
+        let popped: Vec<QueueData<TestData>> = RedisJobInternal::pop_to_process(
+            &mut con,
+            &name,
+            &Priority::ALL,
+            "member",
+            &MQConfig::default(),
+            Some(1000),
+        )
+        .await?;
+        assert!(popped
+            .iter()
+            .all(|data| data.info.priority() == &data.priority));
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_unique() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_unique");
+        let mut config = MQConfig::default();
+        config.substitute(|config| *config.unique_window = Duration::from_millis(500));
+        let mq = idle_queue::<TestData>(&db, &name, &config);
+        let info = |key: &str| {
+            QueueInfo::from(TestData {
+                a: "unique".to_string(),
+            })
+            .with_unique_key(key)
+        };
+
+        let first = mq.queue(&info("a")).await?;
+        assert_eq!(mq.queue(&info("a")).await?, first);
+        assert_ne!(mq.queue(&info("b")).await?, first);
+        assert_eq!(mq.get_queued_len().await?, 2);
+
+        // Released once the job finishes
+        RedisJobInternal::release_unique(&mut con, &name, "a", &Uuid::new_v4()).await?;
+        assert_eq!(mq.queue(&info("a")).await?, first);
+        RedisJobInternal::release_unique(&mut con, &name, "a", &first).await?;
+        let second = mq.queue(&info("a")).await?;
+        assert_ne!(second, first);
+
+        sleep(Duration::from_millis(600)).await;
+        assert_ne!(mq.queue(&info("a")).await?, second);
+        assert_eq!(mq.get_queued_len().await?, 4);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_unique_in_partition() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_unique_in_partition");
+        let mq = idle_queue::<TestData>(&db, &name, &MQConfig::default());
+        let info = |key: &str| {
+            QueueInfo::from(TestData { a: key.to_string() })
+                .with_unique_key(key)
+                .with_partition_key("p")
+        };
+
+        // A resend merges while nothing was queued behind the first one
+        let (first, resend) = (info("a"), info("a"));
+        let (first, resend) = tokio::join!(mq.queue(&first), mq.queue(&resend));
+        let first = first?;
+        assert_eq!(resend?, first);
+        let b = mq.queue(&info("b")).await?;
+        let again = mq.queue(&info("a")).await?;
+        assert_ne!(again, first);
+        assert_ne!(again, b);
+        assert_eq!(mq.get_queued_len().await?, 3);
+
+        // Scheduled jobs claim the key too, and leave the partition alone until they are due
+        let later = OffsetDateTime::now_utc() + Duration::from_secs(60);
+        let scheduled = mq.queue_at(&info("c"), later).await?;
+        assert_eq!(mq.queue_at(&info("c"), later).await?, scheduled);
+        let members: Vec<String> = con
+            .zrange(partition(&name, "p"), 0, -1)
+            .await
+            .convert_error()?;
+        assert!(!members.contains(&scheduled.to_string()));
+
+        // Discarding a delayed job releases its key
+        let delayed =
+            QueueInfo::new(Uuid::new_v4(), TestData { a: "d".into() }).with_unique_key("d");
+        assert_eq!(mq.queue(&delayed).await?, *delayed.id());
+        let entry = loop {
+            let data: QueueData<TestData> = RedisJobInternal::pop_to_process(
+                &mut con,
+                &name,
+                &[Priority::Normal],
+                "member",
+                &MQConfig::default(),
+                Some(1000),
+            )
+            .await?
+            .pop()
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+            if data.info.id() == delayed.id() {
+                break data.id;
+            }
+        };
+        let member = RedisJobInternal::reschedule(
+            &mut con,
+            &name,
+            &Priority::Normal,
+            &entry,
+            &delayed,
+            later,
+        )
+        .await?;
+        let errored = ErroredInfo::new(*delayed.id(), TestData { a: "d".into() }, "".into(), None);
+        RedisJobInternal::push_delayed_info(&mut con, &name, &member, &errored).await?;
+        assert!(mq.discard_delayed(delayed.id()).await?);
+        let requeued = QueueInfo::from(TestData { a: "d".into() }).with_unique_key("d");
+        assert_eq!(mq.queue(&requeued).await?, *requeued.id());
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_partition() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_partition");
+        let config = MQConfig::default();
+        let mq = idle_queue::<TestData>(&db, &name, &config);
+        let info =
+            |key: &str| QueueInfo::from(TestData { a: key.to_string() }).with_partition_key(key);
+        let first = info("a");
+        let second = info("a");
+        let other = info("b");
+        for info in [&first, &second, &other] {
+            mq.queue(info).await?;
+        }
+
+        type Queue = RedisMessageQueue<(), TestData>;
+        let mut turns = Vec::new();
+        for _ in 0..3 {
+            let data: QueueData<TestData> = RedisJobInternal::pop_to_process(
+                &mut con,
+                &name,
+                &[Priority::Normal],
+                "member",
+                &config,
+                None,
+            )
+            .await?
+            .pop()
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+            let turn =
+                Queue::take_turn(&db, &name, &config, &data.priority, &data.id, &data.info).await?;
+            turns.push((*data.info.id(), turn));
+        }
+        assert_eq!(
+            turns,
+            [
+                (*first.id(), true),
+                (*second.id(), false),
+                (*other.id(), true)
+            ]
+        );
+        assert_eq!(mq.get_scheduled_len().await?, 1);
+
+        RedisJobInternal::leave_partition(&mut con, &name, "a", first.id()).await?;
+        let ahead =
+            RedisJobInternal::join_partition(&mut con, &name, "a", second.id(), &config).await?;
+        assert_eq!(ahead, 0);
+
+        // A job that could not be queued leaves its partition again
+        let broken = test_name("test_partition_broken");
+        let _: () = con.set(&broken, "not a stream").await.convert_error()?;
+        let mq = idle_queue::<TestData>(&db, &broken, &config);
+        assert!(mq.queue(&info("c")).await.is_err());
+        let members: usize = con.zcard(partition(&broken, "c")).await.convert_error()?;
+        assert_eq!(members, 0);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_cron_schedule() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_cron");
+        let config = MQConfig::default();
+        let mq = idle_queue::<TestData>(&db, &name, &config);
+        let data = TestData {
+            a: "cron".to_string(),
+        };
+        assert!(Schedule::new("invalid", "* * *", data.clone()).is_err());
+        assert!(Schedule::new("crontab", "0 3 * * *", data.clone()).is_ok());
+        let schedule = Schedule::new("every_second", "* * * * * *", data)?;
+        mq.register_schedule(schedule.clone());
+        assert!(mq.trigger_schedule("unknown").await?.is_none());
+
+        let states = mq.get_schedules().await?;
+        assert_eq!(states.len(), 1);
+        assert!(states[0].last_run().is_none());
+        assert!(states[0].next_run().is_some());
+
+        type Queue = RedisMessageQueue<(), TestData>;
+        // The first tick only records when it is due
+        assert!(
+            Queue::run_schedule(&mut con, &name, &config, &schedule, false)
+                .await?
+                .is_none()
+        );
+        sleep(Duration::from_millis(1100)).await;
+        let fired = Queue::run_schedule(&mut con, &name, &config, &schedule, false)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(mq.get_queued_len().await?, 1);
+        let states = mq.get_schedules().await?;
+        assert_eq!(states[0].last_job(), &Some(fired));
+
+        let token = RedisJobInternal::lock_schedule(&mut con, &name, schedule.name())
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert!(
+            Queue::run_schedule(&mut con, &name, &config, &schedule, false)
+                .await?
+                .is_none()
+        );
+        assert!(mq.trigger_schedule(schedule.name()).await.is_err());
+        RedisJobInternal::unlock_schedule(&mut con, &name, schedule.name(), &token).await?;
+
+        let next_run = *mq.get_schedules().await?[0].next_run();
+        let triggered = mq
+            .trigger_schedule(schedule.name())
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(mq.get_queued_len().await?, 2);
+        let states = mq.get_schedules().await?;
+        assert_eq!(states[0].last_job(), &Some(triggered));
+        assert_eq!(states[0].next_run(), &next_run);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_quarantine() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_quarantine");
+        let config = MQConfig::default();
+        let mq = idle_queue::<TestData>(&db, &name, &config);
+        // Written by a build whose job data had another shape
+        let old = idle_queue::<Reshaped>(&db, &name, &config);
+        let info = QueueInfo::from(Reshaped { a: 1 })
+            .with_unique_key("key")
+            .with_partition_key("partition");
+        let id = old.queue(&info).await?;
+
+        let popped: Vec<QueueData<TestData>> = RedisJobInternal::pop_to_process(
+            &mut con,
+            &name,
+            &[Priority::Normal],
+            "member",
+            &config,
+            None,
+        )
+        .await?;
+        assert!(popped.is_empty());
+        assert_eq!(mq.get_queued_len().await?, 0);
+        assert_eq!(mq.get_quarantined_len().await?, 1);
+        let quarantined = mq
+            .get_quarantined_info(&id)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(quarantined.priority(), &Priority::Normal);
+        assert!(!quarantined.error().is_empty());
+        let status = mq
+            .get_job_status(&id)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(status.state(), &JobState::Failed);
+        let held: bool = con.exists(unique(&name, "key")).await.convert_error()?;
+        assert!(!held);
+        let waiting: usize = con
+            .zcard(partition(&name, "partition"))
+            .await
+            .convert_error()?;
+        assert_eq!(waiting, 0);
+
+        let undecodable = mq.retry_quarantined(&id).await.unwrap_err();
+        assert!(matches!(
+            undecodable.current_context(),
+            KernelError::Unprocessable
+        ));
+        assert!(mq.discard_quarantined(&id).await?);
+        assert!(!mq.discard_quarantined(&id).await?);
+
+        // Decodes once a fix is deployed
+        let fixed = QueueInfo::from(TestData {
+            a: "fixed".to_string(),
+        });
+        let raw = serde_json::to_string(&fixed).change_context_lazy(|| KernelError::Internal)?;
+        let quarantined = QuarantinedInfo::new(*fixed.id(), Priority::Normal, raw, "".into());
+        let quarantined =
+            serde_json::to_string(&quarantined).change_context_lazy(|| KernelError::Internal)?;
+        let _: () = con
+            .hset(quarantine(&name), fixed.id().to_string(), quarantined)
+            .await
+            .convert_error()?;
+        assert_eq!(mq.retry_quarantined(fixed.id()).await?, Some(*fixed.id()));
+        assert_eq!(mq.get_quarantined_len().await?, 0);
+        assert_eq!(mq.get_queued_len().await?, 1);
+
+        // Pages neither skip nor repeat entries
+        for _ in 0..5 {
+            let info = QuarantinedInfo::new(Uuid::new_v4(), Priority::Normal, "".into(), "".into());
+            RedisJobInternal::push_quarantined_info(&mut con, &name, &info).await?;
+        }
+        let mut listed = Vec::new();
+        for offset in [0, 2, 4, 6] {
+            let page = mq.get_quarantined_infos(&2, &offset).await?;
+            assert!(page.len() <= 2);
+            listed.extend(page.into_iter().map(|info| *info.id()));
+        }
+        listed.sort();
+        listed.dedup();
+        assert_eq!(listed.len(), 5);
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_payload_migration() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_payload");
+        let config = MQConfig::default();
+        let old = QueueInfo::from(TestData {
+            a: "old".to_string(),
+        });
+        RedisJobInternal::insert_waiting(&mut con, &name, &old).await?;
+        let popped: Vec<QueueData<Renamed>> = RedisJobInternal::pop_to_process(
+            &mut con,
+            &name,
+            &[Priority::Normal],
+            "member",
+            &config,
+            None,
+        )
+        .await?;
+        assert_eq!(popped.len(), 1);
+        assert_eq!(popped[0].info.id(), old.id());
+        assert_eq!(popped[0].info.data().b, "old");
+        assert_eq!(popped[0].info.schema(), &Some(Renamed::schema()));
+        RedisJobInternal::mark_done(&mut con, &name, &popped[0].priority, &popped[0].id).await?;
+
+        // A worker of the older build leaves it to the newer one
+        let new = QueueInfo::from(Renamed {
+            b: "new".to_string(),
+        });
+        RedisJobInternal::insert_waiting(&mut con, &name, &new).await?;
+        let popped: Vec<QueueData<TestData>> = RedisJobInternal::pop_to_process(
+            &mut con,
+            &name,
+            &[Priority::Normal],
+            "member",
+            &config,
+            None,
+        )
+        .await?;
+        assert!(popped.is_empty());
+        let mq = idle_queue::<TestData>(&db, &name, &config);
+        assert_eq!(mq.get_quarantined_len().await?, 0);
+        assert_eq!(mq.get_queued_len().await?, 1);
+
+        // Failed jobs are upgraded when read, and a newer one is left to the newer build
+        let failed =
+            ErroredInfo::new(*old.id(), old.data().clone(), "".into(), None).with_envelope(&old);
+        RedisJobInternal::push_failed_info(&mut con, &name, &failed).await?;
+        let newer =
+            ErroredInfo::new(*new.id(), new.data().clone(), "".into(), None).with_envelope(&new);
+        RedisJobInternal::push_failed_info(&mut con, &name, &newer).await?;
+        let renamed = idle_queue::<Renamed>(&db, &name, &config);
+        let upgraded = renamed
+            .get_failed_info(old.id())
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(upgraded.data().b, "old");
+        assert_eq!(upgraded.schema(), &Some(Renamed::schema()));
+        let all = ErroredInfoFilter::default();
+        assert_eq!(
+            renamed.get_failed_infos(&all, &None, &10, &0).await?.len(),
+            2
+        );
+        let listed = mq.get_failed_infos(&all, &None, &10, &0).await?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id(), old.id());
+        assert!(mq.get_failed_info(new.id()).await.is_err());
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_attempt_history() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let name = test_name("test_history");
+        let mut config = MQConfig::default();
+        config.substitute(|config| {
+            *config.worker_count = 1;
+            *config.retry_policy = RetryPolicy::fixed(2, Duration::from_millis(100));
+            *config.schedule_interval = Duration::from_millis(100);
+        });
+        let mq = RedisMessageQueue::new(
+            db.clone(),
+            (),
+            &name,
+            config,
+            |_none, _data: TestData| async move { Err(Report::new(Delay)) },
+        );
+        mq.start_workers();
+        let id = mq
+            .queue(&QueueInfo::new(Uuid::new_v4(), TestData { a: "a".into() }))
+            .await?;
+        let mut info = None;
+        for _ in 0..50 {
+            sleep(Duration::from_millis(100)).await;
+            info = mq.get_failed_info(&id).await?;
+            if info.is_some() {
+                break;
+            }
+        }
+        let info = info.ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert!(info.queued_at().is_some());
+        let attempts: Vec<i64> = info.attempts().iter().map(|a| *a.attempt()).collect();
+        assert_eq!(attempts, vec![1, 2]);
+        let first = &info.attempts()[0];
+        assert_eq!(first.operation(), &Delay);
+        assert!(!first.worker().is_empty());
+        assert!(first.started_at() <= first.finished_at());
+        let errored_at = info
+            .errored_at()
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+
+        // Recorded before attempt history existed
+        let legacy = ErroredInfo::new(Uuid::new_v4(), TestData { a: "b".into() }, "".into(), None);
+        RedisJobInternal::push_failed_info(&mut con, &name, &legacy).await?;
+        let all = ErroredInfoFilter::default();
+        let sort = Some(InfoSort::new(InfoSortKey::ErroredAt, SortOrder::Asc));
+        let infos = mq.get_failed_infos(&all, &sort, &10, &0).await?;
+        let ids: Vec<Uuid> = infos.iter().map(|info| *info.id()).collect();
+        assert_eq!(ids, vec![*legacy.id(), id]);
+        let infos = mq.get_failed_infos(&all, &sort, &10, &1).await?;
+        assert_eq!(infos.len(), 1);
+
+        let window = ErroredInfoFilter::default().with_window(Some(errored_at), None);
+        let infos = mq.get_failed_infos(&window, &None, &10, &0).await?;
+        assert_eq!(infos.len(), 1);
+        let window = ErroredInfoFilter::default()
+            .with_window(None, Some(errored_at - Duration::from_secs(1)));
+        assert!(mq
+            .get_failed_infos(&window, &None, &10, &0)
+            .await?
+            .is_empty());
+        let kind = ErroredInfoFilter::default().with_kind(Some(ErrorKind::Timeout));
+        assert!(mq.get_failed_infos(&kind, &None, &10, &0).await?.is_empty());
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_layers() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let name = test_name("test_layers");
+        let mut config = MQConfig::default();
+        config.substitute(|config| {
+            *config.worker_count = 3;
+            *config.retry_policy = RetryPolicy::fixed(1, Duration::from_millis(100));
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let count = {
+            let running = running.clone();
+            let peak = peak.clone();
+            move |job: JobContext, module, data, next: Next<(), TestData>| {
+                let running = running.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    let result = next.run(job, module, data).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    result
+                }
+            }
+        };
+        let mq = RedisMessageQueue::new(
+            db.clone(),
+            (),
+            &name,
+            config,
+            |_none, data: TestData| async move {
+                match data.a.as_str() {
+                    "panic" => panic!("boom"),
+                    "slow" => sleep(Duration::from_secs(5)).await,
+                    _ => sleep(Duration::from_millis(50)).await,
+                }
+                Ok(None)
+            },
+        )
+        .with_layer(TraceLayer)
+        .with_layer(ConcurrencyLimitLayer::new(1))
+        .with_layer(count)
+        .with_layer(CatchPanicLayer)
+        .with_layer(TimeoutLayer::new(Duration::from_millis(200)));
+        mq.start_workers();
+        let job = |a: &str| QueueInfo::new(Uuid::new_v4(), TestData { a: a.into() });
+        let panicked = mq.queue(&job("panic")).await?;
+        let slow = mq.queue(&job("slow")).await?;
+        let mut done = Vec::new();
+        for _ in 0..3 {
+            done.push(mq.queue(&job("ok")).await?);
+        }
+        let expected = [(panicked, JobState::Failed), (slow, JobState::Failed)]
+            .into_iter()
+            .chain(done.iter().map(|id| (*id, JobState::Done)))
+            .collect::<Vec<_>>();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let mut states = Vec::new();
+            for (id, _) in &expected {
+                states.push(mq.get_job_status(id).await?.map(|status| *status.state()));
+            }
+            if states
+                .iter()
+                .zip(&expected)
+                .all(|(state, (_, expected))| state.as_ref() == Some(expected))
+            {
+                break;
+            }
+            assert!(Instant::now() < deadline, "Jobs did not settle: {states:?}");
+            sleep(Duration::from_millis(50)).await;
+        }
+        let info = mq
+            .get_failed_info(&panicked)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(info.kind(), &Some(ErrorKind::Internal));
+        assert!(info.stack_trace().contains("boom"));
+        let info = mq
+            .get_failed_info(&slow)
+            .await?
+            .ok_or_else(|| Report::new(KernelError::Internal))?;
+        assert_eq!(info.kind(), &Some(ErrorKind::Timeout));
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        assert!(mq.get_worker_states().iter().all(|state| *state.alive()));
         Ok(())
     }
 
@@ -642,7 +3006,8 @@ mod test {
         let name = "test";
         let mut config = MQConfig::default();
         config.substitute(|config| {
-            *config.retry_delay = Duration::from_secs(1);
+            *config.reclaim_idle = Duration::from_secs(1);
+            *config.retry_policy = RetryPolicy::fixed(3, Duration::from_secs(1));
         });
         let mq = RedisMessageQueue::new(
             db.clone(),
@@ -654,7 +3019,7 @@ mod test {
                 sleep(Duration::from_millis(20)).await;
                 // Delayed in 50%
                 if random() {
-                    Ok(())
+                    Ok(None)
                 } else {
                     Err(Report::new(Delay))
                 }
@@ -0,0 +1,111 @@
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use deadpool_redis::redis::Script;
+use kernel::interface::rate_limit::{DependOnRateLimiter, RateLimiter};
+use kernel::prelude::entity::{RateLimitDecision, RateLimitKey, RateLimitQuota};
+use kernel::KernelError;
+
+use crate::database::{RedisDatabase, RedisTransaction};
+use crate::error::ConvertError;
+
+const RATE_LIMIT: &str = "rate_limit";
+
+/// Refills `capacity` tokens per `period` ms using the server clock, so all instances share one bucket.
+/// Returns `{allowed, remaining, retry_after_ms, reset_after_ms}`
+static TOKEN_BUCKET: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local capacity = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or capacity
+local updated_at = tonumber(bucket[2]) or now
+local rate = capacity / period
+tokens = math.min(capacity, tokens + math.max(0, now - updated_at) * rate)
+local allowed = 0
+local retry_after = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+else
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', now)
+redis.call('PEXPIRE', KEYS[1], period)
+return {allowed, math.floor(tokens), retry_after, math.ceil((capacity - tokens) / rate)}
+"#,
+    )
+});
+
+pub struct RedisRateLimiter;
+
+#[async_trait::async_trait]
+impl RateLimiter for RedisRateLimiter {
+    type Transaction = RedisTransaction;
+
+    async fn acquire(
+        &self,
+        con: &mut RedisTransaction,
+        key: &RateLimitKey,
+        quota: &RateLimitQuota,
+    ) -> error_stack::Result<RateLimitDecision, KernelError> {
+        let key = format!("{RATE_LIMIT}:{}", key.as_ref());
+        let (allowed, remaining, retry_after, reset_after): (u8, u32, u64, u64) = TOKEN_BUCKET
+            .key(key)
+            .arg(quota.capacity())
+            .arg(quota.period().as_millis() as u64)
+            .invoke_async(&mut **con)
+            .await
+            .convert_error()?;
+        Ok(RateLimitDecision::new(
+            allowed == 1,
+            remaining,
+            Duration::from_millis(retry_after),
+            Duration::from_millis(reset_after),
+        ))
+    }
+}
+
+impl DependOnRateLimiter for RedisDatabase {
+    type RateLimiter = RedisRateLimiter;
+    fn rate_limiter(&self) -> &Self::RateLimiter {
+        &RedisRateLimiter
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use kernel::interface::database::DatabaseConnection;
+    use kernel::interface::rate_limit::RateLimiter;
+    use kernel::prelude::entity::{RateLimitKey, RateLimitQuota};
+    use kernel::KernelError;
+    use uuid::Uuid;
+
+    use crate::database::redis::rate_limit::RedisRateLimiter;
+    use crate::database::RedisDatabase;
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_rate_limit() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let key = RateLimitKey::new(format!("test:{}", Uuid::new_v4()));
+        let quota = RateLimitQuota::new(2, Duration::from_secs(60));
+
+        let first = RedisRateLimiter.acquire(&mut con, &key, &quota).await?;
+        assert!(first.is_allowed());
+        assert_eq!(first.remaining(), 1);
+        let second = RedisRateLimiter.acquire(&mut con, &key, &quota).await?;
+        assert!(second.is_allowed());
+        assert_eq!(second.remaining(), 0);
+        let third = RedisRateLimiter.acquire(&mut con, &key, &quota).await?;
+        assert!(!third.is_allowed());
+        assert!(!third.retry_after().is_zero());
+        Ok(())
+    }
+}
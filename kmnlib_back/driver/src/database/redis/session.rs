@@ -0,0 +1,321 @@
+use std::time::Duration;
+
+use deadpool_redis::redis;
+use deadpool_redis::redis::AsyncCommands;
+use kernel::interface::session::{
+    DependOnPasswordResetDelivery, DependOnPasswordResetStore, DependOnTokenRevocation,
+    PasswordResetDelivery, PasswordResetStore, TokenRevocation,
+};
+use kernel::prelude::entity::{PasswordResetToken, TokenId, UserId};
+use kernel::KernelError;
+use serde_json::json;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::database::{RedisDatabase, RedisTransaction};
+use crate::error::ConvertError;
+
+const REVOKED_TOKEN: &str = "revoked_token";
+const TOKEN_GENERATION: &str = "token_generation";
+const PASSWORD_RESET: &str = "password_reset";
+/// Read by the mailer, which sends each token to its user
+pub const PASSWORD_RESET_OUTBOX: &str = "password_reset_outbox";
+
+pub struct RedisSessionRepository;
+
+#[async_trait::async_trait]
+impl TokenRevocation for RedisSessionRepository {
+    type Transaction = RedisTransaction;
+
+    async fn revoke(
+        &self,
+        con: &mut RedisTransaction,
+        id: &TokenId,
+        ttl: &Duration,
+    ) -> error_stack::Result<bool, KernelError> {
+        let key = format!("{REVOKED_TOKEN}:{}", id.as_ref());
+        let set: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(true)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut **con)
+            .await
+            .convert_error()?;
+        Ok(set.is_some())
+    }
+
+    async fn is_revoked(
+        &self,
+        con: &mut RedisTransaction,
+        id: &TokenId,
+    ) -> error_stack::Result<bool, KernelError> {
+        let key = format!("{REVOKED_TOKEN}:{}", id.as_ref());
+        con.exists(key).await.convert_error()
+    }
+
+    async fn generation(
+        &self,
+        con: &mut RedisTransaction,
+        user_id: &UserId,
+    ) -> error_stack::Result<u64, KernelError> {
+        let key = format!("{TOKEN_GENERATION}:{}", user_id.as_ref());
+        let generation: Option<u64> = con.get(key).await.convert_error()?;
+        Ok(generation.unwrap_or_default())
+    }
+
+    async fn revoke_all(
+        &self,
+        con: &mut RedisTransaction,
+        user_id: &UserId,
+    ) -> error_stack::Result<u64, KernelError> {
+        let key = format!("{TOKEN_GENERATION}:{}", user_id.as_ref());
+        con.incr(key, 1).await.convert_error()
+    }
+}
+
+impl DependOnTokenRevocation for RedisDatabase {
+    type TokenRevocation = RedisSessionRepository;
+    fn token_revocation(&self) -> &Self::TokenRevocation {
+        &RedisSessionRepository
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordResetStore for RedisSessionRepository {
+    type Transaction = RedisTransaction;
+
+    async fn issue(
+        &self,
+        con: &mut RedisTransaction,
+        token: &PasswordResetToken,
+        user_id: &UserId,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError> {
+        let key = format!("{PASSWORD_RESET}:{}", token.as_ref());
+        con.set_ex(key, user_id.as_ref().to_string(), ttl.as_secs().max(1))
+            .await
+            .convert_error()
+    }
+
+    async fn consume(
+        &self,
+        con: &mut RedisTransaction,
+        token: &PasswordResetToken,
+    ) -> error_stack::Result<Option<UserId>, KernelError> {
+        let key = format!("{PASSWORD_RESET}:{}", token.as_ref());
+        let user_id: Option<String> = con.get_del(key).await.convert_error()?;
+        Ok(user_id
+            .and_then(|id| Uuid::parse_str(&id).ok())
+            .map(UserId::new))
+    }
+}
+
+impl DependOnPasswordResetStore for RedisDatabase {
+    type PasswordResetStore = RedisSessionRepository;
+    fn password_reset_store(&self) -> &Self::PasswordResetStore {
+        &RedisSessionRepository
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordResetDelivery for RedisSessionRepository {
+    type Transaction = RedisTransaction;
+
+    async fn deliver(
+        &self,
+        con: &mut RedisTransaction,
+        token: &PasswordResetToken,
+        user_id: &UserId,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError> {
+        let expires_at = OffsetDateTime::now_utc() + *ttl;
+        let message = json!({
+            "user_id": user_id.as_ref(),
+            "token": token.as_ref(),
+            "expires_at": expires_at.unix_timestamp(),
+        });
+        con.rpush(PASSWORD_RESET_OUTBOX, message.to_string())
+            .await
+            .convert_error()
+    }
+}
+
+impl DependOnPasswordResetDelivery for RedisDatabase {
+    type PasswordResetDelivery = RedisSessionRepository;
+    fn password_reset_delivery(&self) -> &Self::PasswordResetDelivery {
+        &RedisSessionRepository
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use deadpool_redis::redis::AsyncCommands;
+    use kernel::interface::database::DatabaseConnection;
+    use kernel::interface::session::{PasswordResetDelivery, PasswordResetStore, TokenRevocation};
+    use kernel::prelude::entity::{PasswordResetToken, TokenId, UserId};
+    use kernel::KernelError;
+    use uuid::Uuid;
+
+    use crate::database::redis::session::{RedisSessionRepository, PASSWORD_RESET_OUTBOX};
+    use crate::database::RedisDatabase;
+    use crate::error::ConvertError;
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_session() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let ttl = Duration::from_secs(10);
+
+        let token_id = TokenId::new(Uuid::new_v4());
+        assert!(
+            !RedisSessionRepository
+                .is_revoked(&mut con, &token_id)
+                .await?
+        );
+        RedisSessionRepository
+            .revoke(&mut con, &token_id, &ttl)
+            .await?;
+        assert!(
+            RedisSessionRepository
+                .is_revoked(&mut con, &token_id)
+                .await?
+        );
+
+        let token = PasswordResetToken::new(Uuid::new_v4());
+        let user_id = UserId::new(Uuid::new_v4());
+        RedisSessionRepository
+            .issue(&mut con, &token, &user_id, &ttl)
+            .await?;
+        let consumed = RedisSessionRepository.consume(&mut con, &token).await?;
+        assert_eq!(consumed, Some(user_id));
+        let consumed = RedisSessionRepository.consume(&mut con, &token).await?;
+        assert!(consumed.is_none());
+        Ok(())
+    }
+
+    /// A refresh token is only exchanged once, also by concurrent refreshes
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_refresh() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let ttl = Duration::from_secs(10);
+        let token_id = TokenId::new(Uuid::new_v4());
+
+        let revoke = |db: RedisDatabase, token_id: TokenId| async move {
+            let mut con = db.transact().await?;
+            RedisSessionRepository
+                .revoke(&mut con, &token_id, &ttl)
+                .await
+        };
+        let (first, second) = tokio::join!(
+            revoke(db.clone(), token_id.clone()),
+            revoke(db.clone(), token_id.clone())
+        );
+        assert!(first? ^ second?);
+
+        let mut con = db.transact().await?;
+        assert!(
+            !RedisSessionRepository
+                .revoke(&mut con, &token_id, &ttl)
+                .await?
+        );
+        Ok(())
+    }
+
+    /// Logging out revokes the token until it would have expired
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_logout() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let token_id = TokenId::new(Uuid::new_v4());
+
+        assert!(
+            RedisSessionRepository
+                .revoke(&mut con, &token_id, &Duration::from_secs(1))
+                .await?
+        );
+        assert!(
+            RedisSessionRepository
+                .is_revoked(&mut con, &token_id)
+                .await?
+        );
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(
+            !RedisSessionRepository
+                .is_revoked(&mut con, &token_id)
+                .await?
+        );
+        Ok(())
+    }
+
+    /// Changing or resetting the password moves the user to the next generation
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_revoke_all() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let user_id = UserId::new(Uuid::new_v4());
+
+        assert_eq!(
+            RedisSessionRepository
+                .generation(&mut con, &user_id)
+                .await?,
+            0
+        );
+        assert_eq!(
+            RedisSessionRepository
+                .revoke_all(&mut con, &user_id)
+                .await?,
+            1
+        );
+        assert_eq!(
+            RedisSessionRepository
+                .generation(&mut con, &user_id)
+                .await?,
+            1
+        );
+        let other = UserId::new(Uuid::new_v4());
+        assert_eq!(
+            RedisSessionRepository.generation(&mut con, &other).await?,
+            0
+        );
+        Ok(())
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_password_reset() -> error_stack::Result<(), KernelError> {
+        let db = RedisDatabase::new()?;
+        let mut con = db.transact().await?;
+        let ttl = Duration::from_secs(10);
+        let token = PasswordResetToken::new(Uuid::new_v4());
+        let user_id = UserId::new(Uuid::new_v4());
+
+        RedisSessionRepository
+            .issue(&mut con, &token, &user_id, &ttl)
+            .await?;
+        RedisSessionRepository
+            .deliver(&mut con, &token, &user_id, &ttl)
+            .await?;
+        let outbox: Vec<String> = con
+            .lrange(PASSWORD_RESET_OUTBOX, 0, -1)
+            .await
+            .convert_error()?;
+        let delivered = outbox
+            .iter()
+            .filter_map(|message| serde_json::from_str::<serde_json::Value>(message).ok())
+            .find(|message| message["token"] == token.as_ref().to_string())
+            .expect("token is delivered");
+        assert_eq!(delivered["user_id"], user_id.as_ref().to_string());
+
+        let consumed = RedisSessionRepository.consume(&mut con, &token).await?;
+        assert_eq!(consumed, Some(user_id));
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use crate::metrics::{MQ_JOBS_IN_FLIGHT, MQ_JOB_DURATION_SECONDS};
+use crate::telemetry::continue_trace;
+use error_stack::Report;
+use kernel::interface::mq::{
+    ErrorKind, ErrorOperation, ErrorPolicy, HandlerResult, JobContext, Layer, Next,
+};
+use kernel::KernelError;
+use metrics::{gauge, histogram};
+use std::any::Any;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::{debug, info_span, warn, Instrument};
+
+/// Runs each job in a `handle_job` span, continuing the trace it was queued from,
+/// and logs how it ended. The queue opens no span of its own
+#[derive(Debug, Clone, Default)]
+pub struct TraceLayer;
+
+impl<M: 'static + Send, T: 'static + Send> Layer<M, T> for TraceLayer {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        let span = info_span!(
+            "handle_job",
+            queue = %job.queue(),
+            job_id = %job.id(),
+            attempt = job.attempt(),
+            worker = %job.worker(),
+        );
+        continue_trace(&span, job.trace_context());
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let result = next.run(job, module, data).await;
+                let elapsed = started.elapsed();
+                match &result {
+                    Ok(_) => debug!(?elapsed, "Handled"),
+                    Err(report) => warn!(?elapsed, "Handled with {}", report.current_context()),
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Records the handling time of jobs by queue and result, and how many are running
+#[derive(Debug, Clone, Default)]
+pub struct MetricsLayer;
+
+impl<M: 'static + Send, T: 'static + Send> Layer<M, T> for MetricsLayer {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        let queue = job.queue().clone();
+        Box::pin(async move {
+            let _in_flight = InFlight::new(queue.clone());
+            let started = Instant::now();
+            let result = next.run(job, module, data).await;
+            let outcome = match &result {
+                Ok(_) => "ok",
+                Err(report) => match report.current_context() {
+                    ErrorOperation::Delay => "delay",
+                    ErrorOperation::Failed => "failed",
+                },
+            };
+            histogram!(MQ_JOB_DURATION_SECONDS, "queue" => queue, "result" => outcome)
+                .record(started.elapsed().as_secs_f64());
+            result
+        })
+    }
+}
+
+/// Decrements the gauge when the job ends, also when a layer above drops it
+struct InFlight(String);
+
+impl InFlight {
+    fn new(queue: String) -> Self {
+        gauge!(MQ_JOBS_IN_FLIGHT, "queue" => queue.clone()).increment(1.0);
+        Self(queue)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        gauge!(MQ_JOBS_IN_FLIGHT, "queue" => self.0.clone()).decrement(1.0);
+    }
+}
+
+/// Gives up on jobs that run longer than `timeout`, they are classified as
+/// [`ErrorKind::Timeout`] by the policy, so retried by default
+#[derive(Debug, Clone)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+    policy: ErrorPolicy,
+}
+
+impl TimeoutLayer {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            policy: ErrorPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(self, policy: ErrorPolicy) -> Self {
+        Self { policy, ..self }
+    }
+}
+
+impl<M: 'static + Send, T: 'static + Send> Layer<M, T> for TimeoutLayer {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        let timeout = self.timeout;
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, next.run(job, module, data)).await {
+                Ok(result) => result,
+                Err(_) => Err(policy.classify(
+                    Report::new(KernelError::Timeout)
+                        .attach_printable(format!("Job did not finish within {timeout:?}")),
+                )),
+            }
+        })
+    }
+}
+
+/// Turns a panicking handler into a failed job instead of taking the worker down.
+/// A panic is a bug, so the job is not retried
+#[derive(Debug, Clone, Default)]
+pub struct CatchPanicLayer;
+
+impl<M: 'static + Send, T: 'static + Send> Layer<M, T> for CatchPanicLayer {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        Box::pin(async move {
+            let caught = match catch_unwind(AssertUnwindSafe(|| next.run(job, module, data))) {
+                Ok(future) => CatchUnwind(future).await,
+                Err(panic) => Err(panic),
+            };
+            caught.unwrap_or_else(|panic| {
+                Err(Report::new(ErrorOperation::Failed)
+                    .attach(ErrorKind::Internal)
+                    .attach_printable(format!("Handler panicked: {}", panic_message(&panic))))
+            })
+        })
+    }
+}
+
+struct CatchUnwind(HandlerResult);
+
+impl Future for CatchUnwind {
+    type Output = std::thread::Result<error_stack::Result<Option<String>, ErrorOperation>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = &mut self.0;
+        match catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(cx))) {
+            Ok(Poll::Ready(result)) => Poll::Ready(Ok(result)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+fn panic_message(panic: &Box<dyn Any + Send>) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown"
+    }
+}
+
+/// Lets at most `max` jobs through at a time, across the workers of every queue the layer,
+/// or a clone of it, is attached to. Jobs wait for a turn before going down the stack
+#[derive(Debug, Clone)]
+pub struct ConcurrencyLimitLayer {
+    semaphore: Arc<Semaphore>,
+}
+
+impl ConcurrencyLimitLayer {
+    pub fn new(max: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+        }
+    }
+}
+
+impl<M: 'static + Send, T: 'static + Send> Layer<M, T> for ConcurrencyLimitLayer {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        let semaphore = self.semaphore.clone();
+        Box::pin(async move {
+            let Ok(_permit) = semaphore.acquire_owned().await else {
+                return Err(Report::new(ErrorOperation::Delay)
+                    .attach(ErrorKind::Internal)
+                    .attach_printable("Concurrency limit is closed"));
+            };
+            next.run(job, module, data).await
+        })
+    }
+}
@@ -4,6 +4,9 @@ use kernel::KernelError;
 
 pub mod database;
 pub mod error;
+pub mod layer;
+pub mod metrics;
+mod telemetry;
 
 pub(crate) fn env(key: &str) -> error_stack::Result<String, KernelError> {
     dotenvy::var(key).change_context_lazy(|| KernelError::Internal)
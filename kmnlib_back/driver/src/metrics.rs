@@ -0,0 +1,29 @@
+use metrics::{describe_counter, describe_gauge, describe_histogram};
+
+pub const MQ_JOBS_TOTAL: &str = "mq_jobs_total";
+pub const MQ_QUEUE_DEPTH: &str = "mq_queue_depth";
+pub const MQ_JOB_DURATION_SECONDS: &str = "mq_job_duration_seconds";
+pub const MQ_JOBS_IN_FLIGHT: &str = "mq_jobs_in_flight";
+pub const DB_POOL_CONNECTIONS: &str = "db_pool_connections";
+pub const EVENTS_APPENDED_TOTAL: &str = "events_appended_total";
+
+pub fn describe_metrics() {
+    describe_counter!(
+        MQ_JOBS_TOTAL,
+        "Number of processed queue jobs by queue and outcome"
+    );
+    describe_gauge!(MQ_QUEUE_DEPTH, "Number of jobs by queue and state");
+    describe_histogram!(
+        MQ_JOB_DURATION_SECONDS,
+        "Time jobs spent in the handler by queue and result"
+    );
+    describe_gauge!(MQ_JOBS_IN_FLIGHT, "Number of jobs being handled by queue");
+    describe_gauge!(
+        DB_POOL_CONNECTIONS,
+        "Number of database pool connections by pool and state"
+    );
+    describe_counter!(
+        EVENTS_APPENDED_TOTAL,
+        "Number of events appended by aggregate type"
+    );
+}
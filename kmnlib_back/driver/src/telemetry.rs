@@ -0,0 +1,65 @@
+use opentelemetry::global;
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+pub(crate) fn current_trace_context() -> HashMap<String, String> {
+    let mut carrier = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&Span::current().context(), &mut carrier)
+    });
+    carrier
+}
+
+pub(crate) fn continue_trace(span: &Span, carrier: &HashMap<String, String>) {
+    let context = global::get_text_map_propagator(|propagator| propagator.extract(carrier));
+    span.set_parent(context);
+}
+
+#[cfg(test)]
+mod test {
+    use kernel::interface::mq::{Payload, QueueInfo};
+    use opentelemetry::global;
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use serde::{Deserialize, Serialize};
+    use tracing::info_span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::layer::SubscriberExt;
+    use uuid::Uuid;
+
+    use crate::telemetry::{continue_trace, current_trace_context};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct TestData;
+
+    impl Payload for TestData {
+        const TYPE_NAME: &'static str = "test_data";
+        const VERSION: u32 = 1;
+    }
+
+    #[test]
+    fn test_round_trip() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = TracerProvider::builder().build();
+        let tracer = provider.tracer("test");
+        let subscriber =
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let queued = info_span!("queue");
+        let trace_id = queued.context().span().span_context().trace_id();
+        let info = queued.in_scope(|| {
+            QueueInfo::new(Uuid::new_v4(), TestData).with_trace_context(current_trace_context())
+        });
+        assert!(info.trace_context().contains_key("traceparent"));
+
+        let raw = serde_json::to_string(&info).unwrap();
+        let job: QueueInfo<TestData> = QueueInfo::decode(raw.as_bytes()).unwrap();
+        let handled = info_span!("handle_job");
+        continue_trace(&handled, job.trace_context());
+        let context = handled.context();
+        assert_eq!(context.span().span_context().trace_id(), trace_id);
+    }
+}
@@ -1,6 +1,12 @@
+mod api_key;
 mod book;
 mod common;
+mod idempotency;
+mod rate_limit;
 mod rent;
+mod session;
 mod user;
 
-pub use self::{book::*, common::*, rent::*, user::*};
+pub use self::{
+    api_key::*, book::*, common::*, idempotency::*, rate_limit::*, rent::*, session::*, user::*,
+};
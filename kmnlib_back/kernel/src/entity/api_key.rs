@@ -0,0 +1,58 @@
+mod expires_at;
+mod hash;
+mod id;
+mod name;
+mod revoked_at;
+mod scope;
+
+pub use self::{expires_at::*, hash::*, id::*, name::*, revoked_at::*, scope::*};
+use crate::entity::{CreatedAt, UserId};
+use destructure::{Destructure, Mutation};
+use time::OffsetDateTime;
+use vodca::References;
+
+/// Non-human credential acting on behalf of its owner, limited to its scopes
+#[derive(Debug, Clone, Eq, PartialEq, Destructure, References, Mutation)]
+pub struct ApiKey {
+    id: ApiKeyId,
+    owner_id: UserId,
+    name: ApiKeyName,
+    hash: ApiKeyHash,
+    scopes: Vec<ApiKeyScope>,
+    expires_at: Option<ApiKeyExpiresAt>,
+    revoked_at: Option<ApiKeyRevokedAt>,
+    created_at: CreatedAt<ApiKey>,
+}
+
+impl ApiKey {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: ApiKeyId,
+        owner_id: UserId,
+        name: ApiKeyName,
+        hash: ApiKeyHash,
+        scopes: Vec<ApiKeyScope>,
+        expires_at: Option<ApiKeyExpiresAt>,
+        revoked_at: Option<ApiKeyRevokedAt>,
+        created_at: CreatedAt<ApiKey>,
+    ) -> Self {
+        Self {
+            id,
+            owner_id,
+            name,
+            hash,
+            scopes,
+            expires_at,
+            revoked_at,
+            created_at,
+        }
+    }
+
+    pub fn is_active(&self, now: &OffsetDateTime) -> bool {
+        self.revoked_at.is_none()
+            && self
+                .expires_at
+                .as_ref()
+                .is_none_or(|expires_at| expires_at.as_ref() > now)
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use vodca::{AsRefln, Fromln};
+
+/// Hex encoded SHA-256 of the raw key, which is only shown once on creation
+#[derive(Debug, Clone, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct ApiKeyHash(String);
+
+impl ApiKeyHash {
+    pub fn new(hash: impl Into<String>) -> Self {
+        Self(hash.into())
+    }
+}
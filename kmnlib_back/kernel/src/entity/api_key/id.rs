@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use vodca::{AsRefln, Fromln};
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct ApiKeyId(Uuid);
+
+impl ApiKeyId {
+    pub fn new(id: impl Into<Uuid>) -> Self {
+        Self(id.into())
+    }
+}
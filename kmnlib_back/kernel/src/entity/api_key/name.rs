@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use vodca::{AsRefln, Fromln};
+
+#[derive(Debug, Clone, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct ApiKeyName(String);
+
+impl ApiKeyName {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+}
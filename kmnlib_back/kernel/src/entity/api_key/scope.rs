@@ -0,0 +1,48 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+
+use crate::KernelError;
+
+/// Write access granted to a key. Reads only need a valid key
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiKeyScope {
+    Books,
+    Users,
+    Rents,
+    Queue,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::Books => "books",
+            ApiKeyScope::Users => "users",
+            ApiKeyScope::Rents => "rents",
+            ApiKeyScope::Queue => "queue",
+        }
+    }
+}
+
+impl Display for ApiKeyScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = Report<KernelError>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "books" => Ok(ApiKeyScope::Books),
+            "users" => Ok(ApiKeyScope::Users),
+            "rents" => Ok(ApiKeyScope::Rents),
+            "queue" => Ok(ApiKeyScope::Queue),
+            other => Err(Report::new(KernelError::Internal)
+                .attach_printable(format!("Unknown api key scope: {other}"))),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 mod event;
 mod flag;
+mod metadata;
 mod operation;
 mod time;
 
-pub use self::{event::*, flag::*, operation::*, time::*};
+pub use self::{event::*, flag::*, metadata::*, operation::*, time::*};
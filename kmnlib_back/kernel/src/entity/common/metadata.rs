@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use vodca::{AsRefln, Fromln};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct ActorId(String);
+
+impl ActorId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct CorrelationId(Uuid);
+
+impl CorrelationId {
+    pub fn new(id: impl Into<Uuid>) -> Self {
+        Self(id.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct CausationId(Uuid);
+
+impl CausationId {
+    pub fn new(id: impl Into<Uuid>) -> Self {
+        Self(id.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct ClientIp(String);
+
+impl ClientIp {
+    pub fn new(ip: impl Into<String>) -> Self {
+        Self(ip.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct UserAgent(String);
+
+impl UserAgent {
+    pub fn new(agent: impl Into<String>) -> Self {
+        Self(agent.into())
+    }
+}
@@ -0,0 +1,5 @@
+mod fingerprint;
+mod key;
+mod record;
+
+pub use self::{fingerprint::*, key::*, record::*};
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use vodca::{AsRefln, Fromln};
+
+/// Digest of the request a key was first used with
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct RequestFingerprint(String);
+
+impl RequestFingerprint {
+    pub fn new(fingerprint: impl Into<String>) -> Self {
+        Self(fingerprint.into())
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use vodca::{AsRefln, Fromln};
+
+/// Client supplied `Idempotency-Key`, scoped to the caller that sent it
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::entity::RequestFingerprint;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IdempotentResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// `response` is `None` while the first request is still being processed
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    fingerprint: RequestFingerprint,
+    response: Option<IdempotentResponse>,
+}
+
+impl IdempotencyRecord {
+    pub fn new(fingerprint: RequestFingerprint, response: Option<IdempotentResponse>) -> Self {
+        Self {
+            fingerprint,
+            response,
+        }
+    }
+
+    pub fn fingerprint(&self) -> &RequestFingerprint {
+        &self.fingerprint
+    }
+
+    pub fn response(&self) -> Option<&IdempotentResponse> {
+        self.response.as_ref()
+    }
+}
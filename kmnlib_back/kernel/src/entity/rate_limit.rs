@@ -0,0 +1,5 @@
+mod decision;
+mod key;
+mod quota;
+
+pub use self::{decision::*, key::*, quota::*};
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+/// Outcome of taking a token from a bucket
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimitDecision {
+    allowed: bool,
+    remaining: u32,
+    retry_after: Duration,
+    reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    pub fn new(
+        allowed: bool,
+        remaining: u32,
+        retry_after: Duration,
+        reset_after: Duration,
+    ) -> Self {
+        Self {
+            allowed,
+            remaining,
+            retry_after,
+            reset_after,
+        }
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Time until the next token is available, zero if allowed
+    pub fn retry_after(&self) -> &Duration {
+        &self.retry_after
+    }
+
+    /// Time until the bucket is full again
+    pub fn reset_after(&self) -> &Duration {
+        &self.reset_after
+    }
+}
@@ -0,0 +1,11 @@
+use vodca::{AsRefln, Fromln};
+
+/// Identifies a bucket, e.g. `books:user:<id>` or `auth:ip:<addr>`
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Fromln, AsRefln)]
+pub struct RateLimitKey(String);
+
+impl RateLimitKey {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self(key.into())
+    }
+}
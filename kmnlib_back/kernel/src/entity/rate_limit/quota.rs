@@ -0,0 +1,44 @@
+use crate::KernelError;
+use error_stack::Report;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Bucket of `capacity` tokens that refills completely over `period`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RateLimitQuota {
+    capacity: u32,
+    period: Duration,
+}
+
+impl RateLimitQuota {
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        Self { capacity, period }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn period(&self) -> &Duration {
+        &self.period
+    }
+}
+
+/// Parses `<capacity>/<seconds>`, e.g. `30/60`
+impl FromStr for RateLimitQuota {
+    type Err = Report<KernelError>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            Report::new(KernelError::Internal)
+                .attach_printable(format!("Invalid rate limit quota: {s}"))
+        };
+        let (capacity, period) = s.split_once('/').ok_or_else(invalid)?;
+        let capacity = capacity.trim().parse().map_err(|_| invalid())?;
+        let period = period.trim().parse().map_err(|_| invalid())?;
+        if capacity == 0 || period == 0 {
+            return Err(invalid());
+        }
+        Ok(Self::new(capacity, Duration::from_secs(period)))
+    }
+}
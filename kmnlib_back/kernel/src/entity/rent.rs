@@ -29,4 +29,11 @@ impl Rent {
             returned_at,
         }
     }
+
+    /// Version of the latest event of this rent, its return once returned
+    pub fn last_version(&self) -> &EventVersion<Rent> {
+        self.returned_at
+            .as_ref()
+            .map_or(&self.version, |(_, version)| version)
+    }
 }
@@ -0,0 +1,4 @@
+mod reset_token;
+mod token_id;
+
+pub use self::{reset_token::*, token_id::*};
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use vodca::{AsRefln, Fromln};
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct PasswordResetToken(Uuid);
+
+impl PasswordResetToken {
+    pub fn new(token: impl Into<Uuid>) -> Self {
+        Self(token.into())
+    }
+}
@@ -1,8 +1,10 @@
 mod id;
 mod name;
+mod password;
 mod rent_limit;
+mod role;
 
-pub use self::{id::*, name::*, rent_limit::*};
+pub use self::{id::*, name::*, password::*, rent_limit::*, role::*};
 use crate::entity::common::EventVersion;
 use crate::entity::IsDeleted;
 use destructure::{Destructure, Mutation};
@@ -13,6 +15,8 @@ pub struct User {
     id: UserId,
     name: UserName,
     rent_limit: UserRentLimit,
+    role: UserRole,
+    password: Option<UserPasswordHash>,
     version: EventVersion<User>,
     is_deleted: IsDeleted<User>,
 }
@@ -22,6 +26,8 @@ impl User {
         id: UserId,
         name: UserName,
         rent_limit: UserRentLimit,
+        role: UserRole,
+        password: Option<UserPasswordHash>,
         version: EventVersion<User>,
         is_deleted: IsDeleted<User>,
     ) -> Self {
@@ -29,6 +35,8 @@ impl User {
             id,
             name,
             rent_limit,
+            role,
+            password,
             version,
             is_deleted,
         }
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use vodca::{AsRefln, Fromln};
+
+/// PHC string of the hashed password
+#[derive(Debug, Clone, Eq, PartialEq, Fromln, AsRefln, Serialize, Deserialize)]
+pub struct UserPasswordHash(String);
+
+impl UserPasswordHash {
+    pub fn new(hash: impl Into<String>) -> Self {
+        Self(hash.into())
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+
+use crate::KernelError;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserRole {
+    #[default]
+    Member,
+    Librarian,
+    Admin,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Member => "member",
+            UserRole::Librarian => "librarian",
+            UserRole::Admin => "admin",
+        }
+    }
+}
+
+impl Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UserRole {
+    type Err = Report<KernelError>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "member" => Ok(UserRole::Member),
+            "librarian" => Ok(UserRole::Librarian),
+            "admin" => Ok(UserRole::Admin),
+            other => Err(Report::new(KernelError::Internal)
+                .attach_printable(format!("Unknown user role: {other}"))),
+        }
+    }
+}
@@ -7,6 +7,13 @@ pub enum KernelError {
     Concurrency,
     Timeout,
     Internal,
+    PermissionDenied(String),
+    /// The client based its request on a version that is no longer current
+    PreconditionFailed,
+    /// The current state does not allow the command, e.g. a book with no copies left
+    Ineligible,
+    /// The input is well formed but cannot be read, e.g. a queued payload that does not decode
+    Unprocessable,
 }
 
 impl Display for KernelError {
@@ -15,6 +22,10 @@ impl Display for KernelError {
             KernelError::Concurrency => write!(f, "Concurrency error"),
             KernelError::Timeout => write!(f, "Process timed out"),
             KernelError::Internal => write!(f, "Internal kernel error"),
+            KernelError::PermissionDenied(reason) => write!(f, "Permission denied: {reason}"),
+            KernelError::PreconditionFailed => write!(f, "Precondition failed"),
+            KernelError::Ineligible => write!(f, "Command not allowed in the current state"),
+            KernelError::Unprocessable => write!(f, "Input cannot be processed"),
         }
     }
 }
@@ -1,8 +1,12 @@
 use destructure::Destructure;
 use error_stack::Report;
+use serde::{Deserialize, Serialize};
 use vodca::References;
 
-use crate::entity::{CreatedAt, EventVersion, ExpectedEventVersion};
+use crate::entity::{
+    ActorId, CausationId, ClientIp, CorrelationId, CreatedAt, EventVersion, ExpectedEventVersion,
+    UserAgent,
+};
 use crate::KernelError;
 
 pub use self::{book::*, rent::*, user::*};
@@ -11,19 +15,53 @@ mod book;
 mod rent;
 mod user;
 
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, References, Destructure)]
+pub struct EventMetadata {
+    actor_id: Option<ActorId>,
+    correlation_id: Option<CorrelationId>,
+    causation_id: Option<CausationId>,
+    client_ip: Option<ClientIp>,
+    user_agent: Option<UserAgent>,
+}
+
+impl EventMetadata {
+    pub fn new(
+        actor_id: Option<ActorId>,
+        correlation_id: Option<CorrelationId>,
+        causation_id: Option<CausationId>,
+        client_ip: Option<ClientIp>,
+        user_agent: Option<UserAgent>,
+    ) -> Self {
+        Self {
+            actor_id,
+            correlation_id,
+            causation_id,
+            client_ip,
+            user_agent,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, References, Destructure)]
 pub struct EventInfo<Event, Entity> {
     event: Event,
     version: EventVersion<Entity>,
     created_at: CreatedAt<Entity>,
+    metadata: EventMetadata,
 }
 
 impl<Event, Entity> EventInfo<Event, Entity> {
-    pub fn new(event: Event, version: EventVersion<Entity>, created_at: CreatedAt<Entity>) -> Self {
+    pub fn new(
+        event: Event,
+        version: EventVersion<Entity>,
+        created_at: CreatedAt<Entity>,
+        metadata: EventMetadata,
+    ) -> Self {
         Self {
             event,
             version,
             created_at,
+            metadata,
         }
     }
 }
@@ -36,11 +74,20 @@ pub trait Applier<Event>: 'static + Sync + Send {
 pub struct CommandInfo<Event, Entity> {
     event: Event,
     version: Option<ExpectedEventVersion<Entity>>,
+    metadata: EventMetadata,
 }
 
 impl<Event: Clone, Entity: Clone> CommandInfo<Event, Entity> {
-    pub fn new(event: Event, version: Option<ExpectedEventVersion<Entity>>) -> Self {
-        Self { event, version }
+    pub fn new(
+        event: Event,
+        version: Option<ExpectedEventVersion<Entity>>,
+        metadata: EventMetadata,
+    ) -> Self {
+        Self {
+            event,
+            version,
+            metadata,
+        }
     }
 }
 
@@ -27,6 +27,16 @@ pub enum BookEvent {
     },
 }
 
+impl BookEvent {
+    pub fn id(&self) -> &BookId {
+        match self {
+            BookEvent::Create { id, .. }
+            | BookEvent::Update { id, .. }
+            | BookEvent::Delete { id } => id,
+        }
+    }
+}
+
 impl Applier<EventInfo<BookEvent, Book>> for Book {
     fn apply(&mut self, event: EventInfo<BookEvent, Book>) {
         let DestructEventInfo { event, version, .. } = event.into_destruct();
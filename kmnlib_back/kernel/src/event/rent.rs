@@ -1,14 +1,15 @@
 use destructure::Destructure;
 use error_stack::Report;
+use serde::{Deserialize, Serialize};
 
 use crate::entity::{BookId, UserId};
 use crate::event::EventRowFieldAttachments;
 use crate::KernelError;
 
-const BOOK_RENTED: &str = "book_rented";
+pub const BOOK_RENTED: &str = "book_rented";
 const BOOK_RETURNED: &str = "book_returned";
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum RentEvent {
     Rent { book_id: BookId, user_id: UserId },
     Return { book_id: BookId, user_id: UserId },
@@ -2,13 +2,16 @@ use destructure::Destructure;
 use error_stack::Report;
 use serde::{Deserialize, Serialize};
 
-use crate::entity::{IsDeleted, User, UserId, UserName, UserRentLimit};
+use crate::entity::{IsDeleted, User, UserId, UserName, UserPasswordHash, UserRentLimit, UserRole};
 use crate::event::{Applier, DestructEventInfo, EventInfo, EventRowFieldAttachments};
 use crate::KernelError;
 
 const USER_CREATED: &str = "user_created";
 const USER_UPDATED: &str = "user_updated";
 const USER_DELETED: &str = "user_deleted";
+const USER_ROLE_CHANGED: &str = "user_role_changed";
+const USER_PASSWORD_CHANGED: &str = "user_password_changed";
+const USER_PASSWORD_RESET: &str = "user_password_reset";
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum UserEvent {
@@ -25,6 +28,31 @@ pub enum UserEvent {
     Delete {
         id: UserId,
     },
+    ChangeRole {
+        id: UserId,
+        role: UserRole,
+    },
+    ChangePassword {
+        id: UserId,
+        password: UserPasswordHash,
+    },
+    ResetPassword {
+        id: UserId,
+        password: UserPasswordHash,
+    },
+}
+
+impl UserEvent {
+    pub fn id(&self) -> &UserId {
+        match self {
+            UserEvent::Create { id, .. }
+            | UserEvent::Update { id, .. }
+            | UserEvent::Delete { id }
+            | UserEvent::ChangeRole { id, .. }
+            | UserEvent::ChangePassword { id, .. }
+            | UserEvent::ResetPassword { id, .. } => id,
+        }
+    }
 }
 
 impl Applier<EventInfo<UserEvent, User>> for User {
@@ -46,6 +74,15 @@ impl Applier<EventInfo<UserEvent, User>> for User {
             UserEvent::Delete { .. } => {
                 self.substitute(|user| *user.is_deleted = IsDeleted::new(true))
             }
+            UserEvent::ChangeRole { role, .. } => self.substitute(|user| {
+                *user.role = role;
+                *user.version = version;
+            }),
+            UserEvent::ChangePassword { password, .. }
+            | UserEvent::ResetPassword { password, .. } => self.substitute(|user| {
+                *user.password = Some(password);
+                *user.version = version;
+            }),
         }
     }
 }
@@ -66,6 +103,8 @@ impl Applier<EventInfo<UserEvent, User>> for Option<User> {
                     id,
                     name,
                     rent_limit,
+                    UserRole::default(),
+                    None,
                     version,
                     IsDeleted::new(false),
                 ))
@@ -87,6 +126,18 @@ impl Applier<EventInfo<UserEvent, User>> for Option<User> {
             (Some(user), UserEvent::Delete { .. }) => {
                 user.substitute(|user| *user.is_deleted = IsDeleted::new(true))
             }
+            (Some(user), UserEvent::ChangeRole { role, .. }) => user.substitute(|user| {
+                *user.role = role;
+                *user.version = version;
+            }),
+            (
+                Some(user),
+                UserEvent::ChangePassword { password, .. }
+                | UserEvent::ResetPassword { password, .. },
+            ) => user.substitute(|user| {
+                *user.password = Some(password);
+                *user.version = version;
+            }),
             _ => {}
         }
     }
@@ -98,6 +149,8 @@ pub struct UserEventRow {
     id: UserId,
     name: Option<UserName>,
     rent_limit: Option<UserRentLimit>,
+    role: Option<UserRole>,
+    password: Option<UserPasswordHash>,
 }
 
 impl UserEventRow {
@@ -106,12 +159,16 @@ impl UserEventRow {
         id: UserId,
         name: Option<UserName>,
         rent_limit: Option<UserRentLimit>,
+        role: Option<UserRole>,
+        password: Option<UserPasswordHash>,
     ) -> Self {
         Self {
             event_name,
             id,
             name,
             rent_limit,
+            role,
+            password,
         }
     }
 }
@@ -123,13 +180,46 @@ impl From<UserEvent> for UserEventRow {
                 id,
                 name,
                 rent_limit,
-            } => Self::new(String::from(USER_CREATED), id, Some(name), Some(rent_limit)),
+            } => Self::new(
+                String::from(USER_CREATED),
+                id,
+                Some(name),
+                Some(rent_limit),
+                None,
+                None,
+            ),
             UserEvent::Update {
                 id,
                 name,
                 rent_limit,
-            } => Self::new(String::from(USER_UPDATED), id, name, rent_limit),
-            UserEvent::Delete { id } => Self::new(String::from(USER_DELETED), id, None, None),
+            } => Self::new(String::from(USER_UPDATED), id, name, rent_limit, None, None),
+            UserEvent::Delete { id } => {
+                Self::new(String::from(USER_DELETED), id, None, None, None, None)
+            }
+            UserEvent::ChangeRole { id, role } => Self::new(
+                String::from(USER_ROLE_CHANGED),
+                id,
+                None,
+                None,
+                Some(role),
+                None,
+            ),
+            UserEvent::ChangePassword { id, password } => Self::new(
+                String::from(USER_PASSWORD_CHANGED),
+                id,
+                None,
+                None,
+                None,
+                Some(password),
+            ),
+            UserEvent::ResetPassword { id, password } => Self::new(
+                String::from(USER_PASSWORD_RESET),
+                id,
+                None,
+                None,
+                None,
+                Some(password),
+            ),
         }
     }
 }
@@ -165,6 +255,30 @@ impl TryFrom<UserEventRow> for UserEvent {
                 })
             }
             USER_DELETED => Ok(Self::Delete { id: value.id }),
+            USER_ROLE_CHANGED => {
+                let role = value.role.ok_or_else(|| {
+                    Report::new(KernelError::Internal).attach_field_details(&event_name, "role")
+                })?;
+                Ok(Self::ChangeRole { id: value.id, role })
+            }
+            USER_PASSWORD_CHANGED => {
+                let password = value.password.ok_or_else(|| {
+                    Report::new(KernelError::Internal).attach_field_details(&event_name, "password")
+                })?;
+                Ok(Self::ChangePassword {
+                    id: value.id,
+                    password,
+                })
+            }
+            USER_PASSWORD_RESET => {
+                let password = value.password.ok_or_else(|| {
+                    Report::new(KernelError::Internal).attach_field_details(&event_name, "password")
+                })?;
+                Ok(Self::ResetPassword {
+                    id: value.id,
+                    password,
+                })
+            }
             _ => Err(Report::new(KernelError::Internal).attach_unknown_event("user", &event_name)),
         }
     }
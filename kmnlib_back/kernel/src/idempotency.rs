@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
+use crate::entity::{IdempotencyKey, IdempotencyRecord};
+use crate::KernelError;
+
+#[async_trait::async_trait]
+pub trait IdempotencyStore: Sync + Send + 'static {
+    type Transaction: Transaction;
+    /// Stores `record` unless the key is already taken, in which case the existing record is returned
+    async fn reserve(
+        &self,
+        con: &mut Self::Transaction,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+        ttl: &Duration,
+    ) -> error_stack::Result<Option<IdempotencyRecord>, KernelError>;
+
+    async fn complete(
+        &self,
+        con: &mut Self::Transaction,
+        key: &IdempotencyKey,
+        record: &IdempotencyRecord,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError>;
+
+    /// Frees the key so the request can be retried
+    async fn release(
+        &self,
+        con: &mut Self::Transaction,
+        key: &IdempotencyKey,
+    ) -> error_stack::Result<(), KernelError>;
+}
+
+pub trait DependOnIdempotencyStore: Sync + Send + 'static + DependOnDatabaseConnection {
+    type IdempotencyStore: IdempotencyStore<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn idempotency_store(&self) -> &Self::IdempotencyStore;
+}
@@ -5,9 +5,12 @@ mod entity;
 mod error;
 mod event;
 mod handler;
+mod idempotency;
 mod modify;
 mod mq;
 mod query;
+mod rate_limit;
+mod session;
 
 #[cfg(feature = "prelude")]
 pub mod prelude {
@@ -34,4 +37,13 @@ pub mod interface {
     pub mod mq {
         pub use crate::mq::*;
     }
+    pub mod session {
+        pub use crate::session::*;
+    }
+    pub mod idempotency {
+        pub use crate::idempotency::*;
+    }
+    pub mod rate_limit {
+        pub use crate::rate_limit::*;
+    }
 }
@@ -1,5 +1,6 @@
+mod api_key;
 mod book;
 mod rent;
 mod user;
 
-pub use self::{book::*, rent::*, user::*};
+pub use self::{api_key::*, book::*, rent::*, user::*};
@@ -0,0 +1,25 @@
+use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
+use crate::entity::ApiKey;
+use crate::KernelError;
+
+#[async_trait::async_trait]
+pub trait ApiKeyModifier: 'static + Sync + Send {
+    type Transaction: Transaction;
+    async fn create(
+        &self,
+        con: &mut Self::Transaction,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError>;
+    async fn update(
+        &self,
+        con: &mut Self::Transaction,
+        api_key: &ApiKey,
+    ) -> error_stack::Result<(), KernelError>;
+}
+
+pub trait DependOnApiKeyModifier: 'static + Sync + Send + DependOnDatabaseConnection {
+    type ApiKeyModifier: ApiKeyModifier<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn api_key_modifier(&self) -> &Self::ApiKeyModifier;
+}
@@ -1,16 +1,32 @@
+mod classify;
 mod config;
+mod filter;
 mod handler;
 mod info;
+mod layer;
+mod payload;
+mod priority;
+mod quarantine;
+mod retry;
+mod schedule;
+mod status;
+mod worker;
 
 use crate::database::DatabaseConnection;
-pub use crate::mq::{config::*, handler::*, info::*};
+pub use crate::mq::{
+    classify::*, config::*, filter::*, handler::*, info::*, layer::*, payload::*, priority::*,
+    quarantine::*, retry::*, schedule::*, status::*, worker::*,
+};
 use crate::KernelError;
 use error_stack::Context;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ErrorOperation {
     Delay,
     Failed,
@@ -31,7 +47,7 @@ impl Context for ErrorOperation {}
 pub trait MessageQueue<M, T>: 'static + Sync + Send
 where
     M: 'static + Clone + Sync + Send,
-    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Sync + Send,
+    T: 'static + Clone + Serialize + for<'de> Deserialize<'de> + Payload + Sync + Send,
 {
     type DatabaseConnection: DatabaseConnection;
 
@@ -47,12 +63,56 @@ where
 
     fn start_workers(&self);
 
-    async fn queue(&self, info: &QueueInfo<T>) -> error_stack::Result<(), KernelError>;
+    fn get_worker_states(&self) -> Vec<WorkerState>;
 
+    /// Returns the id the job can be followed up with through [`MessageQueue::get_job_status`].
+    /// A job whose uniqueness key is held by a pending one is dropped, and the id of that one returned
+    async fn queue(&self, info: &QueueInfo<T>) -> error_stack::Result<Uuid, KernelError>;
+
+    /// Keeps the job aside until `when`, jobs due already are queued right away
+    async fn queue_at(
+        &self,
+        info: &QueueInfo<T>,
+        when: OffsetDateTime,
+    ) -> error_stack::Result<Uuid, KernelError>;
+
+    async fn queue_after(
+        &self,
+        info: &QueueInfo<T>,
+        delay: Duration,
+    ) -> error_stack::Result<Uuid, KernelError> {
+        self.queue_at(info, OffsetDateTime::now_utc() + delay).await
+    }
+
+    /// Fired by the scheduler task of [`MessageQueue::start_workers`]
+    fn register_schedule(&self, schedule: Schedule<T>);
+
+    async fn get_schedules(&self) -> error_stack::Result<Vec<ScheduleState>, KernelError>;
+
+    /// Queues the job of a schedule right away without moving its next run,
+    /// returns `None` if no schedule has that name
+    async fn trigger_schedule(&self, name: &str) -> error_stack::Result<Option<Uuid>, KernelError>;
+
+    async fn get_job_status(
+        &self,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<JobStatus>, KernelError>;
+
+    /// Jobs waiting in every lane
     async fn get_queued_len(&self) -> error_stack::Result<usize, KernelError>;
 
+    async fn get_queued_len_by_priority(
+        &self,
+        priority: &Priority,
+    ) -> error_stack::Result<usize, KernelError>;
+
+    async fn get_scheduled_len(&self) -> error_stack::Result<usize, KernelError>;
+
+    /// `offset` counts the jobs left after filtering and sorting
     async fn get_delayed_infos(
         &self,
+        filter: &ErroredInfoFilter,
+        sort: &Option<InfoSort>,
         size: &i64,
         offset: &i64,
     ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError>;
@@ -64,8 +124,11 @@ where
 
     async fn get_delayed_len(&self) -> error_stack::Result<usize, KernelError>;
 
+    /// `offset` counts the jobs left after filtering and sorting
     async fn get_failed_infos(
         &self,
+        filter: &ErroredInfoFilter,
+        sort: &Option<InfoSort>,
         size: &i64,
         offset: &i64,
     ) -> error_stack::Result<Vec<ErroredInfo<T>>, KernelError>;
@@ -76,4 +139,54 @@ where
     ) -> error_stack::Result<Option<ErroredInfo<T>>, KernelError>;
 
     async fn get_failed_len(&self) -> error_stack::Result<usize, KernelError>;
+
+    /// Makes a delayed job due now, returns `false` if it is no longer delayed
+    async fn retry_delayed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError>;
+
+    /// Queues a failed job again under the same id, returns `false` if it is not failed
+    async fn retry_failed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError>;
+
+    async fn retry_delayed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError>;
+
+    async fn retry_failed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError>;
+
+    /// Drops a delayed job for good, returns `false` if it is no longer delayed
+    async fn discard_delayed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError>;
+
+    async fn discard_failed(&self, id: &Uuid) -> error_stack::Result<bool, KernelError>;
+
+    async fn purge_delayed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError>;
+
+    async fn purge_failed_infos(
+        &self,
+        filter: &ErroredInfoFilter,
+    ) -> error_stack::Result<usize, KernelError>;
+
+    async fn get_quarantined_infos(
+        &self,
+        size: &i64,
+        offset: &i64,
+    ) -> error_stack::Result<Vec<QuarantinedInfo>, KernelError>;
+
+    async fn get_quarantined_info(
+        &self,
+        id: &Uuid,
+    ) -> error_stack::Result<Option<QuarantinedInfo>, KernelError>;
+
+    async fn get_quarantined_len(&self) -> error_stack::Result<usize, KernelError>;
+
+    /// Decodes the payload again and queues the job, e.g. after a fix was deployed.
+    /// Returns `None` if nothing is quarantined under the id, fails if it still does not decode
+    async fn retry_quarantined(&self, id: &Uuid) -> error_stack::Result<Option<Uuid>, KernelError>;
+
+    async fn discard_quarantined(&self, id: &Uuid) -> error_stack::Result<bool, KernelError>;
 }
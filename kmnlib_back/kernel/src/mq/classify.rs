@@ -0,0 +1,118 @@
+use crate::mq::ErrorOperation;
+use crate::KernelError;
+use error_stack::Report;
+use serde::{Deserialize, Serialize};
+
+/// What made a job fail, kept with delayed and failed jobs
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The command is not allowed in the current state
+    Validation,
+    /// The aggregate moved on while the command was handled
+    Conflict,
+    Permission,
+    Timeout,
+    Internal,
+}
+
+impl From<&KernelError> for ErrorKind {
+    fn from(value: &KernelError) -> Self {
+        match value {
+            KernelError::Ineligible | KernelError::Unprocessable => ErrorKind::Validation,
+            KernelError::Concurrency | KernelError::PreconditionFailed => ErrorKind::Conflict,
+            KernelError::PermissionDenied(_) => ErrorKind::Permission,
+            KernelError::Timeout => ErrorKind::Timeout,
+            KernelError::Internal => ErrorKind::Internal,
+        }
+    }
+}
+
+/// Decides which kinds of error are worth another attempt.
+/// Only timeouts and internal errors are by default
+#[derive(Debug, Clone)]
+pub struct ErrorPolicy {
+    transient: Vec<ErrorKind>,
+}
+
+impl Default for ErrorPolicy {
+    fn default() -> Self {
+        Self {
+            transient: vec![ErrorKind::Timeout, ErrorKind::Internal],
+        }
+    }
+}
+
+impl ErrorPolicy {
+    pub fn with_transient(mut self, kind: ErrorKind) -> Self {
+        if !self.transient.contains(&kind) {
+            self.transient.push(kind);
+        }
+        self
+    }
+
+    pub fn is_transient(&self, kind: &ErrorKind) -> bool {
+        self.transient.contains(kind)
+    }
+
+    /// The kind is attached to the report, the queue records it with the job
+    pub fn classify(&self, report: Report<KernelError>) -> Report<ErrorOperation> {
+        let kind = ErrorKind::from(report.current_context());
+        let operation = if self.is_transient(&kind) {
+            ErrorOperation::Delay
+        } else {
+            ErrorOperation::Failed
+        };
+        report.attach(kind).change_context(operation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mq::{ErrorKind, ErrorOperation, ErrorPolicy};
+    use crate::KernelError;
+    use error_stack::Report;
+
+    fn classify(policy: &ErrorPolicy, error: KernelError) -> (ErrorOperation, Option<ErrorKind>) {
+        let report = policy.classify(Report::new(error));
+        (
+            *report.current_context(),
+            report.downcast_ref::<ErrorKind>().copied(),
+        )
+    }
+
+    #[test]
+    fn test_classify() {
+        let policy = ErrorPolicy::default();
+        assert_eq!(
+            classify(&policy, KernelError::Timeout),
+            (ErrorOperation::Delay, Some(ErrorKind::Timeout))
+        );
+        assert_eq!(
+            classify(&policy, KernelError::Internal),
+            (ErrorOperation::Delay, Some(ErrorKind::Internal))
+        );
+        assert_eq!(
+            classify(&policy, KernelError::Ineligible),
+            (ErrorOperation::Failed, Some(ErrorKind::Validation))
+        );
+        assert_eq!(
+            classify(&policy, KernelError::Unprocessable),
+            (ErrorOperation::Failed, Some(ErrorKind::Validation))
+        );
+        assert_eq!(
+            classify(&policy, KernelError::Concurrency),
+            (ErrorOperation::Failed, Some(ErrorKind::Conflict))
+        );
+
+        let policy = policy.with_transient(ErrorKind::Conflict);
+        assert_eq!(
+            classify(&policy, KernelError::PreconditionFailed),
+            (ErrorOperation::Delay, Some(ErrorKind::Conflict))
+        );
+        assert_eq!(
+            classify(&policy, KernelError::Ineligible),
+            (ErrorOperation::Failed, Some(ErrorKind::Validation))
+        );
+    }
+}
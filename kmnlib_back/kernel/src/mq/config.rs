@@ -1,3 +1,4 @@
+use crate::mq::RetryPolicy;
 use destructure::Mutation;
 use std::time::Duration;
 use vodca::References;
@@ -5,16 +6,29 @@ use vodca::References;
 #[derive(Debug, Clone, References, Mutation)]
 pub struct MQConfig {
     worker_count: i32,
-    max_retry: i32,
-    retry_delay: Duration,
+    /// Used for jobs that do not bring their own
+    retry_policy: RetryPolicy,
+    /// Pending jobs idle this long were left by a crashed worker and are claimed again
+    reclaim_idle: Duration,
+    stuck_timeout: Duration,
+    /// How long the status of a job stays readable after its last transition
+    status_ttl: Duration,
+    /// How often due scheduled jobs are moved into the queue
+    schedule_interval: Duration,
+    /// Longest a uniqueness key is held, in case its job never finishes
+    unique_window: Duration,
 }
 
 impl Default for MQConfig {
     fn default() -> Self {
         Self {
             worker_count: 4,
-            max_retry: 3,
-            retry_delay: Duration::from_secs(180),
+            retry_policy: RetryPolicy::default(),
+            reclaim_idle: Duration::from_secs(180),
+            stuck_timeout: Duration::from_secs(60),
+            status_ttl: Duration::from_secs(60 * 60 * 24),
+            schedule_interval: Duration::from_secs(1),
+            unique_window: Duration::from_secs(60 * 5),
         }
     }
 }
@@ -0,0 +1,185 @@
+use crate::mq::{ErrorKind, ErroredInfo};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use time::OffsetDateTime;
+use vodca::References;
+
+/// Narrows listing and bulk operations over delayed or failed jobs, an empty filter matches all of them
+#[derive(Debug, Clone, Default, References)]
+pub struct ErroredInfoFilter {
+    /// Text the stack trace has to contain
+    error: Option<String>,
+    /// Text the serialized job data has to contain
+    data: Option<String>,
+    kind: Option<ErrorKind>,
+    /// Inclusive bounds on [`ErroredInfo::errored_at`],
+    /// jobs recorded without history are left out once one is set
+    from: Option<OffsetDateTime>,
+    to: Option<OffsetDateTime>,
+}
+
+impl ErroredInfoFilter {
+    pub fn new(error: Option<String>, data: Option<String>) -> Self {
+        Self {
+            error,
+            data,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_kind(self, kind: Option<ErrorKind>) -> Self {
+        Self { kind, ..self }
+    }
+
+    pub fn with_window(self, from: Option<OffsetDateTime>, to: Option<OffsetDateTime>) -> Self {
+        Self { from, to, ..self }
+    }
+
+    /// Checks everything but `data`, which needs the job serialized
+    pub fn matches_info<T>(&self, info: &ErroredInfo<T>) -> bool {
+        if let Some(error) = &self.error {
+            if !info.stack_trace().contains(error.as_str()) {
+                return false;
+            }
+        }
+        if self.kind.is_some() && info.kind() != &self.kind {
+            return false;
+        }
+        if self.from.is_none() && self.to.is_none() {
+            return true;
+        }
+        let Some(errored_at) = info.errored_at() else {
+            return false;
+        };
+        self.from.is_none_or(|from| from <= errored_at) && self.to.is_none_or(|to| errored_at <= to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfoSortKey {
+    QueuedAt,
+    ErroredAt,
+    Attempts,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Order of listed delayed or failed jobs, jobs recorded without history come first when ascending
+#[derive(Debug, Clone, Copy, References)]
+pub struct InfoSort {
+    key: InfoSortKey,
+    order: SortOrder,
+}
+
+impl InfoSort {
+    pub fn new(key: InfoSortKey, order: SortOrder) -> Self {
+        Self { key, order }
+    }
+
+    pub fn compare<T>(&self, a: &ErroredInfo<T>, b: &ErroredInfo<T>) -> Ordering {
+        let ordering = match self.key {
+            InfoSortKey::QueuedAt => a.queued_at().cmp(b.queued_at()),
+            InfoSortKey::ErroredAt => a.errored_at().cmp(&b.errored_at()),
+            InfoSortKey::Attempts => a.attempts().len().cmp(&b.attempts().len()),
+        };
+        match self.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mq::{
+        AttemptRecord, ErrorKind, ErrorOperation, ErroredInfo, ErroredInfoFilter, InfoSort,
+        InfoSortKey, SortOrder,
+    };
+    use std::cmp::Ordering;
+    use time::{Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    fn info(
+        stack_trace: &str,
+        kind: Option<ErrorKind>,
+        queued_at: Option<OffsetDateTime>,
+        attempts: usize,
+    ) -> ErroredInfo<()> {
+        let attempts = (1..=attempts)
+            .map(|attempt| {
+                AttemptRecord::new(
+                    attempt as i64,
+                    "worker".to_string(),
+                    OffsetDateTime::now_utc(),
+                    ErrorOperation::Delay,
+                    kind,
+                    stack_trace.to_string(),
+                )
+            })
+            .collect();
+        ErroredInfo::new(Uuid::new_v4(), (), stack_trace.to_string(), kind)
+            .with_history(queued_at, attempts)
+    }
+
+    #[test]
+    fn test_matches_info() {
+        let timeout = info("Job timed out", Some(ErrorKind::Timeout), None, 1);
+        let bare = info("Book not found", None, None, 0);
+
+        let all = ErroredInfoFilter::default();
+        assert!(all.matches_info(&timeout));
+        assert!(all.matches_info(&bare));
+
+        let error = ErroredInfoFilter::new(Some("timed out".to_string()), None);
+        assert!(error.matches_info(&timeout));
+        assert!(!error.matches_info(&bare));
+
+        let kind = ErroredInfoFilter::default().with_kind(Some(ErrorKind::Timeout));
+        assert!(kind.matches_info(&timeout));
+        assert!(!kind.matches_info(&bare));
+        let kind = ErroredInfoFilter::default().with_kind(Some(ErrorKind::Internal));
+        assert!(!kind.matches_info(&timeout));
+
+        let now = OffsetDateTime::now_utc();
+        let recent = ErroredInfoFilter::default().with_window(Some(now - Duration::hours(1)), None);
+        assert!(recent.matches_info(&timeout));
+        assert!(!recent.matches_info(&bare));
+        let old = ErroredInfoFilter::default().with_window(None, Some(now - Duration::hours(1)));
+        assert!(!old.matches_info(&timeout));
+        assert!(!old.matches_info(&bare));
+    }
+
+    #[test]
+    fn test_compare() {
+        let now = OffsetDateTime::now_utc();
+        let first = info("first", None, Some(now - Duration::hours(2)), 2);
+        let second = info("second", None, Some(now - Duration::hours(1)), 1);
+        let bare = info("bare", None, None, 0);
+
+        let queued = InfoSort::new(InfoSortKey::QueuedAt, SortOrder::Asc);
+        assert_eq!(queued.compare(&first, &second), Ordering::Less);
+        assert_eq!(queued.compare(&bare, &first), Ordering::Less);
+        let queued = InfoSort::new(InfoSortKey::QueuedAt, SortOrder::Desc);
+        assert_eq!(queued.compare(&first, &second), Ordering::Greater);
+
+        let attempts = InfoSort::new(InfoSortKey::Attempts, SortOrder::Desc);
+        assert_eq!(attempts.compare(&first, &second), Ordering::Less);
+        assert_eq!(attempts.compare(&bare, &second), Ordering::Greater);
+
+        let errored = InfoSort::new(InfoSortKey::ErroredAt, SortOrder::Asc);
+        assert_eq!(errored.compare(&bare, &first), Ordering::Less);
+        assert_eq!(errored.compare(&bare, &bare), Ordering::Equal);
+
+        let mut infos = [second, bare, first];
+        infos.sort_by(|a, b| queued.compare(a, b));
+        let order = infos.iter().map(|info| info.stack_trace().as_str());
+        assert_eq!(order.collect::<Vec<_>>(), ["second", "first", "bare"]);
+    }
+}
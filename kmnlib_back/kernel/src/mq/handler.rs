@@ -5,7 +5,7 @@ use std::pin::Pin;
 // These trait and struct is based on the following URL
 
 pub type HandlerResult =
-    Pin<Box<dyn Future<Output = error_stack::Result<(), ErrorOperation>> + Send>>;
+    Pin<Box<dyn Future<Output = error_stack::Result<Option<String>, ErrorOperation>> + Send>>;
 
 // https://github.com/tokio-rs/axum/blob/b6b203b3065e4005bda01efac8429176da055ae2/axum/src/handler/mod.rs#L134-139
 pub trait Handler<M, T>: 'static + Clone + Send {
@@ -16,7 +16,7 @@ pub trait Handler<M, T>: 'static + Clone + Send {
 impl<Fn, Res, M, T> Handler<M, T> for Fn
 where
     Fn: 'static + Clone + Send + FnOnce(M, T) -> Res,
-    Res: Future<Output = error_stack::Result<(), ErrorOperation>> + Send,
+    Res: Future<Output = error_stack::Result<Option<String>, ErrorOperation>> + Send,
     M: 'static + Send,
     T: 'static + Send,
 {
@@ -1,42 +1,227 @@
+use crate::mq::{ErrorKind, ErrorOperation, Payload, PayloadSchema, Priority, RetryPolicy};
 use destructure::Destructure;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::OffsetDateTime;
 use uuid::Uuid;
 use vodca::References;
 
-#[derive(Debug, Serialize, Deserialize, Destructure)]
+#[derive(Debug, Clone, Serialize, Deserialize, References, Destructure)]
 pub struct QueueInfo<T> {
     id: Uuid,
     data: T,
+    /// Type and version `data` was written with, see [`QueueInfo::decode`]
+    #[serde(default)]
+    schema: Option<PayloadSchema>,
+    /// Propagation headers of the span that queued this job
+    #[serde(default)]
+    trace_context: HashMap<String, String>,
+    /// Attempts made before this delivery
+    #[serde(default)]
+    attempts: i64,
+    /// Overrides the retry policy of the queue
+    #[serde(default)]
+    retry_policy: Option<RetryPolicy>,
+    #[serde(default)]
+    priority: Priority,
+    /// Jobs sharing a key are collapsed into the one pending
+    #[serde(default)]
+    unique_key: Option<String>,
+    /// Jobs sharing a key run one at a time, in the order they were queued
+    #[serde(default)]
+    partition_key: Option<String>,
+    /// User who queued the job, recorded with its status
+    #[serde(default)]
+    submitter: Option<Uuid>,
 }
 
-impl<T> QueueInfo<T> {
+impl<T: Payload> QueueInfo<T> {
     pub fn new(id: Uuid, data: T) -> Self {
-        Self { id, data }
+        Self {
+            id,
+            data,
+            schema: Some(T::schema()),
+            trace_context: HashMap::new(),
+            attempts: 0,
+            retry_policy: None,
+            priority: Priority::default(),
+            unique_key: None,
+            partition_key: None,
+            submitter: None,
+        }
     }
 }
 
-impl<T> From<T> for QueueInfo<T> {
-    fn from(value: T) -> Self {
+impl<T> QueueInfo<T> {
+    pub fn with_trace_context(self, trace_context: HashMap<String, String>) -> Self {
+        Self {
+            trace_context,
+            ..self
+        }
+    }
+
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy: Some(retry_policy),
+            ..self
+        }
+    }
+
+    pub fn with_priority(self, priority: Priority) -> Self {
+        Self { priority, ..self }
+    }
+
+    pub fn with_unique_key(self, unique_key: impl Into<String>) -> Self {
+        Self {
+            unique_key: Some(unique_key.into()),
+            ..self
+        }
+    }
+
+    pub fn with_partition_key(self, partition_key: impl Into<String>) -> Self {
+        Self {
+            partition_key: Some(partition_key.into()),
+            ..self
+        }
+    }
+
+    pub fn with_submitter(self, submitter: Uuid) -> Self {
         Self {
-            id: Uuid::new_v4(),
-            data: value,
+            submitter: Some(submitter),
+            ..self
         }
     }
 }
 
+impl<T: Payload> From<T> for QueueInfo<T> {
+    fn from(value: T) -> Self {
+        Self::new(Uuid::new_v4(), value)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, References, Destructure)]
 pub struct ErroredInfo<T> {
     id: Uuid,
     data: T,
+    /// Type and version `data` was written with, see [`ErroredInfo::decode`]
+    #[serde(default)]
+    schema: Option<PayloadSchema>,
     stack_trace: String,
+    /// Recorded when the handler classified the error with an `ErrorPolicy`
+    #[serde(default)]
+    kind: Option<ErrorKind>,
+    /// When the job was first queued
+    #[serde(default)]
+    queued_at: Option<OffsetDateTime>,
+    /// Attempts that returned an error, oldest first
+    #[serde(default)]
+    attempts: Vec<AttemptRecord>,
+    /// The rest of the envelope the job was queued with, so a retry queues it the same way
+    #[serde(default)]
+    retry_policy: Option<RetryPolicy>,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    unique_key: Option<String>,
+    #[serde(default)]
+    partition_key: Option<String>,
+    #[serde(default)]
+    submitter: Option<Uuid>,
 }
 
 impl<T> ErroredInfo<T> {
-    pub fn new(id: Uuid, data: T, stack_trace: String) -> Self {
+    pub fn new(id: Uuid, data: T, stack_trace: String, kind: Option<ErrorKind>) -> Self {
         Self {
             id,
             data,
+            schema: None,
             stack_trace,
+            kind,
+            queued_at: None,
+            attempts: Vec::new(),
+            retry_policy: None,
+            priority: Priority::default(),
+            unique_key: None,
+            partition_key: None,
+            submitter: None,
+        }
+    }
+
+    /// Keeps the envelope of `info`, the job this one was recorded for
+    pub fn with_envelope<U>(self, info: &QueueInfo<U>) -> Self {
+        Self {
+            schema: info.schema.clone(),
+            retry_policy: info.retry_policy.clone(),
+            priority: info.priority,
+            unique_key: info.unique_key.clone(),
+            partition_key: info.partition_key.clone(),
+            submitter: info.submitter,
+            ..self
+        }
+    }
+
+    pub fn with_history(
+        self,
+        queued_at: Option<OffsetDateTime>,
+        attempts: Vec<AttemptRecord>,
+    ) -> Self {
+        Self {
+            queued_at,
+            attempts,
+            ..self
+        }
+    }
+
+    /// End of the last recorded attempt, `None` for jobs recorded without history
+    pub fn errored_at(&self) -> Option<OffsetDateTime> {
+        self.attempts.last().map(|attempt| attempt.finished_at)
+    }
+}
+
+impl<T: Payload + Clone> ErroredInfo<T> {
+    /// The job to queue again, with the envelope it was recorded with and no attempts made
+    pub fn to_queue_info(&self) -> QueueInfo<T> {
+        QueueInfo {
+            retry_policy: self.retry_policy.clone(),
+            priority: self.priority,
+            unique_key: self.unique_key.clone(),
+            partition_key: self.partition_key.clone(),
+            submitter: self.submitter,
+            ..QueueInfo::new(self.id, self.data.clone())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, References, Destructure)]
+pub struct AttemptRecord {
+    attempt: i64,
+    /// Consumer name of the worker that ran it
+    worker: String,
+    started_at: OffsetDateTime,
+    finished_at: OffsetDateTime,
+    operation: ErrorOperation,
+    kind: Option<ErrorKind>,
+    /// Rendered report the handler returned
+    error: String,
+}
+
+impl AttemptRecord {
+    pub fn new(
+        attempt: i64,
+        worker: String,
+        started_at: OffsetDateTime,
+        operation: ErrorOperation,
+        kind: Option<ErrorKind>,
+        error: String,
+    ) -> Self {
+        Self {
+            attempt,
+            worker,
+            started_at,
+            finished_at: OffsetDateTime::now_utc(),
+            operation,
+            kind,
+            error,
         }
     }
 }
@@ -0,0 +1,87 @@
+use crate::mq::{ErrorOperation, HandlerConverter, HandlerResult};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use uuid::Uuid;
+use vodca::References;
+
+/// The job a layer is called for
+#[derive(Debug, Clone, References)]
+pub struct JobContext {
+    queue: String,
+    id: Uuid,
+    attempt: i64,
+    /// Consumer name of the worker running it
+    worker: String,
+    /// Propagation headers of the span that queued it
+    trace_context: HashMap<String, String>,
+}
+
+impl JobContext {
+    pub fn new(
+        queue: String,
+        id: Uuid,
+        attempt: i64,
+        worker: String,
+        trace_context: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            queue,
+            id,
+            attempt,
+            worker,
+            trace_context,
+        }
+    }
+}
+
+/// Runs around the handler of a queue for every job, like a `tower::Layer`.
+/// A layer calls [`Next::run`] to go on down the stack, or answers the job itself
+pub trait Layer<M, T>: 'static + Send + Sync {
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult;
+}
+
+impl<Fn, Res, M, T> Layer<M, T> for Fn
+where
+    Fn: 'static + Send + Sync + core::ops::Fn(JobContext, M, T, Next<M, T>) -> Res,
+    Res: Future<Output = error_stack::Result<Option<String>, ErrorOperation>> + Send + 'static,
+{
+    fn call(&self, job: JobContext, module: M, data: T, next: Next<M, T>) -> HandlerResult {
+        Box::pin(self(job, module, data, next))
+    }
+}
+
+/// Layers of a queue, the first one attached is the outermost
+pub type Layers<M, T> = Arc<[Arc<dyn Layer<M, T>>]>;
+
+/// The rest of the stack below a layer, ending with the handler
+pub struct Next<M, T> {
+    layers: Layers<M, T>,
+    index: usize,
+    handler: Box<dyn HandlerConverter<M, T>>,
+}
+
+impl<M: 'static, T: 'static> Next<M, T> {
+    pub fn new(layers: Layers<M, T>, handler: Box<dyn HandlerConverter<M, T>>) -> Self {
+        Self {
+            layers,
+            index: 0,
+            handler,
+        }
+    }
+
+    pub fn run(self, job: JobContext, module: M, data: T) -> HandlerResult {
+        match self.layers.get(self.index).cloned() {
+            Some(layer) => layer.call(
+                job,
+                module,
+                data,
+                Self {
+                    index: self.index + 1,
+                    ..self
+                },
+            ),
+            None => self.handler.convert(module, data),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use crate::mq::{DestructErroredInfo, DestructQueueInfo, ErroredInfo, QueueInfo};
+use error_stack::Context;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use vodca::References;
+
+/// Job data with a name and a version, written next to it in the queue.
+/// Bump `VERSION` whenever the serialized shape changes and register a migration from the previous one
+pub trait Payload {
+    const TYPE_NAME: &'static str;
+    const VERSION: u32;
+
+    fn migrations() -> Migrations {
+        Migrations::default()
+    }
+
+    fn schema() -> PayloadSchema {
+        PayloadSchema::new(Self::TYPE_NAME, Self::VERSION)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, References)]
+pub struct PayloadSchema {
+    type_name: String,
+    version: u32,
+}
+
+impl PayloadSchema {
+    pub fn new(type_name: impl Into<String>, version: u32) -> Self {
+        Self {
+            type_name: type_name.into(),
+            version,
+        }
+    }
+}
+
+/// Upgrades serialized data by one version
+pub type Migration = fn(Value) -> serde_json::Result<Value>;
+
+#[derive(Debug, Clone, Default)]
+pub struct Migrations {
+    steps: BTreeMap<u32, Migration>,
+}
+
+impl Migrations {
+    /// `migration` upgrades data written at version `from` to `from + 1`
+    pub fn register(mut self, from: u32, migration: Migration) -> Self {
+        self.steps.insert(from, migration);
+        self
+    }
+
+    pub fn migrate(&self, mut data: Value, from: u32, to: u32) -> Result<Value, DecodeError> {
+        for version in from..to {
+            let migration = self
+                .steps
+                .get(&version)
+                .ok_or(DecodeError::MissingMigration { from: version })?;
+            data = migration(data).map_err(|error| DecodeError::Migration {
+                from: version,
+                error,
+            })?;
+        }
+        Ok(data)
+    }
+}
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Malformed(serde_json::Error),
+    UnexpectedType {
+        expected: String,
+        found: String,
+    },
+    /// Written by a newer build, one of its workers has to take it
+    Newer {
+        version: u32,
+        current: u32,
+    },
+    MissingMigration {
+        from: u32,
+    },
+    Migration {
+        from: u32,
+        error: serde_json::Error,
+    },
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Malformed(error) => write!(f, "{error}"),
+            DecodeError::UnexpectedType { expected, found } => {
+                write!(f, "Expected a {expected} payload, found {found}")
+            }
+            DecodeError::Newer { version, current } => {
+                write!(f, "Payload version {version} is newer than {current}")
+            }
+            DecodeError::MissingMigration { from } => {
+                write!(f, "No migration from payload version {from}")
+            }
+            DecodeError::Migration { from, error } => {
+                write!(f, "Migration from payload version {from} failed: {error}")
+            }
+        }
+    }
+}
+
+impl Context for DecodeError {}
+
+impl<T: Payload + DeserializeOwned> QueueInfo<T> {
+    /// Reads a serialized job, upgrading data written by an older version first.
+    /// Jobs queued before payloads carried a schema are taken as version 1
+    pub fn decode(raw: &[u8]) -> Result<Self, DecodeError> {
+        let info: QueueInfo<Value> = serde_json::from_slice(raw).map_err(DecodeError::Malformed)?;
+        let version = written_version::<T>(info.schema())?;
+        let DestructQueueInfo {
+            id,
+            data,
+            trace_context,
+            attempts,
+            retry_policy,
+            priority,
+            unique_key,
+            partition_key,
+            submitter,
+            ..
+        } = info.into_destruct();
+        let data = T::migrations().migrate(data, version, T::VERSION)?;
+        Ok(DestructQueueInfo {
+            id,
+            data: serde_json::from_value(data).map_err(DecodeError::Malformed)?,
+            schema: Some(T::schema()),
+            trace_context,
+            attempts,
+            retry_policy,
+            priority,
+            unique_key,
+            partition_key,
+            submitter,
+        }
+        .freeze())
+    }
+}
+
+impl<T: Payload + DeserializeOwned> ErroredInfo<T> {
+    /// Reads a delayed or failed job like [`QueueInfo::decode`]
+    pub fn decode(raw: &[u8]) -> Result<Self, DecodeError> {
+        let info: ErroredInfo<Value> =
+            serde_json::from_slice(raw).map_err(DecodeError::Malformed)?;
+        let version = written_version::<T>(info.schema())?;
+        let DestructErroredInfo {
+            id,
+            data,
+            stack_trace,
+            kind,
+            queued_at,
+            attempts,
+            retry_policy,
+            priority,
+            unique_key,
+            partition_key,
+            submitter,
+            ..
+        } = info.into_destruct();
+        let data = T::migrations().migrate(data, version, T::VERSION)?;
+        Ok(DestructErroredInfo {
+            id,
+            data: serde_json::from_value(data).map_err(DecodeError::Malformed)?,
+            schema: Some(T::schema()),
+            stack_trace,
+            kind,
+            queued_at,
+            attempts,
+            retry_policy,
+            priority,
+            unique_key,
+            partition_key,
+            submitter,
+        }
+        .freeze())
+    }
+}
+
+/// Version data of `T` was written with, entries without a schema predate it and count as 1
+fn written_version<T: Payload>(schema: &Option<PayloadSchema>) -> Result<u32, DecodeError> {
+    let version = match schema {
+        Some(schema) if schema.type_name() != T::TYPE_NAME => {
+            return Err(DecodeError::UnexpectedType {
+                expected: T::TYPE_NAME.to_string(),
+                found: schema.type_name().clone(),
+            })
+        }
+        Some(schema) => *schema.version(),
+        None => 1,
+    };
+    if version > T::VERSION {
+        return Err(DecodeError::Newer {
+            version,
+            current: T::VERSION,
+        });
+    }
+    Ok(version)
+}
+
+#[cfg(test)]
+mod test {
+    use super::written_version;
+    use crate::mq::{DecodeError, Migrations, Payload, PayloadSchema};
+    use serde_json::{json, Value};
+
+    struct TestData;
+
+    impl Payload for TestData {
+        const TYPE_NAME: &'static str = "test_data";
+        const VERSION: u32 = 3;
+    }
+
+    fn migrations() -> Migrations {
+        Migrations::default()
+            .register(1, |data| Ok(json!({ "name": data })))
+            .register(2, |mut data| {
+                data["count"] = json!(0);
+                Ok(data)
+            })
+    }
+
+    #[test]
+    fn test_migrate() {
+        let migrations = migrations();
+        assert_eq!(
+            migrations.migrate(json!("a"), 1, 3).unwrap(),
+            json!({ "name": "a", "count": 0 })
+        );
+        assert_eq!(
+            migrations.migrate(json!({ "name": "a" }), 2, 3).unwrap(),
+            json!({ "name": "a", "count": 0 })
+        );
+        assert_eq!(migrations.migrate(json!("a"), 3, 3).unwrap(), json!("a"));
+        assert!(matches!(
+            migrations.migrate(json!("a"), 1, 4),
+            Err(DecodeError::MissingMigration { from: 3 })
+        ));
+
+        let failing = Migrations::default().register(1, |data| {
+            serde_json::from_value::<u32>(data).map(Value::from)
+        });
+        assert!(matches!(
+            failing.migrate(json!("a"), 1, 2),
+            Err(DecodeError::Migration { from: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_written_version() {
+        assert_eq!(written_version::<TestData>(&None).unwrap(), 1);
+        assert_eq!(
+            written_version::<TestData>(&Some(PayloadSchema::new("test_data", 2))).unwrap(),
+            2
+        );
+        assert!(matches!(
+            written_version::<TestData>(&Some(PayloadSchema::new("test_data", 4))),
+            Err(DecodeError::Newer {
+                version: 4,
+                current: 3
+            })
+        ));
+        assert!(matches!(
+            written_version::<TestData>(&Some(PayloadSchema::new("other", 1))),
+            Err(DecodeError::UnexpectedType { .. })
+        ));
+    }
+}
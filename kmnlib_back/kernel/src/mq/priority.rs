@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// Lane a job waits in, workers drain higher lanes more often without starving the lower ones
+#[derive(
+    Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
+)]
+pub enum Priority {
+    #[serde(rename = "high")]
+    High,
+    #[default]
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "low")]
+    Low,
+}
+
+impl Priority {
+    /// Highest first, the order they sort in
+    pub const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    /// Relative share of the pops that start at this lane
+    pub fn weight(&self) -> u32 {
+        match self {
+            Priority::High => 6,
+            Priority::Normal => 3,
+            Priority::Low => 1,
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use crate::mq::Priority;
+use destructure::Destructure;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use vodca::References;
+
+/// A stream entry that could not be decoded into a job, kept aside so it is not redelivered
+#[derive(Debug, Clone, Serialize, Deserialize, References, Destructure)]
+pub struct QuarantinedInfo {
+    /// Id of the job, or a new one if the entry is not a job at all
+    id: Uuid,
+    priority: Priority,
+    /// Payload as it was read from the stream
+    raw: String,
+    error: String,
+    quarantined_at: OffsetDateTime,
+}
+
+impl QuarantinedInfo {
+    pub fn new(id: Uuid, priority: Priority, raw: String, error: String) -> Self {
+        Self {
+            id,
+            priority,
+            raw,
+            error,
+            quarantined_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vodca::References;
+
+/// How often and when a delayed job is attempted again
+#[derive(Debug, Clone, Serialize, Deserialize, References)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one
+    max_attempts: i64,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    /// Share of each delay that is randomized, so retries of a burst spread out. `0` disables it
+    jitter: f64,
+}
+
+impl RetryPolicy {
+    pub fn new(
+        max_attempts: i64,
+        initial_delay: Duration,
+        max_delay: Duration,
+        multiplier: f64,
+        jitter: f64,
+    ) -> Self {
+        Self {
+            max_attempts,
+            initial_delay,
+            max_delay,
+            multiplier: multiplier.max(1.0),
+            jitter: jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn fixed(max_attempts: i64, delay: Duration) -> Self {
+        Self::new(max_attempts, delay, delay, 1.0, 0.0)
+    }
+
+    pub fn exponential(max_attempts: i64, initial_delay: Duration, max_delay: Duration) -> Self {
+        Self::new(max_attempts, initial_delay, max_delay, 2.0, 0.2)
+    }
+
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    pub fn can_retry(&self, attempt: i64) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Delay after the `attempt`th attempt failed, `random` in `[0, 1)` picks the jittered share
+    pub fn delay(&self, attempt: i64, random: f64) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let max = self.max_delay.as_secs_f64();
+        let base = (self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent)).min(max);
+        let base = if base.is_finite() { base } else { max };
+        Duration::from_secs_f64(base * (1.0 - self.jitter * random.clamp(0.0, 1.0)))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::exponential(5, Duration::from_secs(10), Duration::from_secs(60 * 60))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mq::RetryPolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::exponential(5, Duration::from_secs(10), Duration::from_secs(60));
+        assert_eq!(policy.delay(1, 0.0), Duration::from_secs(10));
+        assert_eq!(policy.delay(2, 0.0), Duration::from_secs(20));
+        assert_eq!(policy.delay(3, 0.0), Duration::from_secs(40));
+        assert_eq!(policy.delay(4, 0.0), Duration::from_secs(60));
+        assert_eq!(policy.delay(i64::MAX, 0.0), Duration::from_secs(60));
+        // Jitter takes off at most its share of the delay
+        assert_eq!(policy.delay(1, 1.0), Duration::from_secs(8));
+        assert_eq!(policy.delay(1, 2.0), Duration::from_secs(8));
+
+        let fixed = RetryPolicy::fixed(3, Duration::from_secs(5));
+        assert_eq!(fixed.delay(1, 0.5), Duration::from_secs(5));
+        assert_eq!(fixed.delay(3, 0.5), Duration::from_secs(5));
+        assert!(fixed.can_retry(2));
+        assert!(!fixed.can_retry(3));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::KernelError;
+use chrono::{TimeZone, Utc};
+use destructure::Destructure;
+use error_stack::{Report, ResultExt};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+use vodca::References;
+
+/// A job queued every time its cron expression comes due
+#[derive(Debug, Clone)]
+pub struct Schedule<T> {
+    name: String,
+    expression: String,
+    cron: cron::Schedule,
+    data: T,
+}
+
+impl<T> Schedule<T> {
+    /// `expression` is a crontab line evaluated in UTC, optionally led by a seconds field
+    pub fn new(
+        name: impl Into<String>,
+        expression: &str,
+        data: T,
+    ) -> error_stack::Result<Self, KernelError> {
+        let normalized = match expression.split_whitespace().count() {
+            5 => format!("0 {expression}"),
+            _ => expression.to_string(),
+        };
+        let cron = cron::Schedule::from_str(&normalized)
+            .map_err(|e| Report::new(e).change_context(KernelError::Internal))
+            .attach_printable_lazy(|| format!("Invalid cron expression: {expression}"))?;
+        Ok(Self {
+            name: name.into(),
+            expression: expression.to_string(),
+            cron,
+            data,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    pub fn data(&self) -> &T {
+        &self.data
+    }
+
+    pub fn next_after(&self, after: OffsetDateTime) -> Option<OffsetDateTime> {
+        let after = Utc
+            .timestamp_opt(after.unix_timestamp(), after.nanosecond())
+            .single()?;
+        let next = self.cron.after(&after).next()?;
+        OffsetDateTime::from_unix_timestamp(next.timestamp()).ok()
+    }
+}
+
+/// What the scheduler recorded for a [`Schedule`]
+#[derive(Debug, Clone, Serialize, Deserialize, References, Destructure)]
+pub struct ScheduleState {
+    name: String,
+    expression: String,
+    last_run: Option<OffsetDateTime>,
+    next_run: Option<OffsetDateTime>,
+    /// Job queued by the last run
+    last_job: Option<Uuid>,
+}
+
+impl ScheduleState {
+    /// State of a schedule that has never run
+    pub fn new<T>(schedule: &Schedule<T>, now: OffsetDateTime) -> Self {
+        Self {
+            name: schedule.name().to_string(),
+            expression: schedule.expression().to_string(),
+            last_run: None,
+            next_run: schedule.next_after(now),
+            last_job: None,
+        }
+    }
+
+    pub fn is_due(&self, now: OffsetDateTime) -> bool {
+        self.next_run.is_some_and(|next_run| next_run <= now)
+    }
+
+    /// `next_run` is left alone for a manual trigger
+    pub fn ran(self, job: Uuid, at: OffsetDateTime, next_run: Option<OffsetDateTime>) -> Self {
+        Self {
+            last_run: Some(at),
+            last_job: Some(job),
+            next_run,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mq::{Schedule, ScheduleState};
+    use time::{Duration, OffsetDateTime};
+    use uuid::Uuid;
+
+    /// 2024-01-01T00:00:00Z
+    fn midnight() -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(1_704_067_200).unwrap()
+    }
+
+    #[test]
+    fn test_next_after() {
+        let nightly = Schedule::new("nightly", "0 2 * * *", ()).unwrap();
+        assert_eq!(
+            nightly.next_after(midnight()),
+            Some(midnight() + Duration::hours(2))
+        );
+        // A due time is not returned again
+        assert_eq!(
+            nightly.next_after(midnight() + Duration::hours(2)),
+            Some(midnight() + Duration::hours(26))
+        );
+
+        let seconds = Schedule::new("seconds", "30 * * * * *", ()).unwrap();
+        assert_eq!(
+            seconds.next_after(midnight()),
+            Some(midnight() + Duration::seconds(30))
+        );
+
+        assert!(Schedule::new("invalid", "not a cron", ()).is_err());
+    }
+
+    #[test]
+    fn test_state() {
+        let nightly = Schedule::new("nightly", "0 2 * * *", ()).unwrap();
+        let state = ScheduleState::new(&nightly, midnight());
+        assert!(!state.is_due(midnight()));
+        assert!(state.is_due(midnight() + Duration::hours(2)));
+
+        let job = Uuid::new_v4();
+        let state = state.ran(job, midnight(), None);
+        assert_eq!(state.last_job(), &Some(job));
+        assert!(!state.is_due(midnight() + Duration::days(1)));
+    }
+}
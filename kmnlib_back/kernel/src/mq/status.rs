@@ -0,0 +1,107 @@
+use destructure::Destructure;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use vodca::References;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+    #[serde(rename = "scheduled")]
+    Scheduled,
+    #[serde(rename = "queued")]
+    Queued,
+    #[serde(rename = "processing")]
+    Processing,
+    #[serde(rename = "done")]
+    Done,
+    #[serde(rename = "delayed")]
+    Delayed,
+    #[serde(rename = "failed")]
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, References, Destructure)]
+pub struct JobStatus {
+    id: Uuid,
+    state: JobState,
+    attempts: i64,
+    queued_at: OffsetDateTime,
+    updated_at: OffsetDateTime,
+    /// When a scheduled job becomes due, or a delayed one is attempted next
+    #[serde(default)]
+    run_at: Option<OffsetDateTime>,
+    /// Output of the handler once the job is done, e.g. the id of the created aggregate
+    result: Option<String>,
+    error: Option<String>,
+    /// User who queued the job, `None` for jobs queued by the system such as schedules
+    #[serde(default)]
+    submitter: Option<Uuid>,
+}
+
+impl JobStatus {
+    pub fn queued(id: Uuid) -> Self {
+        let now = OffsetDateTime::now_utc();
+        Self {
+            id,
+            state: JobState::Queued,
+            attempts: 0,
+            queued_at: now,
+            updated_at: now,
+            run_at: None,
+            result: None,
+            error: None,
+            submitter: None,
+        }
+    }
+
+    pub fn with_submitter(self, submitter: Option<Uuid>) -> Self {
+        Self { submitter, ..self }
+    }
+
+    pub fn scheduled(id: Uuid, run_at: OffsetDateTime) -> Self {
+        Self {
+            state: JobState::Scheduled,
+            run_at: Some(run_at),
+            ..Self::queued(id)
+        }
+    }
+
+    pub fn processing(self, attempts: i64) -> Self {
+        Self {
+            state: JobState::Processing,
+            attempts,
+            updated_at: OffsetDateTime::now_utc(),
+            run_at: None,
+            ..self
+        }
+    }
+
+    pub fn done(self, result: Option<String>) -> Self {
+        Self {
+            state: JobState::Done,
+            updated_at: OffsetDateTime::now_utc(),
+            result,
+            error: None,
+            ..self
+        }
+    }
+
+    pub fn delayed(self, error: String, run_at: OffsetDateTime) -> Self {
+        Self {
+            state: JobState::Delayed,
+            updated_at: OffsetDateTime::now_utc(),
+            run_at: Some(run_at),
+            error: Some(error),
+            ..self
+        }
+    }
+
+    pub fn failed(self, error: String) -> Self {
+        Self {
+            state: JobState::Failed,
+            updated_at: OffsetDateTime::now_utc(),
+            error: Some(error),
+            ..self
+        }
+    }
+}
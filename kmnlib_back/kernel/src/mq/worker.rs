@@ -0,0 +1,26 @@
+use destructure::Destructure;
+use time::OffsetDateTime;
+use vodca::References;
+
+#[derive(Debug, Clone, References, Destructure)]
+pub struct WorkerState {
+    member: String,
+    alive: bool,
+    stuck: bool,
+    last_heartbeat: OffsetDateTime,
+}
+
+impl WorkerState {
+    pub fn new(member: String, alive: bool, stuck: bool, last_heartbeat: OffsetDateTime) -> Self {
+        Self {
+            member,
+            alive,
+            stuck,
+            last_heartbeat,
+        }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.alive && !self.stuck
+    }
+}
@@ -0,0 +1,33 @@
+use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
+use crate::entity::{ApiKey, ApiKeyHash, ApiKeyId, SelectLimit, SelectOffset};
+use crate::KernelError;
+
+#[async_trait::async_trait]
+pub trait ApiKeyQuery: Sync + Send + 'static {
+    type Transaction: Transaction;
+    async fn get_all(
+        &self,
+        con: &mut Self::Transaction,
+        limit: &SelectLimit,
+        offset: &SelectOffset,
+    ) -> error_stack::Result<Vec<ApiKey>, KernelError>;
+
+    async fn find_by_id(
+        &self,
+        con: &mut Self::Transaction,
+        id: &ApiKeyId,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError>;
+
+    async fn find_by_hash(
+        &self,
+        con: &mut Self::Transaction,
+        hash: &ApiKeyHash,
+    ) -> error_stack::Result<Option<ApiKey>, KernelError>;
+}
+
+pub trait DependOnApiKeyQuery: Sync + Send + 'static + DependOnDatabaseConnection {
+    type ApiKeyQuery: ApiKeyQuery<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn api_key_query(&self) -> &Self::ApiKeyQuery;
+}
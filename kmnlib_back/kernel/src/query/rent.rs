@@ -1,5 +1,5 @@
 use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
-use crate::entity::{BookId, EventVersion, Rent, UserId};
+use crate::entity::{BookId, CreatedAt, EventVersion, Rent, UserId};
 use crate::event::{EventInfo, RentEvent};
 use crate::KernelError;
 
@@ -56,6 +56,14 @@ pub trait RentEventQuery: Sync + Send + 'static {
         user_id: &UserId,
         since: Option<&EventVersion<Rent>>,
     ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError>;
+
+    /// Rent events that are the last of their book and user and older than `rented_before`,
+    /// oldest first
+    async fn get_unreturned_before(
+        &self,
+        con: &mut Self::Transaction,
+        rented_before: &CreatedAt<Rent>,
+    ) -> error_stack::Result<Vec<EventInfo<RentEvent, Rent>>, KernelError>;
 }
 
 pub trait DependOnRentEventQuery: Sync + Send + 'static + DependOnDatabaseConnection {
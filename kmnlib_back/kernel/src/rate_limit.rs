@@ -0,0 +1,22 @@
+use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
+use crate::entity::{RateLimitDecision, RateLimitKey, RateLimitQuota};
+use crate::KernelError;
+
+#[async_trait::async_trait]
+pub trait RateLimiter: Sync + Send + 'static {
+    type Transaction: Transaction;
+    /// Takes a token from the bucket, refilling it first. Must be atomic across instances
+    async fn acquire(
+        &self,
+        con: &mut Self::Transaction,
+        key: &RateLimitKey,
+        quota: &RateLimitQuota,
+    ) -> error_stack::Result<RateLimitDecision, KernelError>;
+}
+
+pub trait DependOnRateLimiter: Sync + Send + 'static + DependOnDatabaseConnection {
+    type RateLimiter: RateLimiter<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn rate_limiter(&self) -> &Self::RateLimiter;
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::database::{DatabaseConnection, DependOnDatabaseConnection, Transaction};
+use crate::entity::{PasswordResetToken, TokenId, UserId};
+use crate::KernelError;
+
+#[async_trait::async_trait]
+pub trait TokenRevocation: Sync + Send + 'static {
+    type Transaction: Transaction;
+    /// Revoked ids only need to be kept until the token itself expires.
+    /// Returns `false` when the id was already revoked
+    async fn revoke(
+        &self,
+        con: &mut Self::Transaction,
+        id: &TokenId,
+        ttl: &Duration,
+    ) -> error_stack::Result<bool, KernelError>;
+
+    async fn is_revoked(
+        &self,
+        con: &mut Self::Transaction,
+        id: &TokenId,
+    ) -> error_stack::Result<bool, KernelError>;
+
+    /// Tokens carry the generation of their user they were issued in, starting at 0
+    async fn generation(
+        &self,
+        con: &mut Self::Transaction,
+        user_id: &UserId,
+    ) -> error_stack::Result<u64, KernelError>;
+
+    /// Revokes every token of the user issued so far and returns the new generation
+    async fn revoke_all(
+        &self,
+        con: &mut Self::Transaction,
+        user_id: &UserId,
+    ) -> error_stack::Result<u64, KernelError>;
+}
+
+pub trait DependOnTokenRevocation: Sync + Send + 'static + DependOnDatabaseConnection {
+    type TokenRevocation: TokenRevocation<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn token_revocation(&self) -> &Self::TokenRevocation;
+}
+
+#[async_trait::async_trait]
+pub trait PasswordResetStore: Sync + Send + 'static {
+    type Transaction: Transaction;
+    async fn issue(
+        &self,
+        con: &mut Self::Transaction,
+        token: &PasswordResetToken,
+        user_id: &UserId,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError>;
+
+    /// Tokens are single use, so this also removes it
+    async fn consume(
+        &self,
+        con: &mut Self::Transaction,
+        token: &PasswordResetToken,
+    ) -> error_stack::Result<Option<UserId>, KernelError>;
+}
+
+pub trait DependOnPasswordResetStore: Sync + Send + 'static + DependOnDatabaseConnection {
+    type PasswordResetStore: PasswordResetStore<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn password_reset_store(&self) -> &Self::PasswordResetStore;
+}
+
+#[async_trait::async_trait]
+pub trait PasswordResetDelivery: Sync + Send + 'static {
+    type Transaction: Transaction;
+    /// Hands the token to the user out of band, it is never returned to the caller
+    async fn deliver(
+        &self,
+        con: &mut Self::Transaction,
+        token: &PasswordResetToken,
+        user_id: &UserId,
+        ttl: &Duration,
+    ) -> error_stack::Result<(), KernelError>;
+}
+
+pub trait DependOnPasswordResetDelivery:
+    Sync + Send + 'static + DependOnDatabaseConnection
+{
+    type PasswordResetDelivery: PasswordResetDelivery<
+        Transaction = <Self::DatabaseConnection as DatabaseConnection>::Transaction,
+    >;
+    fn password_reset_delivery(&self) -> &Self::PasswordResetDelivery;
+}
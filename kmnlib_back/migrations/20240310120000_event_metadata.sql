@@ -0,0 +1,20 @@
+ALTER TABLE user_events
+    ADD COLUMN IF NOT EXISTS actor_id       TEXT,
+    ADD COLUMN IF NOT EXISTS correlation_id UUID,
+    ADD COLUMN IF NOT EXISTS causation_id   UUID,
+    ADD COLUMN IF NOT EXISTS client_ip      TEXT,
+    ADD COLUMN IF NOT EXISTS user_agent     TEXT;
+
+ALTER TABLE book_events
+    ADD COLUMN IF NOT EXISTS actor_id       TEXT,
+    ADD COLUMN IF NOT EXISTS correlation_id UUID,
+    ADD COLUMN IF NOT EXISTS causation_id   UUID,
+    ADD COLUMN IF NOT EXISTS client_ip      TEXT,
+    ADD COLUMN IF NOT EXISTS user_agent     TEXT;
+
+ALTER TABLE rent_events
+    ADD COLUMN IF NOT EXISTS actor_id       TEXT,
+    ADD COLUMN IF NOT EXISTS correlation_id UUID,
+    ADD COLUMN IF NOT EXISTS causation_id   UUID,
+    ADD COLUMN IF NOT EXISTS client_ip      TEXT,
+    ADD COLUMN IF NOT EXISTS user_agent     TEXT;
@@ -0,0 +1,5 @@
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS password_hash TEXT;
+
+ALTER TABLE user_events
+    ADD COLUMN IF NOT EXISTS password_hash TEXT;
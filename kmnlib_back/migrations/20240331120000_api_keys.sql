@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS api_keys
+(
+    id         UUID        NOT NULL PRIMARY KEY,
+    owner_id   UUID        NOT NULL,
+    name       TEXT        NOT NULL,
+    hash       TEXT        NOT NULL UNIQUE,
+    scopes     TEXT[]      NOT NULL,
+    expires_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (owner_id) REFERENCES users (id)
+);
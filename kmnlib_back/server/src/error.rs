@@ -1,7 +1,9 @@
-use axum::http::StatusCode;
+use axum::http::header::{CONTENT_TYPE, WWW_AUTHENTICATE};
+use axum::http::{HeaderValue, StatusCode};
 use axum::response::IntoResponse;
 use error_stack::Report;
 use kernel::KernelError;
+use serde::Serialize;
 use std::process::{ExitCode, Termination};
 
 #[derive(Debug)]
@@ -31,10 +33,94 @@ impl From<Report<KernelError>> for ErrorStatus {
 impl IntoResponse for ErrorStatus {
     fn into_response(self) -> axum::response::Response {
         match self.0.current_context() {
-            KernelError::Concurrency => StatusCode::CONFLICT,
-            KernelError::Timeout => StatusCode::REQUEST_TIMEOUT,
-            KernelError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            KernelError::Concurrency | KernelError::Ineligible => {
+                StatusCode::CONFLICT.into_response()
+            }
+            KernelError::Unprocessable => StatusCode::UNPROCESSABLE_ENTITY.into_response(),
+            KernelError::Timeout => StatusCode::REQUEST_TIMEOUT.into_response(),
+            KernelError::Internal => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+            KernelError::PermissionDenied(reason) => {
+                Problem::new(StatusCode::FORBIDDEN, reason.as_str()).into_response()
+            }
+            KernelError::PreconditionFailed => Problem::new(
+                StatusCode::PRECONDITION_FAILED,
+                "The resource has been modified since it was read",
+            )
+            .into_response(),
         }
-        .into_response()
+    }
+}
+
+/// RFC 7807 problem details
+#[derive(Debug, Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    kind: String,
+    title: String,
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            kind: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: Some(detail.into()),
+        }
+    }
+
+    pub fn unauthorized(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, detail)
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> axum::response::Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut response = (
+            status,
+            [(CONTENT_TYPE, "application/problem+json")],
+            axum::Json(self),
+        )
+            .into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use error_stack::Report;
+    use kernel::KernelError;
+
+    use crate::error::ErrorStatus;
+
+    fn status(error: KernelError) -> StatusCode {
+        ErrorStatus::from(Report::new(error))
+            .into_response()
+            .status()
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(status(KernelError::Concurrency), StatusCode::CONFLICT);
+        assert_eq!(status(KernelError::Ineligible), StatusCode::CONFLICT);
+        assert_eq!(
+            status(KernelError::Unprocessable),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            status(KernelError::PreconditionFailed),
+            StatusCode::PRECONDITION_FAILED
+        );
     }
 }
@@ -1,6 +1,13 @@
-use crate::mq::{init_command_worker, CommandOperation};
+use crate::middleware::{JwtKeys, RateLimits};
+use crate::mq::{
+    init_command_worker, init_maintenance_worker, CommandOperation, MaintenanceJob,
+    MaintenanceModule,
+};
+use crate::request::TrustedProxies;
 use driver::database::{PostgresDatabase, RedisDatabase, RedisMessageQueue};
+use kernel::interface::mq::MessageQueue;
 use kernel::KernelError;
+use metrics_exporter_prometheus::PrometheusHandle;
 use std::sync::Arc;
 use vodca::References;
 
@@ -8,13 +15,27 @@ use vodca::References;
 pub struct AppModule {
     handler: Arc<Handler>,
     worker: Arc<Worker>,
+    metrics: PrometheusHandle,
+    jwt: Arc<JwtKeys>,
+    rate_limits: Arc<RateLimits>,
+    trusted_proxies: Arc<TrustedProxies>,
 }
 
 impl AppModule {
-    pub async fn new() -> error_stack::Result<Self, KernelError> {
+    pub async fn new(metrics: PrometheusHandle) -> error_stack::Result<Self, KernelError> {
         let handler = Arc::new(Handler::init().await?);
-        let worker = Arc::new(Worker::new(&handler));
-        Ok(Self { handler, worker })
+        let worker = Arc::new(Worker::new(&handler)?);
+        let jwt = Arc::new(JwtKeys::from_env()?);
+        let rate_limits = Arc::new(RateLimits::from_env()?);
+        let trusted_proxies = Arc::new(TrustedProxies::from_env()?);
+        Ok(Self {
+            handler,
+            worker,
+            metrics,
+            jwt,
+            rate_limits,
+            trusted_proxies,
+        })
     }
 }
 
@@ -35,12 +56,20 @@ impl Handler {
 
 #[derive(References)]
 pub struct Worker {
-    command: RedisMessageQueue<Arc<Handler>, CommandOperation>,
+    command: Arc<RedisMessageQueue<Arc<Handler>, CommandOperation>>,
+    maintenance: RedisMessageQueue<MaintenanceModule, MaintenanceJob>,
 }
 
 impl Worker {
-    pub fn new(handler: &Arc<Handler>) -> Self {
-        let command = init_command_worker(handler);
-        Self { command }
+    pub fn new(handler: &Arc<Handler>) -> error_stack::Result<Self, KernelError> {
+        let command = Arc::new(init_command_worker(handler));
+        command.start_workers();
+        let maintenance =
+            init_maintenance_worker(MaintenanceModule::new(handler.clone(), command.clone()))?;
+        maintenance.start_workers();
+        Ok(Self {
+            command,
+            maintenance,
+        })
     }
 }
@@ -1,11 +1,22 @@
 use crate::error::StackTrace;
 use crate::handler::AppModule;
-use crate::route::{BookRouter, QueueRouter, RentRouter, UserRouter};
+use crate::middleware::{
+    authenticate, idempotency, init_metrics_recorder, rate_limit, track_http_metrics,
+    REQUEST_TIMEOUT,
+};
+use crate::route::{
+    AdminRouter, AuthRouter, BookRouter, HealthRouter, MetricsRouter, QueueRouter, RentRouter,
+    UserRouter,
+};
+use crate::telemetry::{init_tracer, shutdown_tracer};
 use error_stack::ResultExt;
 use kernel::KernelError;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::timeout::TimeoutLayer;
+use tower_http::trace::TraceLayer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::Layer;
@@ -13,15 +24,18 @@ use tracing_subscriber::Layer;
 mod controller;
 mod error;
 mod handler;
+mod middleware;
 mod mq;
 mod request;
 mod response;
 mod route;
+mod telemetry;
 
 #[tokio::main]
 async fn main() -> Result<(), StackTrace> {
     let appender = tracing_appender::rolling::daily(std::path::Path::new("./logs/"), "debug.log");
     let (non_blocking_appender, _guard) = tracing_appender::non_blocking(appender);
+    let tracer = init_tracer()?;
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::fmt::layer()
@@ -38,15 +52,48 @@ async fn main() -> Result<(), StackTrace> {
                 .with_ansi(false)
                 .with_filter(tracing_subscriber::filter::LevelFilter::DEBUG),
         )
+        .with(tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer)))
         .init();
 
-    let app = AppModule::new().await?;
+    let metrics = init_metrics_recorder()?;
+    let app = AppModule::new(metrics).await?;
 
-    let router = axum::Router::new()
+    // Routes that must stay reachable without credentials
+    let public = axum::Router::new()
+        .route_health()
+        .route_metrics()
+        .route_auth()
+        .route_layer(axum::middleware::from_fn_with_state(
+            app.clone(),
+            rate_limit,
+        ));
+    let protected = axum::Router::new()
         .route_book()
         .route_user()
         .route_rent()
         .route_queue()
+        .route_session()
+        .route_admin()
+        .route_layer(axum::middleware::from_fn_with_state(
+            app.clone(),
+            idempotency,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app.clone(),
+            rate_limit,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            app.clone(),
+            authenticate,
+        ));
+
+    let router = public
+        .merge(protected)
+        .route_layer(axum::middleware::from_fn(track_http_metrics))
+        .layer(TimeoutLayer::new(REQUEST_TIMEOUT))
+        .layer(TraceLayer::new_for_http())
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(
             CorsLayer::new(), //TODO .allow_origin([""])
         )
@@ -58,9 +105,13 @@ async fn main() -> Result<(), StackTrace> {
         .change_context_lazy(|| KernelError::Internal)
         .attach_printable_lazy(|| "Failed to listen tcp")?;
 
-    axum::serve(tcp, router.into_make_service())
-        .await
-        .change_context_lazy(|| KernelError::Internal)?;
+    axum::serve(
+        tcp,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .change_context_lazy(|| KernelError::Internal)?;
 
+    shutdown_tracer();
     Ok(())
 }
@@ -0,0 +1,6 @@
+mod auth;
+mod idempotency;
+mod metrics;
+mod rate_limit;
+
+pub use self::{auth::*, idempotency::*, metrics::*, rate_limit::*};
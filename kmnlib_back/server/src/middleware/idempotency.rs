@@ -0,0 +1,286 @@
+use crate::error::Problem;
+use crate::handler::AppModule;
+use crate::middleware::Principal;
+use application::service::IdempotencyService;
+use axum::body::{to_bytes, Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::request::Parts;
+use axum::http::{HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use kernel::prelude::entity::{
+    IdempotencyKey, IdempotencyRecord, IdempotentResponse, RequestFingerprint,
+};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tracing::error;
+
+static IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+static IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+const MAX_KEY_LENGTH: usize = 255;
+const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Requests still running after this are answered with `408`
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Replays the first response of a `POST` carrying an `Idempotency-Key` instead of running it again.
+/// Keys are scoped to the principal, so this must be layered inside `authenticate`
+pub async fn idempotency(
+    State(module): State<AppModule>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+    let Some(subject) = request
+        .extensions()
+        .get::<Principal>()
+        .map(|principal| principal.subject().to_string())
+    else {
+        return next.run(request).await;
+    };
+    idempotent(
+        module.handler().redis_pool(),
+        &subject,
+        &REQUEST_TIMEOUT,
+        request,
+        next,
+    )
+    .await
+}
+
+/// Holds the key for `pending_ttl` while the request runs, no longer than it may take
+async fn idempotent<R: IdempotencyService>(
+    redis: &R,
+    subject: &str,
+    pending_ttl: &Duration,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(header) = request.headers().get(&IDEMPOTENCY_KEY) else {
+        return next.run(request).await;
+    };
+    let key = match header.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => {
+            IdempotencyKey::new(format!("{subject}:{key}"))
+        }
+        _ => {
+            return Problem::new(StatusCode::BAD_REQUEST, "Invalid Idempotency-Key").into_response()
+        }
+    };
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, MAX_BODY_SIZE).await else {
+        return Problem::new(StatusCode::PAYLOAD_TOO_LARGE, "Request body is too large")
+            .into_response();
+    };
+    let fingerprint = fingerprint(&parts, &body);
+
+    let pending = IdempotencyRecord::new(fingerprint.clone(), None);
+    match redis
+        .reserve_idempotency_key(&key, &pending, pending_ttl)
+        .await
+    {
+        Ok(None) => {}
+        Ok(Some(existing)) if existing.fingerprint() != &fingerprint => {
+            return Problem::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used with a different request",
+            )
+            .into_response()
+        }
+        Ok(Some(existing)) => {
+            return match existing.response() {
+                Some(response) => replay(response),
+                None => Problem::new(
+                    StatusCode::CONFLICT,
+                    "A request with this Idempotency-Key is still being processed",
+                )
+                .into_response(),
+            }
+        }
+        Err(report) => {
+            error!("Failed to reserve idempotency key: {report:?}");
+            return Problem::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to reserve idempotency key",
+            )
+            .into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) if !parts.status.is_server_error() => body,
+        result => {
+            // Failed requests may be retried with the same key
+            if let Err(report) = redis.release_idempotency_key(&key).await {
+                error!("Failed to release idempotency key: {report:?}");
+            }
+            let body = result.map(Body::from).unwrap_or_else(|_| Body::empty());
+            return Response::from_parts(parts, body);
+        }
+    };
+    let record = IdempotencyRecord::new(
+        fingerprint,
+        Some(IdempotentResponse {
+            status: parts.status.as_u16(),
+            headers: parts
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = value.to_str().ok()?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }),
+    );
+    if let Err(report) = redis.complete_idempotency_key(&key, &record).await {
+        error!("Failed to store idempotent response: {report:?}");
+    }
+    Response::from_parts(parts, Body::from(body))
+}
+
+fn fingerprint(parts: &Parts, body: &Bytes) -> RequestFingerprint {
+    let mut hasher = Sha256::new();
+    hasher.update(parts.method.as_str());
+    hasher.update(parts.uri.to_string());
+    hasher.update(body);
+    RequestFingerprint::new(
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>(),
+    )
+}
+
+fn replay(stored: &IdempotentResponse) -> Response {
+    let mut response = Response::new(Body::from(stored.body.clone()));
+    *response.status_mut() = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let headers = response.headers_mut();
+    for (name, value) in &stored.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name.as_str()),
+            HeaderValue::try_from(value.as_str()),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    headers.insert(
+        IDEMPOTENT_REPLAYED.clone(),
+        HeaderValue::from_static("true"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use axum::body::Body;
+    use axum::extract::{Request, State};
+    use axum::http::StatusCode;
+    use axum::middleware::{from_fn_with_state, Next};
+    use axum::routing::post;
+    use axum::Router;
+    use driver::database::RedisDatabase;
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::middleware::idempotency::{idempotent, IDEMPOTENCY_KEY, IDEMPOTENT_REPLAYED};
+
+    const PENDING_TTL: Duration = Duration::from_secs(1);
+
+    /// The handler answers `500` for the body `fail` and never finishes for `hang`
+    fn router(calls: Arc<AtomicUsize>) -> Router {
+        let db = RedisDatabase::new().unwrap();
+        Router::new()
+            .route(
+                "/",
+                post(move |body: String| async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    match body.as_str() {
+                        "fail" => StatusCode::INTERNAL_SERVER_ERROR,
+                        "hang" => std::future::pending().await,
+                        _ => StatusCode::CREATED,
+                    }
+                }),
+            )
+            .layer(from_fn_with_state(
+                db,
+                |State(db): State<RedisDatabase>, request: Request, next: Next| async move {
+                    idempotent(&db, "test", &PENDING_TTL, request, next).await
+                },
+            ))
+    }
+
+    fn request(key: &str, body: &'static str) -> Request {
+        Request::post("/")
+            .header(&IDEMPOTENCY_KEY, key)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_replay() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = router(calls.clone());
+        let key = Uuid::new_v4().to_string();
+
+        let first = router.clone().oneshot(request(&key, "ok")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert!(first.headers().get(&IDEMPOTENT_REPLAYED).is_none());
+
+        let replayed = router.clone().oneshot(request(&key, "ok")).await.unwrap();
+        assert_eq!(replayed.status(), StatusCode::CREATED);
+        assert!(replayed.headers().get(&IDEMPOTENT_REPLAYED).is_some());
+
+        let other = router.oneshot(request(&key, "other")).await.unwrap();
+        assert_eq!(other.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_server_error_releases() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = router(calls.clone());
+        let key = Uuid::new_v4().to_string();
+
+        for _ in 0..2 {
+            let response = router.clone().oneshot(request(&key, "fail")).await.unwrap();
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// A request cut off by the timeout only blocks its key until the pending record expires
+    #[test_with::env(REDIS_TEST)]
+    #[tokio::test]
+    async fn test_abandoned_expires() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = router(calls.clone());
+        let key = Uuid::new_v4().to_string();
+
+        let abandoned = tokio::time::timeout(
+            Duration::from_millis(200),
+            router.clone().oneshot(request(&key, "hang")),
+        )
+        .await;
+        assert!(abandoned.is_err());
+
+        let in_flight = router.clone().oneshot(request(&key, "hang")).await;
+        assert_eq!(in_flight.unwrap().status(), StatusCode::CONFLICT);
+
+        tokio::time::sleep(PENDING_TTL + Duration::from_millis(200)).await;
+        let retried = router.oneshot(request(&key, "ok")).await.unwrap();
+        assert_eq!(retried.status(), StatusCode::CREATED);
+    }
+}
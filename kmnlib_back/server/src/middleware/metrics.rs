@@ -0,0 +1,97 @@
+use axum::extract::{MatchedPath, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use error_stack::ResultExt;
+use kernel::KernelError;
+use metrics::{counter, describe_counter, describe_histogram, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::time::Instant;
+
+const HTTP_REQUESTS_TOTAL: &str = "http_requests_total";
+const HTTP_REQUEST_DURATION_SECONDS: &str = "http_request_duration_seconds";
+
+const HTTP_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+pub fn metrics_builder() -> error_stack::Result<PrometheusBuilder, KernelError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(HTTP_REQUEST_DURATION_SECONDS.to_string()),
+            HTTP_DURATION_BUCKETS,
+        )
+        .change_context_lazy(|| KernelError::Internal)
+}
+
+pub fn init_metrics_recorder() -> error_stack::Result<PrometheusHandle, KernelError> {
+    let handle = metrics_builder()?
+        .install_recorder()
+        .change_context_lazy(|| KernelError::Internal)
+        .attach_printable_lazy(|| "Failed to install metrics recorder")?;
+    describe_counter!(
+        HTTP_REQUESTS_TOTAL,
+        "Number of HTTP requests by method, route and status"
+    );
+    describe_histogram!(
+        HTTP_REQUEST_DURATION_SECONDS,
+        "HTTP request latency by method, route and status"
+    );
+    driver::metrics::describe_metrics();
+    Ok(handle)
+}
+
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let start = Instant::now();
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let response = next.run(request).await;
+    let labels = [
+        ("method", method),
+        ("path", path),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    counter!(HTTP_REQUESTS_TOTAL, &labels).increment(1);
+    histogram!(HTTP_REQUEST_DURATION_SECONDS, &labels).record(start.elapsed().as_secs_f64());
+    response
+}
+
+#[cfg(test)]
+mod test {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::middleware::from_fn;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use crate::middleware::metrics::{metrics_builder, track_http_metrics};
+
+    #[tokio::test]
+    async fn test_track_http_metrics() {
+        let recorder = metrics_builder().unwrap().build_recorder();
+        let handle = recorder.handle();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        let router = Router::new()
+            .route("/books/:id", get(|| async { StatusCode::NO_CONTENT }))
+            .route_layer(from_fn(track_http_metrics));
+        let request = Request::get("/books/1").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let rendered = handle.render();
+        let labels = r#"method="GET",path="/books/:id",status="204""#;
+        assert!(rendered.contains(&format!("http_requests_total{{{labels}}} 1")));
+        assert!(rendered.contains(&format!(
+            r#"http_request_duration_seconds_bucket{{{labels},le="0.005"}}"#
+        )));
+        assert!(rendered.contains(&format!(
+            "http_request_duration_seconds_count{{{labels}}} 1"
+        )));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::error::Problem;
+use crate::handler::AppModule;
+use crate::middleware::Principal;
+use crate::request::client_ip;
+use application::service::RateLimitService;
+use axum::extract::{Request, State};
+use axum::http::header::RETRY_AFTER;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use kernel::prelude::entity::{RateLimitDecision, RateLimitKey, RateLimitQuota};
+use kernel::KernelError;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+
+static RATE_LIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+static RATE_LIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+static RATE_LIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+
+/// Write endpoints that share a bucket, by the first path segment
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum RouteGroup {
+    Auth,
+    Books,
+    Users,
+    Rents,
+    Admin,
+}
+
+impl RouteGroup {
+    const ALL: [RouteGroup; 5] = [
+        RouteGroup::Auth,
+        RouteGroup::Books,
+        RouteGroup::Users,
+        RouteGroup::Rents,
+        RouteGroup::Admin,
+    ];
+
+    fn of(path: &str) -> Option<Self> {
+        let segment = path.trim_start_matches('/').split('/').next()?;
+        Self::ALL
+            .into_iter()
+            .find(|group| group.as_str() == segment)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteGroup::Auth => "auth",
+            RouteGroup::Books => "books",
+            RouteGroup::Users => "users",
+            RouteGroup::Rents => "rents",
+            RouteGroup::Admin => "admin",
+        }
+    }
+
+    fn default_quota(&self) -> RateLimitQuota {
+        let minute = Duration::from_secs(60);
+        match self {
+            RouteGroup::Auth => RateLimitQuota::new(10, minute),
+            RouteGroup::Books | RouteGroup::Rents => RateLimitQuota::new(60, minute),
+            RouteGroup::Users | RouteGroup::Admin => RateLimitQuota::new(30, minute),
+        }
+    }
+}
+
+pub struct RateLimits {
+    quotas: HashMap<RouteGroup, RateLimitQuota>,
+}
+
+impl RateLimits {
+    /// Reads `RATE_LIMIT_<GROUP>` as `<capacity>/<seconds>`, or `off` to disable the group
+    pub fn from_env() -> error_stack::Result<Self, KernelError> {
+        let mut quotas = HashMap::new();
+        for group in RouteGroup::ALL {
+            let key = format!("RATE_LIMIT_{}", group.as_str().to_uppercase());
+            let quota = match dotenvy::var(&key) {
+                Ok(value) if value.eq_ignore_ascii_case("off") => continue,
+                Ok(value) => value.parse()?,
+                Err(_) => group.default_quota(),
+            };
+            quotas.insert(group, quota);
+        }
+        Ok(Self { quotas })
+    }
+
+    fn quota(&self, group: &RouteGroup) -> Option<&RateLimitQuota> {
+        self.quotas.get(group)
+    }
+}
+
+/// Limits non-safe requests per principal, or per client ip before authentication.
+/// Must be layered inside `authenticate` so the principal is known
+pub async fn rate_limit(State(module): State<AppModule>, request: Request, next: Next) -> Response {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return next.run(request).await;
+    }
+    let Some((group, quota)) = RouteGroup::of(request.uri().path()).and_then(|group| {
+        module
+            .rate_limits()
+            .quota(&group)
+            .map(|quota| (group, *quota))
+    }) else {
+        return next.run(request).await;
+    };
+    let client = match request.extensions().get::<Principal>() {
+        Some(principal) => format!("principal:{}", principal.subject()),
+        None => {
+            let ip = client_ip(
+                request.headers(),
+                request.extensions(),
+                module.trusted_proxies(),
+            );
+            format!("ip:{}", ip.as_deref().unwrap_or("unknown"))
+        }
+    };
+    let key = RateLimitKey::new(format!("{}:{client}", group.as_str()));
+    let decision = match module
+        .handler()
+        .redis_pool()
+        .acquire_rate_limit(&key, &quota)
+        .await
+    {
+        Ok(decision) => decision,
+        Err(report) => {
+            // Availability of the api is preferred over strict limiting
+            warn!("Rate limiter is unavailable: {report:?}");
+            return next.run(request).await;
+        }
+    };
+    if !decision.is_allowed() {
+        let mut response =
+            Problem::new(StatusCode::TOO_MANY_REQUESTS, "Rate limit exceeded").into_response();
+        let headers = response.headers_mut();
+        insert_headers(headers, &quota, &decision);
+        headers.insert(RETRY_AFTER, seconds(decision.retry_after()));
+        return response;
+    }
+    let mut response = next.run(request).await;
+    insert_headers(response.headers_mut(), &quota, &decision);
+    response
+}
+
+fn insert_headers(headers: &mut HeaderMap, quota: &RateLimitQuota, decision: &RateLimitDecision) {
+    headers.insert(
+        RATE_LIMIT_LIMIT.clone(),
+        HeaderValue::from(quota.capacity()),
+    );
+    headers.insert(
+        RATE_LIMIT_REMAINING.clone(),
+        HeaderValue::from(decision.remaining()),
+    );
+    headers.insert(RATE_LIMIT_RESET.clone(), seconds(decision.reset_after()));
+}
+
+/// Whole seconds, rounded up so clients never retry too early
+fn seconds(duration: &Duration) -> HeaderValue {
+    HeaderValue::from(duration.as_millis().div_ceil(1000) as u64)
+}
@@ -1,3 +1,5 @@
 mod command;
+mod maintenance;
 
 pub use crate::mq::command::*;
+pub use crate::mq::maintenance::*;
@@ -1,29 +1,101 @@
 use crate::handler::Handler;
-use application::service::{HandleBookService, HandleUserService};
+use application::service::{HandleBookService, HandleRentService, HandleUserService};
 use driver::database::RedisMessageQueue;
-use error_stack::ResultExt;
-use kernel::interface::event::{BookEvent, UserEvent};
+use driver::layer::{CatchPanicLayer, MetricsLayer, TimeoutLayer, TraceLayer};
+use kernel::interface::event::{BookEvent, EventMetadata, RentEvent, UserEvent};
 use kernel::interface::mq::MQConfig;
-use kernel::interface::mq::{ErrorOperation, MessageQueue};
+use kernel::interface::mq::{ErrorKind, ErrorPolicy, MessageQueue, Payload, QueueInfo};
+use kernel::prelude::entity::{Book, EventVersion, ExpectedEventVersion, User};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Longest a command may take before it is given up on and retried
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CommandOperation {
-    Book(BookEvent),
-    User(UserEvent),
+    Book(BookEvent, Option<EventVersion<Book>>, EventMetadata),
+    User(UserEvent, Option<EventVersion<User>>, EventMetadata),
+    Rent(RentEvent, EventMetadata),
 }
 
 impl CommandOperation {
-    pub fn book(event: BookEvent) -> Self {
-        Self::Book(event)
+    /// `version` is the one the client has seen, the command fails if the stream has moved on since
+    pub fn book(
+        event: BookEvent,
+        version: Option<EventVersion<Book>>,
+        metadata: EventMetadata,
+    ) -> Self {
+        Self::Book(event, version, metadata)
+    }
+
+    pub fn user(
+        event: UserEvent,
+        version: Option<EventVersion<User>>,
+        metadata: EventMetadata,
+    ) -> Self {
+        Self::User(event, version, metadata)
+    }
+
+    pub fn rent(event: RentEvent, metadata: EventMetadata) -> Self {
+        Self::Rent(event, metadata)
+    }
+
+    /// Identical commands of the same actor on the same aggregate share a key, so a resend joins
+    /// the pending job as long as nothing was queued on the aggregate after it.
+    /// The rest of the metadata is left out since it differs between requests
+    pub fn unique_key(&self) -> Option<String> {
+        let (kind, command) = match self {
+            Self::Book(event, version, metadata) => (
+                "book",
+                serde_json::to_vec(&(event, version, metadata.actor_id())),
+            ),
+            Self::User(event, version, metadata) => (
+                "user",
+                serde_json::to_vec(&(event, version, metadata.actor_id())),
+            ),
+            Self::Rent(event, metadata) => {
+                ("rent", serde_json::to_vec(&(event, metadata.actor_id())))
+            }
+        };
+        let digest = Sha256::digest(command.ok()?);
+        let digest = digest
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        Some(format!("{kind}:{digest}"))
+    }
+
+    /// Commands on the same aggregate are applied in the order they were queued.
+    /// Rents compete for the copies of a book, so they line up behind the other commands on it
+    pub fn partition_key(&self) -> String {
+        match self {
+            Self::Book(event, ..) => format!("book:{}", event.id().as_ref()),
+            Self::User(event, ..) => format!("user:{}", event.id().as_ref()),
+            Self::Rent(RentEvent::Rent { book_id, .. } | RentEvent::Return { book_id, .. }, _) => {
+                format!("book:{}", book_id.as_ref())
+            }
+        }
     }
 
-    pub fn user(event: UserEvent) -> Self {
-        Self::User(event)
+    pub fn into_queue_info(self) -> QueueInfo<Self> {
+        let partition_key = self.partition_key();
+        let info = match self.unique_key() {
+            Some(key) => QueueInfo::from(self).with_unique_key(key),
+            None => QueueInfo::from(self),
+        };
+        info.with_partition_key(partition_key)
     }
 }
 
+/// Changes to `BookEvent`, `UserEvent` and `RentEvent` change this payload too
+impl Payload for CommandOperation {
+    const TYPE_NAME: &'static str = "command_operation";
+    const VERSION: u32 = 1;
+}
+
 pub fn init_command_worker(
     handler: &Arc<Handler>,
 ) -> RedisMessageQueue<Arc<Handler>, CommandOperation> {
@@ -38,17 +110,59 @@ pub fn init_command_worker(
         |handler: Arc<Handler>, data: CommandOperation| async move {
             let pgpool = handler.pgpool();
             match data {
-                CommandOperation::Book(book) => pgpool
-                    .handle_book_event(book)
+                CommandOperation::Book(book, version, metadata) => pgpool
+                    .handle_book_event(book, version.map(ExpectedEventVersion::Exact), metadata)
+                    .await
+                    .map(|id| Some(id.as_ref().to_string()))
+                    .map_err(|report| command_policy().classify(report)),
+                CommandOperation::User(user, version, metadata) => pgpool
+                    .handle_user_event(user, version.map(ExpectedEventVersion::Exact), metadata)
                     .await
-                    .map(|_| ())
-                    .change_context_lazy(|| ErrorOperation::Delay),
-                CommandOperation::User(user) => pgpool
-                    .handle_user_event(user)
+                    .map(|id| Some(id.as_ref().to_string()))
+                    .map_err(|report| command_policy().classify(report)),
+                CommandOperation::Rent(rent, metadata) => pgpool
+                    .handle_rent_event(rent, metadata)
                     .await
-                    .map(|_| ())
-                    .change_context_lazy(|| ErrorOperation::Delay),
+                    .map(|()| None)
+                    .map_err(|report| rent_policy().classify(report)),
             }
         },
     )
+    .with_layer(TraceLayer)
+    .with_layer(MetricsLayer)
+    .with_layer(CatchPanicLayer)
+    .with_layer(TimeoutLayer::new(COMMAND_TIMEOUT))
+}
+
+/// A stale version will never succeed, so conflicts fail right away
+fn command_policy() -> ErrorPolicy {
+    ErrorPolicy::default()
+}
+
+/// Eligibility is checked again on every attempt, so a lost race is retried
+/// while a rent the library does not allow fails right away
+fn rent_policy() -> ErrorPolicy {
+    ErrorPolicy::default().with_transient(ErrorKind::Conflict)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mq::CommandOperation;
+    use kernel::interface::event::{EventMetadata, RentEvent};
+    use kernel::prelude::entity::{ActorId, BookId, UserId};
+    use uuid::Uuid;
+
+    #[test]
+    fn test_unique_key() {
+        let event = RentEvent::Rent {
+            book_id: BookId::new(Uuid::new_v4()),
+            user_id: UserId::new(Uuid::new_v4()),
+        };
+        let by = |actor: &str| {
+            let metadata = EventMetadata::new(Some(ActorId::new(actor)), None, None, None, None);
+            CommandOperation::rent(event.clone(), metadata).unique_key()
+        };
+        assert_eq!(by("a"), by("a"));
+        assert_ne!(by("a"), by("b"));
+    }
 }
@@ -0,0 +1,113 @@
+use crate::handler::Handler;
+use crate::mq::CommandOperation;
+use application::service::GetRentService;
+use application::transfer::GetOverdueRentsDto;
+use driver::database::RedisMessageQueue;
+use driver::layer::{CatchPanicLayer, MetricsLayer, TimeoutLayer, TraceLayer};
+use kernel::interface::mq::{
+    ErrorPolicy, ErroredInfoFilter, MQConfig, MessageQueue, Payload, Schedule,
+};
+use kernel::KernelError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::warn;
+use vodca::References;
+
+/// How long a book may be out before the overdue scan reports it
+pub const LOAN_PERIOD: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// How long failed commands are kept before compaction drops them
+pub const FAILED_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+const MAINTENANCE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Housekeeping run on schedules, see [`init_maintenance_worker`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MaintenanceJob {
+    /// Logs the rents open longer than [`LOAN_PERIOD`] and answers how many there are
+    ScanOverdueRents,
+    /// Drops failed commands whose last attempt ended more than [`FAILED_RETENTION`] ago
+    CompactCommandQueue,
+}
+
+impl Payload for MaintenanceJob {
+    const TYPE_NAME: &'static str = "maintenance_job";
+    const VERSION: u32 = 1;
+}
+
+/// Maintenance jobs act on the command queue, so it is handed to them next to the handler
+#[derive(Clone, References)]
+pub struct MaintenanceModule {
+    handler: Arc<Handler>,
+    command: Arc<RedisMessageQueue<Arc<Handler>, CommandOperation>>,
+}
+
+impl MaintenanceModule {
+    pub fn new(
+        handler: Arc<Handler>,
+        command: Arc<RedisMessageQueue<Arc<Handler>, CommandOperation>>,
+    ) -> Self {
+        Self { handler, command }
+    }
+}
+
+/// Runs the overdue scan nightly at 02:00 UTC and the compaction at 03:00 UTC
+pub fn init_maintenance_worker(
+    module: MaintenanceModule,
+) -> error_stack::Result<RedisMessageQueue<MaintenanceModule, MaintenanceJob>, KernelError> {
+    let pool = module.handler().redis_pool().clone();
+    let queue = RedisMessageQueue::new(
+        pool,
+        module,
+        "maintenance_worker",
+        MQConfig::default(),
+        |module: MaintenanceModule, data: MaintenanceJob| async move {
+            match data {
+                MaintenanceJob::ScanOverdueRents => scan_overdue_rents(&module).await,
+                MaintenanceJob::CompactCommandQueue => compact_command_queue(&module).await,
+            }
+            .map(|count| Some(count.to_string()))
+            .map_err(|report| ErrorPolicy::default().classify(report))
+        },
+    )
+    .with_layer(TraceLayer)
+    .with_layer(MetricsLayer)
+    .with_layer(CatchPanicLayer)
+    .with_layer(TimeoutLayer::new(MAINTENANCE_TIMEOUT));
+    queue.register_schedule(Schedule::new(
+        "overdue_scan",
+        "0 2 * * *",
+        MaintenanceJob::ScanOverdueRents,
+    )?);
+    queue.register_schedule(Schedule::new(
+        "command_compaction",
+        "0 3 * * *",
+        MaintenanceJob::CompactCommandQueue,
+    )?);
+    Ok(queue)
+}
+
+async fn scan_overdue_rents(module: &MaintenanceModule) -> error_stack::Result<usize, KernelError> {
+    let dto = GetOverdueRentsDto {
+        rented_before: OffsetDateTime::now_utc() - LOAN_PERIOD,
+    };
+    let rents = module.handler().pgpool().get_overdue_rents(&dto).await?;
+    for rent in &rents {
+        warn!(
+            event = ?rent.event(),
+            rented_at = %rent.created_at().as_ref(),
+            "Rent is overdue"
+        );
+    }
+    Ok(rents.len())
+}
+
+async fn compact_command_queue(
+    module: &MaintenanceModule,
+) -> error_stack::Result<usize, KernelError> {
+    let filter = ErroredInfoFilter::default()
+        .with_window(None, Some(OffsetDateTime::now_utc() - FAILED_RETENTION));
+    module.command().purge_failed_infos(&filter).await
+}
@@ -1,6 +1,12 @@
+mod api_key;
+mod auth;
 mod book;
+mod context;
+mod precondition;
 mod queue;
 mod rent;
 mod user;
 
-pub use crate::request::{book::*, queue::*, rent::*, user::*};
+pub use crate::request::{
+    api_key::*, auth::*, book::*, context::*, precondition::*, queue::*, rent::*, user::*,
+};
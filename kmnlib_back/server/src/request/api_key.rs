@@ -0,0 +1,73 @@
+use crate::controller::Intake;
+use application::transfer::{CreateApiKeyDto, GetAllApiKeyDto, RevokeApiKeyDto};
+use kernel::prelude::entity::{
+    ApiKeyExpiresAt, ApiKeyId, ApiKeyName, ApiKeyScope, SelectLimit, SelectOffset, UserId,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    owner_id: Uuid,
+    name: String,
+    #[serde(default)]
+    scopes: Vec<ApiKeyScope>,
+    /// Lifetime in seconds, the key never expires if omitted
+    expires_in: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetAllApiKeyRequest {
+    #[serde(default)]
+    limit: SelectLimit,
+    #[serde(default)]
+    offset: SelectOffset,
+}
+
+#[derive(Debug)]
+pub struct RevokeApiKeyRequest {
+    id: Uuid,
+}
+
+impl RevokeApiKeyRequest {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+pub struct ApiKeyTransformer;
+
+impl Intake<CreateApiKeyRequest> for ApiKeyTransformer {
+    type To = CreateApiKeyDto;
+    fn emit(&self, input: CreateApiKeyRequest) -> Self::To {
+        CreateApiKeyDto {
+            owner_id: UserId::new(input.owner_id),
+            name: ApiKeyName::new(input.name),
+            scopes: input.scopes,
+            expires_at: input.expires_in.map(|secs| {
+                ApiKeyExpiresAt::new(OffsetDateTime::now_utc() + Duration::from_secs(secs))
+            }),
+        }
+    }
+}
+
+impl Intake<GetAllApiKeyRequest> for ApiKeyTransformer {
+    type To = GetAllApiKeyDto;
+    fn emit(&self, input: GetAllApiKeyRequest) -> Self::To {
+        GetAllApiKeyDto {
+            limit: input.limit,
+            offset: input.offset,
+        }
+    }
+}
+
+impl Intake<RevokeApiKeyRequest> for ApiKeyTransformer {
+    type To = RevokeApiKeyDto;
+    fn emit(&self, input: RevokeApiKeyRequest) -> Self::To {
+        RevokeApiKeyDto {
+            id: ApiKeyId::new(input.id),
+        }
+    }
+}
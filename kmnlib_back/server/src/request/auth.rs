@@ -0,0 +1,89 @@
+use crate::controller::Intake;
+use application::transfer::{ChangePasswordDto, LoginDto};
+use kernel::interface::event::EventMetadata;
+use kernel::prelude::entity::{PasswordResetToken, UserId};
+use serde::Deserialize;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    id: Uuid,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshRequest {
+    refresh_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    token: Uuid,
+    new_password: String,
+}
+
+#[derive(Debug)]
+pub struct PasswordResetRequest {
+    id: Uuid,
+}
+
+impl PasswordResetRequest {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+pub struct AuthTransformer;
+
+impl Intake<LoginRequest> for AuthTransformer {
+    type To = LoginDto;
+    fn emit(&self, input: LoginRequest) -> Self::To {
+        LoginDto {
+            id: UserId::new(input.id),
+            password: input.password,
+        }
+    }
+}
+
+impl Intake<RefreshRequest> for AuthTransformer {
+    type To = String;
+    fn emit(&self, input: RefreshRequest) -> Self::To {
+        input.refresh_token
+    }
+}
+
+impl Intake<(Uuid, ChangePasswordRequest, EventMetadata)> for AuthTransformer {
+    type To = (ChangePasswordDto, EventMetadata);
+    fn emit(&self, (id, req, metadata): (Uuid, ChangePasswordRequest, EventMetadata)) -> Self::To {
+        let dto = ChangePasswordDto {
+            id: UserId::new(id),
+            current_password: req.current_password,
+            new_password: req.new_password,
+        };
+        (dto, metadata)
+    }
+}
+
+impl Intake<PasswordResetRequest> for AuthTransformer {
+    type To = UserId;
+    fn emit(&self, input: PasswordResetRequest) -> Self::To {
+        UserId::new(input.id)
+    }
+}
+
+impl Intake<(ResetPasswordRequest, EventMetadata)> for AuthTransformer {
+    type To = (PasswordResetToken, String, EventMetadata);
+    fn emit(&self, (req, metadata): (ResetPasswordRequest, EventMetadata)) -> Self::To {
+        (
+            PasswordResetToken::new(req.token),
+            req.new_password,
+            metadata,
+        )
+    }
+}
@@ -1,7 +1,8 @@
 use crate::controller::Intake;
 use crate::mq::CommandOperation;
+use crate::request::IfMatch;
 use application::transfer::{GetAllBookDto, GetBookDto};
-use kernel::interface::event::BookEvent;
+use kernel::interface::event::{BookEvent, EventMetadata};
 use kernel::interface::mq::QueueInfo;
 use kernel::prelude::entity::{BookAmount, BookId, BookTitle, SelectLimit, SelectOffset};
 use serde::Deserialize;
@@ -52,37 +53,49 @@ impl GetBookRequest {
 
 pub struct BookTransformer;
 
-impl Intake<CreateBookRequest> for BookTransformer {
-    type To = BookEvent;
-    fn emit(&self, input: CreateBookRequest) -> Self::To {
-        Self::To::Create {
+impl Intake<(CreateBookRequest, EventMetadata)> for BookTransformer {
+    type To = (BookEvent, EventMetadata);
+    fn emit(&self, (input, metadata): (CreateBookRequest, EventMetadata)) -> Self::To {
+        let event = BookEvent::Create {
             id: BookId::new(Uuid::new_v4()),
             title: BookTitle::new(input.title),
             amount: BookAmount::new(input.amount),
-        }
+        };
+        (event, metadata)
     }
 }
 
-impl Intake<(Uuid, UpdateBookRequest)> for BookTransformer {
+impl Intake<(Uuid, UpdateBookRequest, IfMatch, EventMetadata)> for BookTransformer {
     type To = QueueInfo<CommandOperation>;
-    fn emit(&self, input: (Uuid, UpdateBookRequest)) -> Self::To {
-        let (id, input) = input;
-        let operation = CommandOperation::book(BookEvent::Update {
-            id: BookId::new(id),
-            title: input.title.map(BookTitle::new),
-            amount: input.amount.map(BookAmount::new),
-        });
-        Self::To::from(operation)
+    fn emit(&self, input: (Uuid, UpdateBookRequest, IfMatch, EventMetadata)) -> Self::To {
+        let (id, input, if_match, metadata) = input;
+        let operation = CommandOperation::book(
+            BookEvent::Update {
+                id: BookId::new(id),
+                title: input.title.map(BookTitle::new),
+                amount: input.amount.map(BookAmount::new),
+            },
+            if_match.version(),
+            metadata,
+        );
+        operation.into_queue_info()
     }
 }
 
-impl Intake<DeleteBookRequest> for BookTransformer {
+impl Intake<(DeleteBookRequest, IfMatch, EventMetadata)> for BookTransformer {
     type To = QueueInfo<CommandOperation>;
-    fn emit(&self, input: DeleteBookRequest) -> Self::To {
-        let operation = CommandOperation::book(BookEvent::Delete {
-            id: BookId::new(input.id),
-        });
-        Self::To::from(operation)
+    fn emit(
+        &self,
+        (input, if_match, metadata): (DeleteBookRequest, IfMatch, EventMetadata),
+    ) -> Self::To {
+        let operation = CommandOperation::book(
+            BookEvent::Delete {
+                id: BookId::new(input.id),
+            },
+            if_match.version(),
+            metadata,
+        );
+        operation.into_queue_info()
     }
 }
 
@@ -0,0 +1,170 @@
+use crate::handler::AppModule;
+use crate::middleware::Principal;
+use axum::async_trait;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+use axum::http::{Extensions, HeaderMap, HeaderName};
+use error_stack::{Report, ResultExt};
+use kernel::interface::event::EventMetadata;
+use kernel::prelude::entity::{ActorId, CausationId, ClientIp, CorrelationId, UserAgent};
+use kernel::KernelError;
+use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
+use uuid::Uuid;
+
+const TRUSTED_PROXIES: &str = "TRUSTED_PROXIES";
+
+static X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+static X_CORRELATION_ID: HeaderName = HeaderName::from_static("x-correlation-id");
+static X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// Metadata of the current request that is persisted alongside the events it causes
+#[derive(Debug, Clone)]
+pub struct RequestMetadata(EventMetadata);
+
+impl From<RequestMetadata> for EventMetadata {
+    fn from(value: RequestMetadata) -> Self {
+        value.0
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppModule> for RequestMetadata {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        module: &AppModule,
+    ) -> Result<Self, Self::Rejection> {
+        let headers = &parts.headers;
+        let request_id = header_uuid(headers, &X_REQUEST_ID).unwrap_or_else(Uuid::new_v4);
+        let correlation_id = header_uuid(headers, &X_CORRELATION_ID).unwrap_or(request_id);
+        let client_ip = client_ip(headers, &parts.extensions, module.trusted_proxies());
+        let user_agent = header_str(headers, &USER_AGENT);
+        let actor_id = parts
+            .extensions
+            .get::<Principal>()
+            .map(|principal| ActorId::new(principal.subject()));
+        Ok(Self(EventMetadata::new(
+            actor_id,
+            Some(CorrelationId::new(correlation_id)),
+            Some(CausationId::new(request_id)),
+            client_ip.map(ClientIp::new),
+            user_agent.map(UserAgent::new),
+        )))
+    }
+}
+
+/// Proxies whose `X-Forwarded-For` hops are believed
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies(Vec<IpAddr>);
+
+impl TrustedProxies {
+    /// Reads `TRUSTED_PROXIES` as a comma separated list of ips, none are trusted if unset
+    pub fn from_env() -> error_stack::Result<Self, KernelError> {
+        let Ok(value) = dotenvy::var(TRUSTED_PROXIES) else {
+            return Ok(Self::default());
+        };
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|ip| !ip.is_empty())
+            .map(|ip| {
+                ip.parse::<IpAddr>()
+                    .change_context_lazy(|| KernelError::Internal)
+                    .attach_printable_lazy(|| format!("Invalid {TRUSTED_PROXIES}: {ip}"))
+            })
+            .collect::<Result<_, Report<KernelError>>>()
+            .map(Self)
+    }
+
+    fn contains(&self, ip: &IpAddr) -> bool {
+        self.0.contains(ip)
+    }
+}
+
+/// The peer address, or when that is a trusted proxy, the right-most `X-Forwarded-For` hop
+/// not added by one. Hops left of it are up to the client, so they are never used
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trusted: &TrustedProxies,
+) -> Option<String> {
+    let ConnectInfo(peer) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+    let mut ip = peer.ip();
+    let hops = headers
+        .get_all(&X_FORWARDED_FOR)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect::<Vec<_>>();
+    for hop in hops.into_iter().rev() {
+        if !trusted.contains(&ip) {
+            break;
+        }
+        let Ok(hop) = hop.parse() else {
+            break;
+        };
+        ip = hop;
+    }
+    Some(ip.to_string())
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &HeaderName) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn header_uuid(headers: &HeaderMap, name: &HeaderName) -> Option<Uuid> {
+    header_str(headers, name).and_then(|value| Uuid::parse_str(value).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use axum::extract::ConnectInfo;
+    use axum::http::{Extensions, HeaderMap, HeaderValue};
+    use std::net::SocketAddr;
+
+    use crate::request::context::{client_ip, TrustedProxies, X_FORWARDED_FOR};
+
+    fn resolve(peer: &str, forwarded: &[&str], trusted: &[&str]) -> Option<String> {
+        let mut headers = HeaderMap::new();
+        for value in forwarded {
+            headers.append(&X_FORWARDED_FOR, HeaderValue::from_str(value).unwrap());
+        }
+        let mut extensions = Extensions::new();
+        extensions.insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        let trusted = TrustedProxies(trusted.iter().map(|ip| ip.parse().unwrap()).collect());
+        client_ip(&headers, &extensions, &trusted)
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let peer = "10.0.0.1:443";
+        assert_eq!(
+            resolve(peer, &["1.1.1.1"], &[]),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(
+            resolve(peer, &["6.6.6.6, 1.1.1.1"], &["10.0.0.1"]),
+            Some("1.1.1.1".to_string())
+        );
+        assert_eq!(
+            resolve(
+                peer,
+                &["6.6.6.6", "1.1.1.1, 10.0.0.2"],
+                &["10.0.0.1", "10.0.0.2"]
+            ),
+            Some("1.1.1.1".to_string())
+        );
+        assert_eq!(
+            resolve(peer, &["1.1.1.1, spoofed"], &["10.0.0.1"]),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(
+            resolve(peer, &[], &["10.0.0.1"]),
+            Some("10.0.0.1".to_string())
+        );
+    }
+}
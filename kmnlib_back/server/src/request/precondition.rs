@@ -0,0 +1,44 @@
+use crate::error::Problem;
+use axum::async_trait;
+use axum::extract::FromRequestParts;
+use axum::http::header::IF_MATCH;
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use kernel::prelude::entity::EventVersion;
+
+/// `If-Match` carrying an ETag previously returned for the resource.
+/// Absent or `*` imposes no precondition
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IfMatch(Option<i64>);
+
+impl IfMatch {
+    pub fn version<T>(&self) -> Option<EventVersion<T>> {
+        self.0.map(EventVersion::new)
+    }
+}
+
+#[async_trait]
+impl<S: Sync> FromRequestParts<S> for IfMatch {
+    type Rejection = Problem;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts.headers.get(IF_MATCH) else {
+            return Ok(Self(None));
+        };
+        let value = value.to_str().map(str::trim).unwrap_or_default();
+        if value == "*" {
+            return Ok(Self(None));
+        }
+        value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .and_then(|version| version.parse().ok())
+            .map(|version| Self(Some(version)))
+            .ok_or_else(|| Problem::new(StatusCode::BAD_REQUEST, "Invalid If-Match"))
+    }
+}
+
+/// Strong ETag of an entity, matched by [`IfMatch`]
+pub fn entity_tag<T>(version: &EventVersion<T>) -> String {
+    format!("\"{}\"", version.as_ref())
+}
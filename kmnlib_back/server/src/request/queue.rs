@@ -1,5 +1,12 @@
 use crate::controller::Intake;
+use crate::error::Problem;
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use kernel::interface::mq::{ErrorKind, InfoSortKey, Priority, SortOrder};
 use serde::Deserialize;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +22,76 @@ pub struct InfosRequest {
     pub target: InfoTarget,
     pub size: i64,
     pub offset: i64,
+    pub error: Option<String>,
+    pub data: Option<String>,
+    pub kind: Option<ErrorKind>,
+    /// Lower bound on when the last attempt ended, RFC 3339
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Upper bound on when the last attempt ended, RFC 3339
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    /// Jobs are listed in storage order without one
+    pub sort: Option<InfoSortKey>,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuarantinedInfosRequest {
+    pub size: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InfosFilterRequest {
+    pub target: InfoTarget,
+    /// Text the stack trace has to contain
+    pub error: Option<String>,
+    /// Text the serialized job data has to contain
+    pub data: Option<String>,
+    pub kind: Option<ErrorKind>,
+    /// Lower bound on when the last attempt ended, RFC 3339
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub from: Option<OffsetDateTime>,
+    /// Upper bound on when the last attempt ended, RFC 3339
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub to: Option<OffsetDateTime>,
+    /// Has to be set to purge every entry, when no filter is given
+    #[serde(default)]
+    pub all: bool,
+}
+
+impl InfosFilterRequest {
+    fn is_unfiltered(&self) -> bool {
+        self.error.is_none()
+            && self.data.is_none()
+            && self.kind.is_none()
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+}
+
+/// Filter of a purge, which refuses to match every entry unless `all=true` is passed
+#[derive(Debug)]
+pub struct PurgeInfosRequest(pub InfosFilterRequest);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for PurgeInfosRequest {
+    type Rejection = Problem;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(req) = Query::<InfosFilterRequest>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| Problem::new(StatusCode::BAD_REQUEST, rejection.body_text()))?;
+        if req.is_unfiltered() && !req.all {
+            return Err(Problem::new(
+                StatusCode::BAD_REQUEST,
+                "Pass a filter, or all=true to purge every entry",
+            ));
+        }
+        Ok(Self(req))
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,15 +103,21 @@ pub struct InfoRequestBody {
 pub enum InfoLengthTarget {
     #[serde(rename = "queued")]
     Queued,
+    #[serde(rename = "scheduled")]
+    Scheduled,
     #[serde(rename = "delayed")]
     Delayed,
     #[serde(rename = "failed")]
     Failed,
+    #[serde(rename = "quarantined")]
+    Quarantined,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct InfoLengthRequest {
     pub target: InfoLengthTarget,
+    /// Narrows `queued` down to a single lane
+    pub priority: Option<Priority>,
 }
 
 #[derive(Debug)]
@@ -1,7 +1,7 @@
 use crate::controller::Intake;
 use crate::request::{BookTransformer, UserTransformer};
 use application::transfer::{GetRentFromBookIdDto, GetRentFromUserIdDto};
-use kernel::interface::event::RentEvent;
+use kernel::interface::event::{EventMetadata, RentEvent};
 use kernel::prelude::entity::{BookId, UserId};
 use serde::Deserialize;
 use uuid::Uuid;
@@ -10,12 +10,29 @@ use uuid::Uuid;
 pub struct RentRequest {
     book_id: Uuid,
     user_id: Uuid,
+    /// Hand the command to the command worker and answer with the job id
+    #[serde(default, rename = "async")]
+    queued: bool,
+}
+
+impl RentRequest {
+    pub fn queued(&self) -> bool {
+        self.queued
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ReturnRequest {
     book_id: Uuid,
     user_id: Uuid,
+    #[serde(default, rename = "async")]
+    queued: bool,
+}
+
+impl ReturnRequest {
+    pub fn queued(&self) -> bool {
+        self.queued
+    }
 }
 
 #[derive(Debug)]
@@ -49,22 +66,40 @@ impl Intake<GetRentsRequest> for UserTransformer {
 
 pub struct RentTransformer;
 
-impl Intake<RentRequest> for RentTransformer {
-    type To = RentEvent;
-    fn emit(&self, RentRequest { book_id, user_id }: RentRequest) -> Self::To {
-        Self::To::Rent {
+impl Intake<(RentRequest, EventMetadata)> for RentTransformer {
+    type To = (RentEvent, EventMetadata);
+    fn emit(
+        &self,
+        (
+            RentRequest {
+                book_id, user_id, ..
+            },
+            metadata,
+        ): (RentRequest, EventMetadata),
+    ) -> Self::To {
+        let event = RentEvent::Rent {
             book_id: BookId::new(book_id),
             user_id: UserId::new(user_id),
-        }
+        };
+        (event, metadata)
     }
 }
 
-impl Intake<ReturnRequest> for RentTransformer {
-    type To = RentEvent;
-    fn emit(&self, ReturnRequest { book_id, user_id }: ReturnRequest) -> Self::To {
-        Self::To::Return {
+impl Intake<(ReturnRequest, EventMetadata)> for RentTransformer {
+    type To = (RentEvent, EventMetadata);
+    fn emit(
+        &self,
+        (
+            ReturnRequest {
+                book_id, user_id, ..
+            },
+            metadata,
+        ): (ReturnRequest, EventMetadata),
+    ) -> Self::To {
+        let event = RentEvent::Return {
             book_id: BookId::new(book_id),
             user_id: UserId::new(user_id),
-        }
+        };
+        (event, metadata)
     }
 }
@@ -1,9 +1,12 @@
 use crate::controller::Intake;
 use crate::mq::CommandOperation;
+use crate::request::IfMatch;
 use application::transfer::{GetAllUserDto, GetUserDto};
-use kernel::interface::event::UserEvent;
+use kernel::interface::event::{EventMetadata, UserEvent};
 use kernel::interface::mq::QueueInfo;
-use kernel::prelude::entity::{SelectLimit, SelectOffset, UserId, UserName, UserRentLimit};
+use kernel::prelude::entity::{
+    SelectLimit, SelectOffset, UserId, UserName, UserRentLimit, UserRole,
+};
 use serde::Deserialize;
 use uuid::Uuid;
 
@@ -19,6 +22,11 @@ pub struct UpdateUserRequest {
     rent_limit: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ChangeRoleRequest {
+    role: UserRole,
+}
+
 #[derive(Debug)]
 pub struct DeleteUserRequest {
     id: Uuid,
@@ -51,36 +59,69 @@ impl GetUserRequest {
 
 pub struct UserTransformer;
 
-impl Intake<CreateUserRequest> for UserTransformer {
-    type To = UserEvent;
-    fn emit(&self, input: CreateUserRequest) -> Self::To {
-        Self::To::Create {
+impl Intake<(CreateUserRequest, EventMetadata)> for UserTransformer {
+    type To = (UserEvent, EventMetadata);
+    fn emit(&self, (input, metadata): (CreateUserRequest, EventMetadata)) -> Self::To {
+        let event = UserEvent::Create {
             id: UserId::new(Uuid::new_v4()),
             name: UserName::new(input.name),
             rent_limit: UserRentLimit::new(input.rent_limit),
-        }
+        };
+        (event, metadata)
     }
 }
 
-impl Intake<(Uuid, UpdateUserRequest)> for UserTransformer {
+impl Intake<(Uuid, UpdateUserRequest, IfMatch, EventMetadata)> for UserTransformer {
     type To = QueueInfo<CommandOperation>;
-    fn emit(&self, (id, req): (Uuid, UpdateUserRequest)) -> Self::To {
-        let operation = CommandOperation::user(UserEvent::Update {
-            id: UserId::new(id),
-            name: req.name.map(UserName::new),
-            rent_limit: req.rent_limit.map(UserRentLimit::new),
-        });
-        Self::To::from(operation)
+    fn emit(
+        &self,
+        (id, req, if_match, metadata): (Uuid, UpdateUserRequest, IfMatch, EventMetadata),
+    ) -> Self::To {
+        let operation = CommandOperation::user(
+            UserEvent::Update {
+                id: UserId::new(id),
+                name: req.name.map(UserName::new),
+                rent_limit: req.rent_limit.map(UserRentLimit::new),
+            },
+            if_match.version(),
+            metadata,
+        );
+        operation.into_queue_info()
     }
 }
 
-impl Intake<DeleteUserRequest> for UserTransformer {
+impl Intake<(Uuid, ChangeRoleRequest, IfMatch, EventMetadata)> for UserTransformer {
     type To = QueueInfo<CommandOperation>;
-    fn emit(&self, input: DeleteUserRequest) -> Self::To {
-        let operation = CommandOperation::user(UserEvent::Delete {
-            id: UserId::new(input.id),
-        });
-        Self::To::from(operation)
+    fn emit(
+        &self,
+        (id, req, if_match, metadata): (Uuid, ChangeRoleRequest, IfMatch, EventMetadata),
+    ) -> Self::To {
+        let operation = CommandOperation::user(
+            UserEvent::ChangeRole {
+                id: UserId::new(id),
+                role: req.role,
+            },
+            if_match.version(),
+            metadata,
+        );
+        operation.into_queue_info()
+    }
+}
+
+impl Intake<(DeleteUserRequest, IfMatch, EventMetadata)> for UserTransformer {
+    type To = QueueInfo<CommandOperation>;
+    fn emit(
+        &self,
+        (input, if_match, metadata): (DeleteUserRequest, IfMatch, EventMetadata),
+    ) -> Self::To {
+        let operation = CommandOperation::user(
+            UserEvent::Delete {
+                id: UserId::new(input.id),
+            },
+            if_match.version(),
+            metadata,
+        );
+        operation.into_queue_info()
     }
 }
 
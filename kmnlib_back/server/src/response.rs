@@ -1,6 +1,12 @@
+mod api_key;
+mod auth;
 mod book;
+mod health;
+mod metrics;
 mod queue;
 mod rent;
 mod user;
 
-pub use crate::response::{book::*, queue::*, rent::*, user::*};
+pub use crate::response::{
+    api_key::*, auth::*, book::*, health::*, metrics::*, queue::*, rent::*, user::*,
+};
@@ -0,0 +1,93 @@
+use crate::controller::Exhaust;
+use crate::error::Problem;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use kernel::prelude::entity::{
+    ApiKey, ApiKeyExpiresAt, ApiKeyId, ApiKeyName, ApiKeyRevokedAt, ApiKeyScope, CreatedAt,
+    DestructApiKey, UserId,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    id: ApiKeyId,
+    owner_id: UserId,
+    name: ApiKeyName,
+    scopes: Vec<ApiKeyScope>,
+    expires_at: Option<ApiKeyExpiresAt>,
+    revoked_at: Option<ApiKeyRevokedAt>,
+    created_at: CreatedAt<ApiKey>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(value: ApiKey) -> Self {
+        let DestructApiKey {
+            id,
+            owner_id,
+            name,
+            scopes,
+            expires_at,
+            revoked_at,
+            created_at,
+            ..
+        } = value.into_destruct();
+        Self {
+            id,
+            owner_id,
+            name,
+            scopes,
+            expires_at,
+            revoked_at,
+            created_at,
+        }
+    }
+}
+
+impl IntoResponse for ApiKeyResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, axum::Json(self)).into_response()
+    }
+}
+
+/// The raw key is only ever returned here
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKeyResponse {
+    key: String,
+    #[serde(flatten)]
+    api_key: ApiKeyResponse,
+}
+
+impl IntoResponse for CreatedApiKeyResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::CREATED, axum::Json(self)).into_response()
+    }
+}
+
+pub struct ApiKeyPresenter;
+
+impl Exhaust<Option<(ApiKey, String)>> for ApiKeyPresenter {
+    type To = Result<CreatedApiKeyResponse, Problem>;
+    fn emit(&self, input: Option<(ApiKey, String)>) -> Self::To {
+        let (api_key, key) = input.ok_or_else(|| {
+            Problem::new(StatusCode::UNPROCESSABLE_ENTITY, "Owner does not exist")
+        })?;
+        Ok(CreatedApiKeyResponse {
+            key,
+            api_key: ApiKeyResponse::from(api_key),
+        })
+    }
+}
+
+impl Exhaust<Vec<ApiKey>> for ApiKeyPresenter {
+    type To = axum::Json<Vec<ApiKeyResponse>>;
+    fn emit(&self, input: Vec<ApiKey>) -> Self::To {
+        axum::Json(input.into_iter().map(ApiKeyResponse::from).collect())
+    }
+}
+
+impl Exhaust<Option<ApiKey>> for ApiKeyPresenter {
+    type To = Option<ApiKeyResponse>;
+    fn emit(&self, input: Option<ApiKey>) -> Self::To {
+        input.map(ApiKeyResponse::from)
+    }
+}
@@ -0,0 +1,84 @@
+use crate::controller::Exhaust;
+use crate::error::Problem;
+use crate::middleware::{TokenPair, ACCESS_TOKEN_TTL};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use kernel::prelude::entity::UserId;
+use serde::Serialize;
+use std::time::Duration;
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    token_type: &'static str,
+    expires_in: u64,
+}
+
+impl IntoResponse for TokenResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, axum::Json(self)).into_response()
+    }
+}
+
+/// The token itself is delivered to the user, never part of the response
+#[derive(Debug, Serialize)]
+pub struct PasswordResetResponse {
+    expires_in: u64,
+}
+
+impl IntoResponse for PasswordResetResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::ACCEPTED, axum::Json(self)).into_response()
+    }
+}
+
+pub struct AuthPresenter;
+
+impl Exhaust<Option<TokenPair>> for AuthPresenter {
+    type To = Result<TokenResponse, Problem>;
+    fn emit(&self, input: Option<TokenPair>) -> Self::To {
+        let TokenPair {
+            access_token,
+            refresh_token,
+        } = input.ok_or_else(|| Problem::unauthorized("Invalid credentials"))?;
+        Ok(TokenResponse {
+            access_token,
+            refresh_token,
+            token_type: "Bearer",
+            expires_in: ACCESS_TOKEN_TTL.as_secs(),
+        })
+    }
+}
+
+impl Exhaust<Option<UserId>> for AuthPresenter {
+    type To = Result<StatusCode, Problem>;
+    fn emit(&self, input: Option<UserId>) -> Self::To {
+        input
+            .map(|_| StatusCode::NO_CONTENT)
+            .ok_or_else(|| Problem::new(StatusCode::BAD_REQUEST, "Invalid or expired reset token"))
+    }
+}
+
+impl Exhaust<UserId> for AuthPresenter {
+    type To = StatusCode;
+    fn emit(&self, _: UserId) -> Self::To {
+        StatusCode::NO_CONTENT
+    }
+}
+
+impl Exhaust<Duration> for AuthPresenter {
+    type To = PasswordResetResponse;
+    fn emit(&self, input: Duration) -> Self::To {
+        PasswordResetResponse {
+            expires_in: input.as_secs(),
+        }
+    }
+}
+
+impl Exhaust<()> for AuthPresenter {
+    type To = StatusCode;
+    fn emit(&self, _: ()) -> Self::To {
+        StatusCode::NO_CONTENT
+    }
+}
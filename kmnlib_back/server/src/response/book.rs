@@ -1,7 +1,11 @@
 use crate::controller::Exhaust;
+use crate::request::entity_tag;
+use crate::response::AcceptedJobResponse;
+use axum::http::header::ETAG;
 use axum::response::{IntoResponse, Response};
-use kernel::prelude::entity::{Book, BookAmount, BookId, BookTitle, DestructBook};
+use kernel::prelude::entity::{Book, BookAmount, BookId, BookTitle, DestructBook, EventVersion};
 use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 pub struct CreatedBookResponse {
@@ -19,20 +23,23 @@ pub struct BookResponse {
     id: BookId,
     title: BookTitle,
     amount: BookAmount,
+    #[serde(skip)]
+    version: EventVersion<Book>,
 }
 
 impl IntoResponse for BookResponse {
     fn into_response(self) -> Response {
-        (axum::http::StatusCode::OK, axum::Json(self)).into_response()
+        let etag = entity_tag(&self.version);
+        (axum::http::StatusCode::OK, [(ETAG, etag)], axum::Json(self)).into_response()
     }
 }
 
 pub struct BookPresenter;
 
-impl Exhaust<()> for BookPresenter {
-    type To = ();
-    fn emit(&self, input: ()) -> Self::To {
-        input
+impl Exhaust<Uuid> for BookPresenter {
+    type To = AcceptedJobResponse;
+    fn emit(&self, input: Uuid) -> Self::To {
+        AcceptedJobResponse::new(input)
     }
 }
 
@@ -48,9 +55,18 @@ impl Exhaust<Option<Book>> for BookPresenter {
     fn emit(&self, input: Option<Book>) -> Self::To {
         input.map(|input| {
             let DestructBook {
-                id, title, amount, ..
+                id,
+                title,
+                amount,
+                version,
+                ..
             } = input.into_destruct();
-            BookResponse { id, title, amount }
+            BookResponse {
+                id,
+                title,
+                amount,
+                version,
+            }
         })
     }
 }
@@ -62,9 +78,18 @@ impl Exhaust<Vec<Book>> for BookPresenter {
             .into_iter()
             .map(|book| {
                 let DestructBook {
-                    id, title, amount, ..
+                    id,
+                    title,
+                    amount,
+                    version,
+                    ..
                 } = book.into_destruct();
-                BookResponse { id, title, amount }
+                BookResponse {
+                    id,
+                    title,
+                    amount,
+                    version,
+                }
             })
             .collect::<Vec<_>>();
 
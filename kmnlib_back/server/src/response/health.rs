@@ -0,0 +1,184 @@
+use crate::controller::Exhaust;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use kernel::interface::mq::{DestructWorkerState, WorkerState};
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+pub enum HealthStatus {
+    #[serde(rename = "up")]
+    Up,
+    #[serde(rename = "down")]
+    Down,
+}
+
+impl HealthStatus {
+    fn from_healthy(healthy: bool) -> Self {
+        if healthy {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ComponentHealth {
+    status: HealthStatus,
+    latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ComponentHealth {
+    pub fn new(latency_ms: f64, error: Option<String>) -> Self {
+        Self {
+            status: HealthStatus::from_healthy(error.is_none()),
+            latency_ms,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkerHealth {
+    member: String,
+    status: HealthStatus,
+    alive: bool,
+    stuck: bool,
+    last_heartbeat: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkersHealth {
+    status: HealthStatus,
+    workers: Vec<WorkerHealth>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthComponents {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    postgres: Option<ComponentHealth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    redis: Option<ComponentHealth>,
+    command_worker: WorkersHealth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    status: HealthStatus,
+    components: HealthComponents,
+}
+
+impl IntoResponse for HealthResponse {
+    fn into_response(self) -> Response {
+        let status = match self.status {
+            HealthStatus::Up => StatusCode::OK,
+            HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status, axum::Json(self)).into_response()
+    }
+}
+
+pub struct HealthReport {
+    pub postgres: Option<ComponentHealth>,
+    pub redis: Option<ComponentHealth>,
+    pub command_worker: Vec<WorkerState>,
+}
+
+pub struct HealthPresenter;
+
+impl Exhaust<HealthReport> for HealthPresenter {
+    type To = HealthResponse;
+    fn emit(&self, input: HealthReport) -> Self::To {
+        let workers_up = !input.command_worker.is_empty()
+            && input.command_worker.iter().all(WorkerState::is_healthy);
+        let workers = input
+            .command_worker
+            .into_iter()
+            .map(|worker| {
+                let status = HealthStatus::from_healthy(worker.is_healthy());
+                let DestructWorkerState {
+                    member,
+                    alive,
+                    stuck,
+                    last_heartbeat,
+                } = worker.into_destruct();
+                WorkerHealth {
+                    member,
+                    status,
+                    alive,
+                    stuck,
+                    last_heartbeat,
+                }
+            })
+            .collect();
+        let command_worker = WorkersHealth {
+            status: HealthStatus::from_healthy(workers_up),
+            workers,
+        };
+        let healthy = [&input.postgres, &input.redis]
+            .into_iter()
+            .flatten()
+            .all(|component| component.status == HealthStatus::Up)
+            && workers_up;
+        HealthResponse {
+            status: HealthStatus::from_healthy(healthy),
+            components: HealthComponents {
+                postgres: input.postgres,
+                redis: input.redis,
+                command_worker,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::body::to_bytes;
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+    use kernel::interface::mq::WorkerState;
+    use serde_json::Value;
+    use time::{Duration, OffsetDateTime};
+
+    use crate::controller::Exhaust;
+    use crate::response::{ComponentHealth, HealthPresenter, HealthReport};
+
+    async fn ready(command_worker: Vec<WorkerState>) -> (StatusCode, Value) {
+        let report = HealthReport {
+            postgres: Some(ComponentHealth::new(1.0, None)),
+            redis: Some(ComponentHealth::new(1.0, None)),
+            command_worker,
+        };
+        let response = HealthPresenter.emit(report).into_response();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_stale_worker() {
+        let now = OffsetDateTime::now_utc();
+        let fresh = WorkerState::new("fresh".to_string(), true, false, now);
+        let (status, body) = ready(vec![fresh.clone()]).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "up");
+
+        let stale = WorkerState::new("stale".to_string(), true, true, now - Duration::minutes(10));
+        let (status, body) = ready(vec![fresh, stale]).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "down");
+        assert_eq!(body["components"]["postgres"]["status"], "up");
+        let workers = &body["components"]["command_worker"];
+        assert_eq!(workers["status"], "down");
+        assert_eq!(workers["workers"][0]["status"], "up");
+        assert_eq!(workers["workers"][1]["member"], "stale");
+        assert_eq!(workers["workers"][1]["status"], "down");
+        assert_eq!(workers["workers"][1]["stuck"], true);
+
+        let (status, _) = ready(Vec::new()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
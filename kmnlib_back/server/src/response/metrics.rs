@@ -0,0 +1,20 @@
+use crate::controller::Exhaust;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{IntoResponse, Response};
+
+pub struct MetricsResponse(String);
+
+impl IntoResponse for MetricsResponse {
+    fn into_response(self) -> Response {
+        ([(CONTENT_TYPE, "text/plain; version=0.0.4")], self.0).into_response()
+    }
+}
+
+pub struct MetricsPresenter;
+
+impl Exhaust<String> for MetricsPresenter {
+    type To = MetricsResponse;
+    fn emit(&self, input: String) -> Self::To {
+        MetricsResponse(input)
+    }
+}
@@ -1,17 +1,115 @@
 use crate::controller::TryExhaust;
 use crate::mq::CommandOperation;
-use axum::response::IntoResponse;
+use axum::http::header::LOCATION;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use error_stack::{Report, ResultExt};
-use kernel::interface::mq::{DestructErroredInfo, ErroredInfo};
+use kernel::interface::mq::{
+    AttemptRecord, DestructAttemptRecord, DestructErroredInfo, DestructJobStatus,
+    DestructQuarantinedInfo, DestructScheduleState, ErrorKind, ErrorOperation, ErroredInfo,
+    JobState, JobStatus, Priority, QuarantinedInfo, ScheduleState,
+};
 use kernel::KernelError;
 use serde::Serialize;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
+#[derive(Debug, Serialize)]
+pub struct AcceptedJobResponse {
+    id: Uuid,
+}
+
+impl AcceptedJobResponse {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+impl IntoResponse for AcceptedJobResponse {
+    fn into_response(self) -> Response {
+        let location = format!("/queue/jobs/{}", self.id);
+        (
+            StatusCode::ACCEPTED,
+            [(LOCATION, location)],
+            axum::Json(self),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobStatusResponse {
+    id: Uuid,
+    state: JobState,
+    attempts: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    queued_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+    #[serde(
+        with = "time::serde::rfc3339::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    run_at: Option<OffsetDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submitter: Option<Uuid>,
+}
+
+impl IntoResponse for JobStatusResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, axum::Json(self)).into_response()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct InfoResponse {
     pub id: Uuid,
     pub data: String,
     pub stack_trace: String,
+    pub kind: Option<ErrorKind>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub queued_at: Option<OffsetDateTime>,
+    pub attempts: Vec<AttemptResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AttemptResponse {
+    attempt: i64,
+    worker: String,
+    #[serde(with = "time::serde::rfc3339")]
+    started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    finished_at: OffsetDateTime,
+    operation: ErrorOperation,
+    kind: Option<ErrorKind>,
+    error: String,
+}
+
+impl From<AttemptRecord> for AttemptResponse {
+    fn from(value: AttemptRecord) -> Self {
+        let DestructAttemptRecord {
+            attempt,
+            worker,
+            started_at,
+            finished_at,
+            operation,
+            kind,
+            error,
+        } = value.into_destruct();
+        Self {
+            attempt,
+            worker,
+            started_at,
+            finished_at,
+            operation,
+            kind,
+            error,
+        }
+    }
 }
 
 impl IntoResponse for InfoResponse {
@@ -20,6 +118,22 @@ impl IntoResponse for InfoResponse {
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct QuarantinedInfoResponse {
+    id: Uuid,
+    priority: Priority,
+    raw: String,
+    error: String,
+    #[serde(with = "time::serde::rfc3339")]
+    quarantined_at: OffsetDateTime,
+}
+
+impl IntoResponse for QuarantinedInfoResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, axum::Json(self)).into_response()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct InfoLengthResponse {
     pub length: u64,
@@ -31,6 +145,32 @@ impl IntoResponse for InfoLengthResponse {
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct ScheduleResponse {
+    name: String,
+    expression: String,
+    #[serde(with = "time::serde::rfc3339::option")]
+    last_run: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    next_run: Option<OffsetDateTime>,
+    last_job: Option<Uuid>,
+}
+
+/// Number of jobs a bulk retry or purge touched
+#[derive(Debug)]
+pub struct AffectedInfos(pub usize);
+
+#[derive(Debug, Serialize)]
+pub struct AffectedInfosResponse {
+    pub count: u64,
+}
+
+impl IntoResponse for AffectedInfosResponse {
+    fn into_response(self) -> Response {
+        (StatusCode::OK, axum::Json(self)).into_response()
+    }
+}
+
 pub struct QueuePresenter;
 
 impl TryExhaust<ErroredInfo<CommandOperation>> for QueuePresenter {
@@ -42,12 +182,19 @@ impl TryExhaust<ErroredInfo<CommandOperation>> for QueuePresenter {
             id,
             data,
             stack_trace,
+            kind,
+            queued_at,
+            attempts,
+            ..
         } = input.into_destruct();
         let data = serde_json::to_string(&data).change_context_lazy(|| KernelError::Internal)?;
         Ok(InfoResponse {
             id,
             data,
             stack_trace,
+            kind,
+            queued_at,
+            attempts: attempts.into_iter().map(AttemptResponse::from).collect(),
         })
     }
 }
@@ -72,6 +219,47 @@ impl TryExhaust<Option<ErroredInfo<CommandOperation>>> for QueuePresenter {
     }
 }
 
+impl TryExhaust<QuarantinedInfo> for QueuePresenter {
+    type To = QuarantinedInfoResponse;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: QuarantinedInfo) -> Result<Self::To, Self::Error> {
+        let DestructQuarantinedInfo {
+            id,
+            priority,
+            raw,
+            error,
+            quarantined_at,
+        } = input.into_destruct();
+        Ok(QuarantinedInfoResponse {
+            id,
+            priority,
+            raw,
+            error,
+            quarantined_at,
+        })
+    }
+}
+
+impl TryExhaust<Vec<QuarantinedInfo>> for QueuePresenter {
+    type To = axum::Json<Vec<QuarantinedInfoResponse>>;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: Vec<QuarantinedInfo>) -> Result<Self::To, Self::Error> {
+        let infos = input
+            .into_iter()
+            .map(|info| self.emit(info))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(axum::Json(infos))
+    }
+}
+
+impl TryExhaust<Option<QuarantinedInfo>> for QueuePresenter {
+    type To = Option<QuarantinedInfoResponse>;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: Option<QuarantinedInfo>) -> Result<Self::To, Self::Error> {
+        input.map(|info| self.emit(info)).transpose()
+    }
+}
+
 impl TryExhaust<usize> for QueuePresenter {
     type To = InfoLengthResponse;
     type Error = Report<KernelError>;
@@ -80,3 +268,90 @@ impl TryExhaust<usize> for QueuePresenter {
         Ok(InfoLengthResponse { length })
     }
 }
+
+impl TryExhaust<Option<JobStatus>> for QueuePresenter {
+    type To = Option<JobStatusResponse>;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: Option<JobStatus>) -> Result<Self::To, Self::Error> {
+        Ok(input.map(|status| {
+            let DestructJobStatus {
+                id,
+                state,
+                attempts,
+                queued_at,
+                updated_at,
+                run_at,
+                result,
+                error,
+                submitter,
+            } = status.into_destruct();
+            JobStatusResponse {
+                id,
+                state,
+                attempts,
+                queued_at,
+                updated_at,
+                run_at,
+                result,
+                error,
+                submitter,
+            }
+        }))
+    }
+}
+
+impl TryExhaust<AffectedInfos> for QueuePresenter {
+    type To = AffectedInfosResponse;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: AffectedInfos) -> Result<Self::To, Self::Error> {
+        let count = u64::try_from(input.0).change_context_lazy(|| KernelError::Internal)?;
+        Ok(AffectedInfosResponse { count })
+    }
+}
+
+impl TryExhaust<Option<Uuid>> for QueuePresenter {
+    type To = Option<AcceptedJobResponse>;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: Option<Uuid>) -> Result<Self::To, Self::Error> {
+        Ok(input.map(AcceptedJobResponse::new))
+    }
+}
+
+impl TryExhaust<Vec<ScheduleState>> for QueuePresenter {
+    type To = axum::Json<Vec<ScheduleResponse>>;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: Vec<ScheduleState>) -> Result<Self::To, Self::Error> {
+        let schedules = input
+            .into_iter()
+            .map(|state| {
+                let DestructScheduleState {
+                    name,
+                    expression,
+                    last_run,
+                    next_run,
+                    last_job,
+                } = state.into_destruct();
+                ScheduleResponse {
+                    name,
+                    expression,
+                    last_run,
+                    next_run,
+                    last_job,
+                }
+            })
+            .collect();
+        Ok(axum::Json(schedules))
+    }
+}
+
+impl TryExhaust<bool> for QueuePresenter {
+    type To = StatusCode;
+    type Error = Report<KernelError>;
+    fn emit(&self, input: bool) -> Result<Self::To, Self::Error> {
+        Ok(if input {
+            StatusCode::NO_CONTENT
+        } else {
+            StatusCode::NOT_FOUND
+        })
+    }
+}
@@ -1,8 +1,10 @@
 use crate::controller::Exhaust;
+use crate::response::AcceptedJobResponse;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use kernel::prelude::entity::{BookId, DestructRent, Rent, ReturnedAt, UserId};
 use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 pub struct RentResponse {
@@ -19,10 +21,11 @@ impl IntoResponse for RentResponse {
 
 pub struct RentPresenter;
 
-impl Exhaust<()> for RentPresenter {
-    type To = ();
-    fn emit(&self, input: ()) -> Self::To {
-        input
+/// `None` when the command was handled in the request, the job id when it was queued
+impl Exhaust<Option<Uuid>> for RentPresenter {
+    type To = Option<AcceptedJobResponse>;
+    fn emit(&self, input: Option<Uuid>) -> Self::To {
+        input.map(AcceptedJobResponse::new)
     }
 }
 
@@ -1,8 +1,14 @@
 use crate::controller::Exhaust;
+use crate::request::entity_tag;
+use crate::response::AcceptedJobResponse;
+use axum::http::header::ETAG;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use kernel::prelude::entity::{DestructUser, User, UserId, UserName, UserRentLimit};
+use kernel::prelude::entity::{
+    DestructUser, EventVersion, User, UserId, UserName, UserRentLimit, UserRole,
+};
 use serde::Serialize;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize)]
 pub struct CreatedUserResponse {
@@ -20,20 +26,24 @@ pub struct UserResponse {
     id: UserId,
     name: UserName,
     rent_limit: UserRentLimit,
+    role: UserRole,
+    #[serde(skip)]
+    version: EventVersion<User>,
 }
 
 impl IntoResponse for UserResponse {
     fn into_response(self) -> Response {
-        (StatusCode::OK, axum::Json(self)).into_response()
+        let etag = entity_tag(&self.version);
+        (StatusCode::OK, [(ETAG, etag)], axum::Json(self)).into_response()
     }
 }
 
 pub struct UserPresenter;
 
-impl Exhaust<()> for UserPresenter {
-    type To = ();
-    fn emit(&self, input: ()) -> Self::To {
-        input
+impl Exhaust<Uuid> for UserPresenter {
+    type To = AcceptedJobResponse;
+    fn emit(&self, input: Uuid) -> Self::To {
+        AcceptedJobResponse::new(input)
     }
 }
 
@@ -52,12 +62,16 @@ impl Exhaust<Option<User>> for UserPresenter {
                 id,
                 name,
                 rent_limit,
+                role,
+                version,
                 ..
             } = input.into_destruct();
             UserResponse {
                 id,
                 name,
                 rent_limit,
+                role,
+                version,
             }
         })
     }
@@ -73,12 +87,16 @@ impl Exhaust<Vec<User>> for UserPresenter {
                     id,
                     name,
                     rent_limit,
+                    role,
+                    version,
                     ..
                 } = user.into_destruct();
                 UserResponse {
                     id,
                     name,
                     rent_limit,
+                    role,
+                    version,
                 }
             })
             .collect::<Vec<_>>();
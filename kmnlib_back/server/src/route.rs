@@ -1,6 +1,10 @@
+mod admin;
+mod auth;
 mod book;
+mod health;
+mod metrics;
 mod queue;
 mod rent;
 mod user;
 
-pub use self::{book::*, queue::*, rent::*, user::*};
+pub use self::{admin::*, auth::*, book::*, health::*, metrics::*, queue::*, rent::*, user::*};
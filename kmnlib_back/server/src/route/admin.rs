@@ -0,0 +1,77 @@
+use crate::controller::Controller;
+use crate::error::ErrorStatus;
+use crate::handler::AppModule;
+use crate::middleware::Principal;
+use crate::request::{
+    ApiKeyTransformer, CreateApiKeyRequest, GetAllApiKeyRequest, RevokeApiKeyRequest,
+};
+use crate::response::{ApiKeyPresenter, ApiKeyResponse};
+use application::policy::Permission;
+use application::service::ApiKeyService;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use uuid::Uuid;
+
+pub trait AdminRouter {
+    fn route_admin(self) -> Self;
+}
+
+impl AdminRouter for Router<AppModule> {
+    fn route_admin(self) -> Self {
+        self.route(
+            "/admin/api-keys",
+            get(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Query(req): Query<GetAllApiKeyRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageApiKeys)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(ApiKeyTransformer, ApiKeyPresenter)
+                        .intake(req)
+                        .handle(|dto| module.handler().pgpool().get_api_keys(dto))
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            )
+            .post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Json(req): Json<CreateApiKeyRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageApiKeys)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(ApiKeyTransformer, ApiKeyPresenter)
+                        .intake(req)
+                        .handle(|dto| module.handler().pgpool().create_api_key(dto))
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/admin/api-keys/:id",
+            delete(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    principal
+                        .authorize(Permission::ManageApiKeys)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(ApiKeyTransformer, ApiKeyPresenter)
+                        .intake(RevokeApiKeyRequest::new(id))
+                        .handle(|dto| module.handler().pgpool().revoke_api_key(dto))
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(ApiKeyResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
+            ),
+        )
+    }
+}
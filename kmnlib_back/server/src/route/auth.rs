@@ -0,0 +1,232 @@
+use crate::controller::Controller;
+use crate::error::ErrorStatus;
+use crate::handler::AppModule;
+use crate::middleware::{Claims, Principal, TokenKind, TokenPair};
+use crate::request::{
+    AuthTransformer, ChangePasswordRequest, LoginRequest, PasswordResetRequest, RefreshRequest,
+    RequestMetadata, ResetPasswordRequest,
+};
+use crate::response::AuthPresenter;
+use crate::route::user::target_role;
+use application::policy::Permission;
+use application::service::{CredentialService, GetUserService, SessionService};
+use application::transfer::{GetUserDto, ResetPasswordDto};
+use axum::extract::{Path, State};
+use axum::routing::{post, put};
+use axum::{Json, Router};
+use kernel::prelude::entity::UserId;
+use kernel::KernelError;
+use uuid::Uuid;
+
+pub trait AuthRouter {
+    /// Routes reachable without an access token
+    fn route_auth(self) -> Self;
+    fn route_session(self) -> Self;
+}
+
+impl AuthRouter for Router<AppModule> {
+    fn route_auth(self) -> Self {
+        self.route(
+            "/auth/login",
+            post(
+                |State(module): State<AppModule>, Json(req): Json<LoginRequest>| async move {
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake(req)
+                        .handle(|dto| async move {
+                            match module.handler().pgpool().login(dto).await? {
+                                Some(user) => issue(&module, user.id()).await.map(Some),
+                                None => Ok(None),
+                            }
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/auth/refresh",
+            post(
+                |State(module): State<AppModule>, Json(req): Json<RefreshRequest>| async move {
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake(req)
+                        .handle(|token| refresh(module, token))
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/auth/password-reset",
+            post(
+                |State(module): State<AppModule>,
+                 metadata: RequestMetadata,
+                 Json(req): Json<ResetPasswordRequest>| async move {
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake((req, metadata.into()))
+                        .handle(|(token, new_password, metadata)| async move {
+                            let Some(id) = module
+                                .handler()
+                                .redis_pool()
+                                .consume_password_reset(&token)
+                                .await?
+                            else {
+                                return Ok(None);
+                            };
+                            let id = module
+                                .handler()
+                                .pgpool()
+                                .reset_password(ResetPasswordDto { id, new_password }, metadata)
+                                .await?;
+                            module
+                                .handler()
+                                .redis_pool()
+                                .revoke_user_tokens(&id)
+                                .await?;
+                            Ok::<_, error_stack::Report<KernelError>>(Some(id))
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+    }
+
+    fn route_session(self) -> Self {
+        self.route(
+            "/auth/logout",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Json(req): Json<RefreshRequest>| async move {
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake(req)
+                        .handle(|token| async move {
+                            let redis = module.handler().redis_pool();
+                            if let Some((id, ttl)) = principal.claims().and_then(Claims::revocable) {
+                                redis.revoke_token(&id, &ttl).await?;
+                            }
+                            let refresh = module
+                                .jwt()
+                                .verify(&token, TokenKind::Refresh)
+                                .ok()
+                                .filter(|claims| claims.subject() == principal.subject())
+                                .and_then(|claims| claims.revocable());
+                            if let Some((id, ttl)) = refresh {
+                                redis.revoke_token(&id, &ttl).await?;
+                            }
+                            Ok::<_, error_stack::Report<KernelError>>(())
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/users/:id/password",
+            put(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 metadata: RequestMetadata,
+                 Json(req): Json<ChangePasswordRequest>| async move {
+                    principal
+                        .authorize(Permission::ChangePassword {
+                            user_id: &UserId::new(id),
+                        })
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake((id, req, metadata.into()))
+                        .handle(|(dto, metadata)| async move {
+                            let id = module
+                                .handler()
+                                .pgpool()
+                                .change_password(dto, metadata)
+                                .await?;
+                            module
+                                .handler()
+                                .redis_pool()
+                                .revoke_user_tokens(&id)
+                                .await?;
+                            Ok::<_, error_stack::Report<KernelError>>(id)
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/users/:id/password-reset",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    let target = target_role(&module, id).await?;
+                    principal
+                        .authorize(Permission::ManageUsers { target })
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(AuthTransformer, AuthPresenter)
+                        .intake(PasswordResetRequest::new(id))
+                        .handle(|id| async move {
+                            module
+                                .handler()
+                                .redis_pool()
+                                .issue_password_reset(&id)
+                                .await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+    }
+}
+
+async fn issue(
+    module: &AppModule,
+    user_id: &UserId,
+) -> error_stack::Result<TokenPair, KernelError> {
+    let generation = module
+        .handler()
+        .redis_pool()
+        .token_generation(user_id)
+        .await?;
+    module.jwt().issue(user_id, generation)
+}
+
+/// Refresh tokens are single use, each refresh revokes the presented one.
+/// Of concurrent refreshes with the same token only the one that revokes it wins
+async fn refresh(
+    module: AppModule,
+    token: String,
+) -> error_stack::Result<Option<TokenPair>, KernelError> {
+    let Ok(claims) = module.jwt().verify(&token, TokenKind::Refresh) else {
+        return Ok(None);
+    };
+    let (Some((id, ttl)), Ok(user_id)) = (claims.revocable(), Uuid::parse_str(claims.subject()))
+    else {
+        return Ok(None);
+    };
+    if claims.is_revoked(&module).await? {
+        return Ok(None);
+    }
+    if !module
+        .handler()
+        .redis_pool()
+        .revoke_token(&id, &ttl)
+        .await?
+    {
+        return Ok(None);
+    }
+    let user = module
+        .handler()
+        .pgpool()
+        .get_user(&GetUserDto {
+            id: UserId::new(user_id),
+        })
+        .await?
+        .filter(|user| !user.is_deleted().as_ref());
+    match user {
+        Some(user) => issue(&module, user.id()).await.map(Some),
+        None => Ok(None),
+    }
+}
@@ -1,18 +1,22 @@
 use crate::controller::Controller;
 use crate::error::ErrorStatus;
 use crate::handler::AppModule;
+use crate::middleware::Principal;
 use crate::request::{
     BookTransformer, CreateBookRequest, DeleteBookRequest, GetAllBookRequest, GetBookRequest,
-    GetRentsRequest, UpdateBookRequest,
+    GetRentsRequest, IfMatch, RequestMetadata, UpdateBookRequest,
 };
 use crate::response::{BookPresenter, BookResponse, RentPresenter};
+use application::policy::Permission;
 use application::service::{GetBookService, GetRentService, HandleBookService};
+use application::transfer::GetBookDto;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
 use kernel::interface::mq::MessageQueue;
+use kernel::prelude::entity::BookId;
 use uuid::Uuid;
 
 pub trait BookRouter {
@@ -33,10 +37,21 @@ impl BookRouter for Router<AppModule> {
                 },
             )
             .post(
-                |State(module): State<AppModule>, Json(req): Json<CreateBookRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 metadata: RequestMetadata,
+                 Json(req): Json<CreateBookRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageBooks)
+                        .map_err(ErrorStatus::from)?;
                     Controller::new(BookTransformer, BookPresenter)
-                        .intake(req)
-                        .handle(|event| module.handler().pgpool().handle_book_event(event))
+                        .intake((req, metadata.into()))
+                        .handle(|(event, metadata)| {
+                            module
+                                .handler()
+                                .pgpool()
+                                .handle_book_event(event, None, metadata)
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
@@ -59,20 +74,71 @@ impl BookRouter for Router<AppModule> {
             )
             .patch(
                 |State(module): State<AppModule>,
+                 principal: Principal,
                  Path(id): Path<Uuid>,
+                 if_match: IfMatch,
+                 metadata: RequestMetadata,
                  Json(req): Json<UpdateBookRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageBooks)
+                        .map_err(ErrorStatus::from)?;
+                    if let Some(version) = if_match.version() {
+                        module
+                            .handler()
+                            .pgpool()
+                            .check_book_version(
+                                &GetBookDto {
+                                    id: BookId::new(id),
+                                },
+                                &version,
+                            )
+                            .await
+                            .map_err(ErrorStatus::from)?;
+                    }
                     Controller::new(BookTransformer, BookPresenter)
-                        .intake((id, req))
-                        .handle(|info| async move { module.worker().command().queue(&info).await })
+                        .intake((id, req, if_match, metadata.into()))
+                        .handle(|info| async move {
+                            module
+                                .worker()
+                                .command()
+                                .queue(&principal.submit(info))
+                                .await
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
             )
             .delete(
-                |State(module): State<AppModule>, Path(id): Path<Uuid>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 if_match: IfMatch,
+                 metadata: RequestMetadata| async move {
+                    principal
+                        .authorize(Permission::ManageBooks)
+                        .map_err(ErrorStatus::from)?;
+                    if let Some(version) = if_match.version() {
+                        module
+                            .handler()
+                            .pgpool()
+                            .check_book_version(
+                                &GetBookDto {
+                                    id: BookId::new(id),
+                                },
+                                &version,
+                            )
+                            .await
+                            .map_err(ErrorStatus::from)?;
+                    }
                     Controller::new(BookTransformer, BookPresenter)
-                        .intake(DeleteBookRequest::new(id))
-                        .handle(|info| async move { module.worker().command().queue(&info).await })
+                        .intake((DeleteBookRequest::new(id), if_match, metadata.into()))
+                        .handle(|info| async move {
+                            module
+                                .worker()
+                                .command()
+                                .queue(&principal.submit(info))
+                                .await
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
@@ -0,0 +1,78 @@
+use crate::controller::Controller;
+use crate::handler::AppModule;
+use crate::response::{ComponentHealth, HealthPresenter, HealthReport};
+use axum::extract::State;
+use axum::routing::get;
+use axum::Router;
+use kernel::interface::database::{DatabaseConnection, Transaction};
+use kernel::interface::mq::MessageQueue;
+use std::convert::Infallible;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+use tracing::warn;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+pub trait HealthRouter {
+    fn route_health(self) -> Self;
+}
+
+impl HealthRouter for Router<AppModule> {
+    fn route_health(self) -> Self {
+        self.route(
+            "/health/live",
+            get(|State(module): State<AppModule>| async move {
+                // Liveness only fails on problems a restart can fix, so external dependencies are not probed here
+                Controller::new((), HealthPresenter)
+                    .bypass(|| async move {
+                        Ok::<_, Infallible>(HealthReport {
+                            postgres: None,
+                            redis: None,
+                            command_worker: module.worker().command().get_worker_states(),
+                        })
+                    })
+                    .await
+            }),
+        )
+        .route(
+            "/health/ready",
+            get(|State(module): State<AppModule>| async move {
+                Controller::new((), HealthPresenter)
+                    .bypass(|| async move {
+                        let (postgres, redis) = tokio::join!(
+                            probe("postgres", module.handler().pgpool()),
+                            probe("redis", module.handler().redis_pool())
+                        );
+                        Ok::<_, Infallible>(HealthReport {
+                            postgres: Some(postgres),
+                            redis: Some(redis),
+                            command_worker: module.worker().command().get_worker_states(),
+                        })
+                    })
+                    .await
+            }),
+        )
+    }
+}
+
+async fn probe<D: DatabaseConnection>(component: &str, db: &D) -> ComponentHealth {
+    let start = Instant::now();
+    let result = timeout(PROBE_TIMEOUT, async {
+        let con = db.transact().await?;
+        con.commit().await
+    })
+    .await;
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let error = match result {
+        Ok(Ok(())) => None,
+        Ok(Err(report)) => {
+            warn!("Health probe for {component} failed: {report:?}");
+            Some(report.current_context().to_string())
+        }
+        Err(_) => {
+            warn!("Health probe for {component} timed out");
+            Some(format!("Timed out after {}s", PROBE_TIMEOUT.as_secs()))
+        }
+    };
+    ComponentHealth::new(latency_ms, error)
+}
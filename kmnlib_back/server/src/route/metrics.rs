@@ -0,0 +1,77 @@
+use crate::controller::Controller;
+use crate::handler::AppModule;
+use crate::response::MetricsPresenter;
+use axum::extract::State;
+use axum::routing::get;
+use axum::Router;
+use std::convert::Infallible;
+use tracing::warn;
+
+pub trait MetricsRouter {
+    fn route_metrics(self) -> Self;
+}
+
+impl MetricsRouter for Router<AppModule> {
+    fn route_metrics(self) -> Self {
+        self.route(
+            "/metrics",
+            get(|State(module): State<AppModule>| async move {
+                Controller::new((), MetricsPresenter)
+                    .bypass(|| async move {
+                        // Gauges are sampled on scrape instead of being kept up to date in the background
+                        module.handler().pgpool().record_pool_metrics();
+                        module.handler().redis_pool().record_pool_metrics();
+                        if let Err(report) = module.worker().command().record_depth_metrics().await
+                        {
+                            warn!("Failed to record queue depth: {report:?}");
+                        }
+                        Ok::<_, Infallible>(module.metrics().render())
+                    })
+                    .await
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::middleware::from_fn;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use crate::handler::AppModule;
+    use crate::middleware::{metrics_builder, track_http_metrics};
+    use crate::route::MetricsRouter;
+
+    #[test_with::env(POSTGRES_TEST, REDIS_TEST)]
+    #[tokio::test]
+    async fn test_scrape() {
+        let recorder = metrics_builder().unwrap().build_recorder();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+        let module = AppModule::new(recorder.handle()).await.unwrap();
+        let router = Router::new()
+            .route_metrics()
+            .route_layer(from_fn(track_http_metrics))
+            .with_state(module);
+
+        let scrape = || Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(scrape()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router.oneshot(scrape()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        // The first scrape is counted by the time the second one renders
+        let labels = r#"method="GET",path="/metrics",status="200""#;
+        assert!(body.contains(&format!("http_requests_total{{{labels}}} 1")));
+        assert!(body.contains(&format!(
+            "http_request_duration_seconds_count{{{labels}}} 1"
+        )));
+        assert!(body.contains(r#"db_pool_connections{pool="postgres",state="max"}"#));
+        assert!(body.contains(r#"db_pool_connections{pool="redis",state="max"}"#));
+        assert!(body.contains(r#"mq_queue_depth{queue="command_worker",state="waiting"}"#));
+    }
+}
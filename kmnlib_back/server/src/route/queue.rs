@@ -1,17 +1,25 @@
 use crate::controller::Controller;
 use crate::error::ErrorStatus;
 use crate::handler::AppModule;
+use crate::middleware::Principal;
 use crate::request::{
-    InfoLengthRequest, InfoLengthTarget, InfoRequest, InfoRequestBody, InfoTarget, InfosRequest,
-    QueueTransformer,
+    InfoLengthRequest, InfoLengthTarget, InfoRequest, InfoRequestBody, InfoTarget,
+    InfosFilterRequest, InfosRequest, PurgeInfosRequest, QuarantinedInfosRequest, QueueTransformer,
 };
-use crate::response::{InfoResponse, QueuePresenter};
+use crate::response::{
+    AcceptedJobResponse, AffectedInfos, InfoResponse, JobStatusResponse, QuarantinedInfoResponse,
+    QueuePresenter,
+};
+use application::policy::Permission;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::get;
+use axum::routing::{get, post};
 use axum::Router;
-use kernel::interface::mq::MessageQueue;
+use error_stack::Report;
+use kernel::interface::mq::{ErroredInfoFilter, InfoSort, MessageQueue};
+use kernel::prelude::entity::UserId;
+use kernel::KernelError;
 use uuid::Uuid;
 
 pub trait QueueRouter {
@@ -23,7 +31,12 @@ impl QueueRouter for Router<AppModule> {
         self.route(
             "/queue/infos",
             get(
-                |State(module): State<AppModule>, Query(req): Query<InfosRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Query(req): Query<InfosRequest>| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
                     Controller::new(QueueTransformer, QueuePresenter)
                         .intake(req)
                         .try_handle(
@@ -31,20 +44,31 @@ impl QueueRouter for Router<AppModule> {
                                  target,
                                  size,
                                  offset,
+                                 error,
+                                 data,
+                                 kind,
+                                 from,
+                                 to,
+                                 sort,
+                                 order,
                              }| async move {
+                                let filter = ErroredInfoFilter::new(error, data)
+                                    .with_kind(kind)
+                                    .with_window(from, to);
+                                let sort = sort.map(|key| InfoSort::new(key, order));
                                 match target {
                                     InfoTarget::Delayed => {
                                         module
                                             .worker()
                                             .command()
-                                            .get_delayed_infos(&size, &offset)
+                                            .get_delayed_infos(&filter, &sort, &size, &offset)
                                             .await
                                     }
                                     InfoTarget::Failed => {
                                         module
                                             .worker()
                                             .command()
-                                            .get_failed_infos(&size, &offset)
+                                            .get_failed_infos(&filter, &sort, &size, &offset)
                                             .await
                                     }
                                 }
@@ -53,14 +77,122 @@ impl QueueRouter for Router<AppModule> {
                         .await
                         .map_err(ErrorStatus::from)
                 },
+            )
+            .delete(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 PurgeInfosRequest(req): PurgeInfosRequest| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(req)
+                        .try_handle(
+                            |InfosFilterRequest {
+                                 target,
+                                 error,
+                                 data,
+                                 kind,
+                                 from,
+                                 to,
+                                 ..
+                             }| async move {
+                                let filter = ErroredInfoFilter::new(error, data)
+                                    .with_kind(kind)
+                                    .with_window(from, to);
+                                let command = module.worker().command();
+                                match target {
+                                    InfoTarget::Delayed => {
+                                        command.purge_delayed_infos(&filter).await
+                                    }
+                                    InfoTarget::Failed => command.purge_failed_infos(&filter).await,
+                                }
+                                .map(AffectedInfos)
+                            },
+                        )
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/infos/retry",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Query(req): Query<InfosFilterRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(req)
+                        .try_handle(
+                            |InfosFilterRequest {
+                                 target,
+                                 error,
+                                 data,
+                                 kind,
+                                 from,
+                                 to,
+                                 ..
+                             }| async move {
+                                let filter = ErroredInfoFilter::new(error, data)
+                                    .with_kind(kind)
+                                    .with_window(from, to);
+                                let command = module.worker().command();
+                                match target {
+                                    InfoTarget::Delayed => {
+                                        command.retry_delayed_infos(&filter).await
+                                    }
+                                    InfoTarget::Failed => command.retry_failed_infos(&filter).await,
+                                }
+                                .map(AffectedInfos)
+                            },
+                        )
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/infos/:id/retry",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 Query(req): Query<InfoRequestBody>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(InfoRequest::new(id, req.target))
+                        .try_handle(|InfoRequest { id, target }| async move {
+                            let command = module.worker().command();
+                            match target {
+                                InfoTarget::Delayed => command.retry_delayed(&id).await,
+                                InfoTarget::Failed => command.retry_failed(&id).await,
+                            }
+                            .map(|retried| retried.then_some(id))
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(AcceptedJobResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
             ),
         )
         .route(
             "/queue/infos/:id",
             get(
                 |State(module): State<AppModule>,
+                 principal: Principal,
                  Path(id): Path<Uuid>,
                  Query(req): Query<InfoRequestBody>| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
                     Controller::new(QueueTransformer, QueuePresenter)
                         .intake(InfoRequest::new(id, req.target))
                         .try_handle(|InfoRequest { id, target }| async move {
@@ -80,18 +212,214 @@ impl QueueRouter for Router<AppModule> {
                                 .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
                         })
                 },
+            )
+            .delete(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 Query(req): Query<InfoRequestBody>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(InfoRequest::new(id, req.target))
+                        .try_handle(|InfoRequest { id, target }| async move {
+                            let command = module.worker().command();
+                            match target {
+                                InfoTarget::Delayed => command.discard_delayed(&id).await,
+                                InfoTarget::Failed => command.discard_failed(&id).await,
+                            }
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/quarantine",
+            get(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Query(req): Query<QuarantinedInfosRequest>| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(req)
+                        .try_handle(|QuarantinedInfosRequest { size, offset }| async move {
+                            module
+                                .worker()
+                                .command()
+                                .get_quarantined_infos(&size, &offset)
+                                .await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/quarantine/:id",
+            get(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(id)
+                        .try_handle(|id| async move {
+                            module.worker().command().get_quarantined_info(&id).await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(QuarantinedInfoResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
+            )
+            .delete(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(id)
+                        .try_handle(|id| async move {
+                            module.worker().command().discard_quarantined(&id).await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/quarantine/:id/retry",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(id)
+                        .try_handle(|id| async move {
+                            module.worker().command().retry_quarantined(&id).await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(AcceptedJobResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
+            ),
+        )
+        .route(
+            "/queue/jobs/:id",
+            get(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>| async move {
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(id)
+                        .try_handle(|id| async move {
+                            let worker = module.worker();
+                            let status = match worker.command().get_job_status(&id).await? {
+                                Some(status) => Some(status),
+                                None => worker.maintenance().get_job_status(&id).await?,
+                            };
+                            if let Some(status) = &status {
+                                let submitter = status.submitter().map(UserId::new);
+                                principal.authorize(Permission::ViewJob {
+                                    submitter: submitter.as_ref(),
+                                })?;
+                            }
+                            Ok::<_, Report<KernelError>>(status)
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(JobStatusResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
+            ),
+        )
+        .route(
+            "/queue/schedules",
+            get(
+                |State(module): State<AppModule>, principal: Principal| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(())
+                        .try_handle(|_| async move {
+                            module.worker().maintenance().get_schedules().await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/queue/schedules/:name/trigger",
+            post(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(name): Path<String>| async move {
+                    principal
+                        .authorize(Permission::ManageQueue)
+                        .map_err(ErrorStatus::from)?;
+                    Controller::new(QueueTransformer, QueuePresenter)
+                        .intake(name)
+                        .try_handle(|name| async move {
+                            module
+                                .worker()
+                                .maintenance()
+                                .trigger_schedule(&name)
+                                .await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                        .map(|res| {
+                            res.map(AcceptedJobResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::NOT_FOUND.into_response())
+                        })
+                },
             ),
         )
         .route(
             "/queue/infos/len",
             get(
-                |State(module): State<AppModule>, Query(req): Query<InfoLengthRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Query(req): Query<InfoLengthRequest>| async move {
+                    principal
+                        .authorize(Permission::ViewQueue)
+                        .map_err(ErrorStatus::from)?;
                     Controller::new(QueueTransformer, QueuePresenter)
                         .intake(req)
-                        .try_handle(|InfoLengthRequest { target }| async move {
+                        .try_handle(|InfoLengthRequest { target, priority }| async move {
                             match target {
-                                InfoLengthTarget::Queued => {
-                                    module.worker().command().get_queued_len().await
+                                InfoLengthTarget::Queued => match priority {
+                                    Some(priority) => {
+                                        module
+                                            .worker()
+                                            .command()
+                                            .get_queued_len_by_priority(&priority)
+                                            .await
+                                    }
+                                    None => module.worker().command().get_queued_len().await,
+                                },
+                                InfoLengthTarget::Scheduled => {
+                                    module.worker().command().get_scheduled_len().await
                                 }
                                 InfoLengthTarget::Delayed => {
                                     module.worker().command().get_delayed_len().await
@@ -99,6 +427,9 @@ impl QueueRouter for Router<AppModule> {
                                 InfoLengthTarget::Failed => {
                                     module.worker().command().get_failed_len().await
                                 }
+                                InfoLengthTarget::Quarantined => {
+                                    module.worker().command().get_quarantined_len().await
+                                }
                             }
                         })
                         .await
@@ -1,12 +1,21 @@
 use crate::controller::Controller;
 use crate::error::ErrorStatus;
 use crate::handler::AppModule;
-use crate::request::{RentRequest, RentTransformer, ReturnRequest};
-use crate::response::RentPresenter;
+use crate::middleware::Principal;
+use crate::mq::CommandOperation;
+use crate::request::{RentRequest, RentTransformer, RequestMetadata, ReturnRequest};
+use crate::response::{AcceptedJobResponse, RentPresenter};
+use application::policy::Permission;
 use application::service::HandleRentService;
 use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
 use axum::routing::post;
 use axum::Router;
+use kernel::interface::event::{EventMetadata, RentEvent};
+use kernel::interface::mq::MessageQueue;
+use kernel::KernelError;
+use uuid::Uuid;
 
 pub trait RentRouter {
     fn route_rent(self) -> Self;
@@ -17,23 +26,69 @@ impl RentRouter for Router<AppModule> {
         self.route(
             "/rents",
             post(
-                |State(module): State<AppModule>, Query(req): Query<RentRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 metadata: RequestMetadata,
+                 Query(req): Query<RentRequest>| async move {
+                    let queued = req.queued();
                     Controller::new(RentTransformer, RentPresenter)
-                        .intake(req)
-                        .handle(|event| module.handler().pgpool().handle_rent_event(event))
+                        .intake((req, metadata.into()))
+                        .handle(|(event, metadata)| {
+                            handle_rent(module, principal, event, metadata, queued)
+                        })
                         .await
+                        .map(|res| {
+                            res.map(AcceptedJobResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::OK.into_response())
+                        })
                         .map_err(ErrorStatus::from)
                 },
             )
             .delete(
-                |State(module): State<AppModule>, Query(req): Query<ReturnRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 metadata: RequestMetadata,
+                 Query(req): Query<ReturnRequest>| async move {
+                    let queued = req.queued();
                     Controller::new(RentTransformer, RentPresenter)
-                        .intake(req)
-                        .handle(|event| module.handler().pgpool().handle_rent_event(event))
+                        .intake((req, metadata.into()))
+                        .handle(|(event, metadata)| {
+                            handle_rent(module, principal, event, metadata, queued)
+                        })
                         .await
+                        .map(|res| {
+                            res.map(AcceptedJobResponse::into_response)
+                                .unwrap_or_else(|| StatusCode::OK.into_response())
+                        })
                         .map_err(ErrorStatus::from)
                 },
             ),
         )
     }
 }
+
+async fn handle_rent(
+    module: AppModule,
+    principal: Principal,
+    event: RentEvent,
+    metadata: EventMetadata,
+    queued: bool,
+) -> error_stack::Result<Option<Uuid>, KernelError> {
+    let (RentEvent::Rent { user_id, .. } | RentEvent::Return { user_id, .. }) = &event;
+    principal.authorize(Permission::Rent { user_id })?;
+    if !queued {
+        module
+            .handler()
+            .pgpool()
+            .handle_rent_event(event, metadata)
+            .await?;
+        return Ok(None);
+    }
+    let info = CommandOperation::rent(event, metadata).into_queue_info();
+    module
+        .worker()
+        .command()
+        .queue(&principal.submit(info))
+        .await
+        .map(Some)
+}
@@ -1,18 +1,22 @@
 use crate::controller::Controller;
 use crate::error::ErrorStatus;
 use crate::handler::AppModule;
+use crate::middleware::Principal;
 use crate::request::{
-    CreateUserRequest, DeleteUserRequest, GetAllUserRequest, GetRentsRequest, GetUserRequest,
-    UpdateUserRequest, UserTransformer,
+    ChangeRoleRequest, CreateUserRequest, DeleteUserRequest, GetAllUserRequest, GetRentsRequest,
+    GetUserRequest, IfMatch, RequestMetadata, UpdateUserRequest, UserTransformer,
 };
 use crate::response::{RentPresenter, UserPresenter, UserResponse};
+use application::policy::Permission;
 use application::service::{GetRentService, GetUserService, HandleUserService};
+use application::transfer::GetUserDto;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
-use axum::routing::get;
+use axum::routing::{get, put};
 use axum::{Json, Router};
 use kernel::interface::mq::MessageQueue;
+use kernel::prelude::entity::{UserId, UserRole};
 use uuid::Uuid;
 
 pub trait UserRouter {
@@ -33,10 +37,21 @@ impl UserRouter for Router<AppModule> {
                 },
             )
             .post(
-                |State(module): State<AppModule>, Json(req): Json<CreateUserRequest>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 metadata: RequestMetadata,
+                 Json(req): Json<CreateUserRequest>| async move {
+                    principal
+                        .authorize(Permission::ManageUsers { target: None })
+                        .map_err(ErrorStatus::from)?;
                     Controller::new(UserTransformer, UserPresenter)
-                        .intake(req)
-                        .handle(|event| module.handler().pgpool().handle_user_event(event))
+                        .intake((req, metadata.into()))
+                        .handle(|(event, metadata)| {
+                            module
+                                .handler()
+                                .pgpool()
+                                .handle_user_event(event, None, metadata)
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
@@ -59,20 +74,112 @@ impl UserRouter for Router<AppModule> {
             )
             .patch(
                 |State(module): State<AppModule>,
+                 principal: Principal,
                  Path(id): Path<Uuid>,
+                 if_match: IfMatch,
+                 metadata: RequestMetadata,
                  Json(req): Json<UpdateUserRequest>| async move {
+                    let target = target_role(&module, id).await?;
+                    principal
+                        .authorize(Permission::ManageUsers { target })
+                        .map_err(ErrorStatus::from)?;
+                    if let Some(version) = if_match.version() {
+                        module
+                            .handler()
+                            .pgpool()
+                            .check_user_version(
+                                &GetUserDto {
+                                    id: UserId::new(id),
+                                },
+                                &version,
+                            )
+                            .await
+                            .map_err(ErrorStatus::from)?;
+                    }
                     Controller::new(UserTransformer, UserPresenter)
-                        .intake((id, req))
-                        .handle(|info| async move { module.worker().command().queue(&info).await })
+                        .intake((id, req, if_match, metadata.into()))
+                        .handle(|info| async move {
+                            module
+                                .worker()
+                                .command()
+                                .queue(&principal.submit(info))
+                                .await
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
             )
             .delete(
-                |State(module): State<AppModule>, Path(id): Path<Uuid>| async move {
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 if_match: IfMatch,
+                 metadata: RequestMetadata| async move {
+                    let target = target_role(&module, id).await?;
+                    principal
+                        .authorize(Permission::ManageUsers { target })
+                        .map_err(ErrorStatus::from)?;
+                    if let Some(version) = if_match.version() {
+                        module
+                            .handler()
+                            .pgpool()
+                            .check_user_version(
+                                &GetUserDto {
+                                    id: UserId::new(id),
+                                },
+                                &version,
+                            )
+                            .await
+                            .map_err(ErrorStatus::from)?;
+                    }
+                    Controller::new(UserTransformer, UserPresenter)
+                        .intake((DeleteUserRequest::new(id), if_match, metadata.into()))
+                        .handle(|info| async move {
+                            module
+                                .worker()
+                                .command()
+                                .queue(&principal.submit(info))
+                                .await
+                        })
+                        .await
+                        .map_err(ErrorStatus::from)
+                },
+            ),
+        )
+        .route(
+            "/users/:id/role",
+            put(
+                |State(module): State<AppModule>,
+                 principal: Principal,
+                 Path(id): Path<Uuid>,
+                 if_match: IfMatch,
+                 metadata: RequestMetadata,
+                 Json(req): Json<ChangeRoleRequest>| async move {
+                    principal
+                        .authorize(Permission::ChangeRole)
+                        .map_err(ErrorStatus::from)?;
+                    if let Some(version) = if_match.version() {
+                        module
+                            .handler()
+                            .pgpool()
+                            .check_user_version(
+                                &GetUserDto {
+                                    id: UserId::new(id),
+                                },
+                                &version,
+                            )
+                            .await
+                            .map_err(ErrorStatus::from)?;
+                    }
                     Controller::new(UserTransformer, UserPresenter)
-                        .intake(DeleteUserRequest::new(id))
-                        .handle(|info| async move { module.worker().command().queue(&info).await })
+                        .intake((id, req, if_match, metadata.into()))
+                        .handle(|info| async move {
+                            module
+                                .worker()
+                                .command()
+                                .queue(&principal.submit(info))
+                                .await
+                        })
                         .await
                         .map_err(ErrorStatus::from)
                 },
@@ -94,3 +201,19 @@ impl UserRouter for Router<AppModule> {
         )
     }
 }
+
+/// Role of the account with `id`, `None` if there is none
+pub(crate) async fn target_role(
+    module: &AppModule,
+    id: Uuid,
+) -> Result<Option<UserRole>, ErrorStatus> {
+    module
+        .handler()
+        .pgpool()
+        .get_user(&GetUserDto {
+            id: UserId::new(id),
+        })
+        .await
+        .map(|user| user.map(|user| *user.role()))
+        .map_err(ErrorStatus::from)
+}
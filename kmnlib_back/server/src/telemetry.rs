@@ -0,0 +1,38 @@
+use error_stack::ResultExt;
+use kernel::KernelError;
+use opentelemetry::global;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{config, Tracer};
+use opentelemetry_sdk::{runtime, Resource};
+
+const DEFAULT_SERVICE_NAME: &str = "kmnlib";
+
+/// Returns `None` when `OTEL_EXPORTER_OTLP_ENDPOINT` is not set so that local runs work without a collector
+pub fn init_tracer() -> error_stack::Result<Option<Tracer>, KernelError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let Ok(endpoint) = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT") else {
+        return Ok(None);
+    };
+    let service_name =
+        std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            config().with_resource(Resource::new([KeyValue::new("service.name", service_name)])),
+        )
+        .install_batch(runtime::Tokio)
+        .change_context_lazy(|| KernelError::Internal)
+        .attach_printable_lazy(|| "Failed to install OTLP tracer")?;
+    Ok(Some(tracer))
+}
+
+pub fn shutdown_tracer() {
+    global::shutdown_tracer_provider();
+}